@@ -3,7 +3,7 @@ CREATE TYPE CREDENTIAL AS (
     password TEXT
 );
 
-CREATE TYPE STATUS as ENUM ('Reserved', 'Unknown', 'Online', 'Offline');
+CREATE TYPE STATUS as ENUM ('Reserved', 'Unknown', 'Online', 'Offline', 'Allocated', 'Deprecated', 'Quarantined', 'DHCP');
 
 CREATE TABLE IF NOT EXISTS networks (
     id UUID PRIMARY KEY,
@@ -12,29 +12,162 @@ CREATE TABLE IF NOT EXISTS networks (
     used INTEGER NOT NULL,
     total INTEGER NOT NULL,
     vlan INTEGER,
-    description VARCHAR
+    description VARCHAR,
+    gateway VARCHAR,
+    infrastructure JSONB NOT NULL DEFAULT '[]',
+    deleted_at TIMESTAMPTZ,
+    deleted_by UUID
 );
 
 CREATE TABLE IF NOT EXISTS offices (
     id UUID PRIMARY KEY,
+    name VARCHAR NOT NULL,
     description VARCHAR,
-    address VARCHAR UNIQUE
+    address VARCHAR UNIQUE,
+    deleted_at TIMESTAMPTZ,
+    deleted_by UUID
 );
 
 CREATE TABLE IF NOT EXISTS devices (
-    ip VARCHAR NOT NULL,
+    id UUID PRIMARY KEY,
+    hostname VARCHAR,
+    domain VARCHAR,
+    kind VARCHAR,
+    model VARCHAR,
+    serial VARCHAR,
     description VARCHAR,
     office_id UUID,
     rack VARCHAR,
     room VARCHAR,
-    status STATUS NOT NULL,
-    network_id UUID NOT NULL,
     credential CREDENTIAL,
+    deleted_at TIMESTAMPTZ,
+    deleted_by UUID,
+    FOREIGN KEY (office_id) REFERENCES offices(id) ON DELETE SET NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS devices_fqdn ON devices (hostname, COALESCE(domain, '')) WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS addresses (
+    ip VARCHAR NOT NULL,
+    network_id UUID NOT NULL,
+    device_id UUID,
+    status STATUS NOT NULL,
+    mac VARCHAR,
+    last_seen TIMESTAMPTZ,
+    reserved_until TIMESTAMPTZ,
+    deleted_at TIMESTAMPTZ,
+    deleted_by UUID,
     PRIMARY KEY (ip, network_id),
     FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE,
-    FOREIGN KEY (office_id) REFERENCES offices(id) ON DELETE SET NULL
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS addresses_device ON addresses (device_id);
+CREATE UNIQUE INDEX IF NOT EXISTS addresses_mac ON addresses (network_id, mac) WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS dhcp_scopes (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL,
+    range_start VARCHAR NOT NULL,
+    range_end VARCHAR NOT NULL,
+    gateway VARCHAR,
+    dns_servers JSONB NOT NULL,
+    lease_time BIGINT NOT NULL,
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS dhcp_reservations (
+    ip VARCHAR NOT NULL,
+    network_id UUID NOT NULL,
+    device_id UUID NOT NULL,
+    PRIMARY KEY (ip, network_id),
+    FOREIGN KEY (ip, network_id) REFERENCES addresses(ip, network_id) ON DELETE CASCADE,
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS device_services (
+    device_id UUID NOT NULL,
+    ip VARCHAR NOT NULL,
+    network_id UUID NOT NULL,
+    port INTEGER NOT NULL,
+    name VARCHAR,
+    seen TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (device_id, ip, network_id, port),
+    FOREIGN KEY (device_id) REFERENCES devices(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS scan_schedules (
+    network_id UUID PRIMARY KEY,
+    interval BIGINT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_run TIMESTAMPTZ,
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS scan_results (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ NOT NULL,
+    scanned BIGINT NOT NULL,
+    online BIGINT NOT NULL,
+    offline BIGINT NOT NULL,
+    alive JSONB NOT NULL,
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS scan_results_network_idx ON scan_results (network_id, started_at DESC);
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    id UUID PRIMARY KEY,
+    url VARCHAR NOT NULL,
+    secret VARCHAR NOT NULL,
+    entities JSONB NOT NULL DEFAULT '[]',
+    actions JSONB NOT NULL DEFAULT '[]',
+    network_id UUID,
+    office_id UUID,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY,
+    webhook_id UUID NOT NULL,
+    event JSONB NOT NULL,
+    attempts BIGINT NOT NULL,
+    status BIGINT,
+    error VARCHAR,
+    delivered BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    last_attempt TIMESTAMPTZ,
+    FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_idx ON webhook_deliveries (webhook_id, created_at DESC);
+
+CREATE OR REPLACE VIEW device_view AS
+    SELECT
+        a.ip,
+        a.network_id,
+        a.status,
+        a.device_id,
+        a.mac,
+        a.last_seen,
+        a.reserved_until,
+        d.hostname,
+        d.domain,
+        d.kind,
+        d.model,
+        d.serial,
+        d.description,
+        d.office_id,
+        d.rack,
+        d.room,
+        d.credential,
+        a.deleted_at,
+        a.deleted_by
+    FROM addresses a
+    LEFT JOIN devices d ON d.id = a.device_id;
+
 CREATE TYPE ROLE AS ENUM ('Admin', 'Operator', 'Guest');
 
 CREATE TABLE IF NOT EXISTS users (
@@ -42,4 +175,41 @@ CREATE TABLE IF NOT EXISTS users (
     username VARCHAR UNIQUE,
     password TEXT,
     role ROLE
-);
\ No newline at end of file
+);
+
+CREATE TYPE ENTITY AS ENUM ('Network', 'Device', 'Address', 'User', 'Office', 'Scope', 'Reservation', 'Schedule', 'Webhook');
+
+CREATE TYPE ACTION AS ENUM ('Insert', 'Update', 'Delete', 'Restore');
+
+CREATE TABLE IF NOT EXISTS audit (
+    id UUID PRIMARY KEY,
+    actor UUID,
+    timestamp TIMESTAMPTZ NOT NULL,
+    entity ENTITY NOT NULL,
+    action ACTION NOT NULL,
+    key JSONB NOT NULL,
+    before JSONB,
+    after JSONB
+);
+
+CREATE INDEX IF NOT EXISTS audit_entity_key ON audit (entity, key);
+CREATE INDEX IF NOT EXISTS audit_timestamp ON audit (timestamp);
+
+CREATE TABLE IF NOT EXISTS ip_history (
+    id UUID PRIMARY KEY,
+    ip VARCHAR NOT NULL,
+    network_id UUID NOT NULL,
+    device_id UUID NOT NULL,
+    device JSONB NOT NULL,
+    assigned_from TIMESTAMPTZ NOT NULL,
+    assigned_to TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS ip_history_ip ON ip_history (ip, assigned_from);
+CREATE INDEX IF NOT EXISTS ip_history_device ON ip_history (device_id, assigned_from);
+
+CREATE TABLE IF NOT EXISTS dns_zones (
+    zone VARCHAR PRIMARY KEY,
+    serial BIGINT NOT NULL,
+    content TEXT NOT NULL
+);
@@ -0,0 +1,58 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    RepositoryInjection,
+};
+use crate::models::audit::{Audit, AuditFilter};
+use sqlx::Postgres;
+
+impl RepositoryInjection<Postgres> {
+    pub async fn get_audit(&self, filter: AuditFilter) -> Result<Vec<Audit>, RepositoryError> {
+        let mut query = format!("SELECT * FROM {}", Audit::name());
+        let mut conditions = Vec::new();
+        let mut values: Vec<TypeTable> = Vec::new();
+
+        if let Some(entity) = filter.entity {
+            values.push(entity.into());
+            conditions.push(format!("entity = ${}", values.len()));
+        }
+
+        if let Some(actor) = filter.actor {
+            values.push(actor.into());
+            conditions.push(format!("actor = ${}", values.len()));
+        }
+
+        if let Some(key) = filter.key {
+            values.push(key.into());
+            conditions.push(format!("key = ${}", values.len()));
+        }
+
+        if let Some(since) = filter.since {
+            values.push(since.into());
+            conditions.push(format!("timestamp >= ${}", values.len()));
+        }
+
+        if let Some(until) = filter.until {
+            values.push(until.into());
+            conditions.push(format!("timestamp <= ${}", values.len()));
+        }
+
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        query.push_str(" ORDER BY timestamp");
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(Audit::from)
+            .collect())
+    }
+}
@@ -0,0 +1,73 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    RepositoryInjection,
+};
+use crate::models::device::{DeviceFilter, DeviceView};
+use sqlx::Postgres;
+
+impl RepositoryInjection<Postgres> {
+    pub async fn search_devices(
+        &self,
+        filter: DeviceFilter,
+    ) -> Result<Vec<DeviceView>, RepositoryError> {
+        let mut query = format!(
+            "SELECT * FROM {} WHERE deleted_at IS NULL",
+            DeviceView::name()
+        );
+        let mut values: Vec<TypeTable> = Vec::new();
+
+        if let Some(network_id) = filter.network_id {
+            values.push(network_id.into());
+            query.push_str(&format!(" AND network_id = ${}", values.len()));
+        }
+
+        if let Some(hostname) = filter.hostname {
+            values.push(format!("%{}%", hostname).into());
+            query.push_str(&format!(" AND hostname ILIKE ${}", values.len()));
+        }
+
+        if let Some(domain) = filter.domain {
+            values.push(domain.into());
+            query.push_str(&format!(" AND domain = ${}", values.len()));
+        }
+
+        if let Some(mac) = filter.mac {
+            values.push(mac.into());
+            query.push_str(&format!(" AND mac = ${}", values.len()));
+        }
+
+        if let Some(oui) = filter.oui {
+            values.push(format!("{}:%", oui).into());
+            query.push_str(&format!(" AND mac LIKE ${}", values.len()));
+        }
+        query.push_str(" ORDER BY network_id, ip");
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(DeviceView::from)
+            .collect())
+    }
+
+    pub async fn named_devices(&self) -> Result<Vec<DeviceView>, RepositoryError> {
+        let query = format!(
+            "SELECT * FROM {} WHERE deleted_at IS NULL AND hostname IS NOT NULL AND domain IS NOT NULL ORDER BY domain, hostname, ip",
+            DeviceView::name()
+        );
+        tracing::debug!("{}", query);
+
+        Ok(sqlx::query(&query)
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(DeviceView::from)
+            .collect())
+    }
+}
@@ -0,0 +1,48 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    RepositoryInjection,
+};
+use crate::models::{
+    device::{Address, Device},
+    dhcp::{Reservation, ReservationView},
+};
+use sqlx::Postgres;
+use uuid::Uuid;
+
+impl RepositoryInjection<Postgres> {
+    // Only the reservations whose device still holds the address and has a mac
+    pub async fn get_reservations(
+        &self,
+        network_id: Option<Uuid>,
+    ) -> Result<Vec<ReservationView>, RepositoryError> {
+        let mut query = format!(
+            "SELECT r.ip, r.network_id, r.device_id, a.mac, d.hostname, d.domain FROM {} r \
+             JOIN {} a ON a.ip = r.ip AND a.network_id = r.network_id AND a.device_id = r.device_id \
+             JOIN {} d ON d.id = r.device_id \
+             WHERE a.deleted_at IS NULL AND d.deleted_at IS NULL AND a.mac IS NOT NULL",
+            Reservation::name(),
+            Address::name(),
+            Device::name()
+        );
+        let mut values: Vec<TypeTable> = Vec::new();
+
+        if let Some(network_id) = network_id {
+            values.push(network_id.into());
+            query.push_str(&format!(" AND r.network_id = ${}", values.len()));
+        }
+        query.push_str(" ORDER BY r.network_id, r.ip");
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(ReservationView::from)
+            .collect())
+    }
+}
@@ -1,6 +1,9 @@
 use super::HashMap;
 use super::{Table, TypeTable, Updatable};
-use crate::models::{device::*, network::*, office::*, user::*};
+use crate::models::{
+    audit::*, device::*, dhcp::*, dns::*, ip_history::*, network::*, office::*, scan::*,
+    trash::SoftDelete, user::*, webhook::*,
+};
 
 impl Table for User {
     fn columns() -> Vec<&'static str> {
@@ -30,39 +33,137 @@ impl Table for User {
 impl Table for Device {
     fn columns() -> Vec<&'static str> {
         vec![
-            "ip",
+            "id",
+            "hostname",
+            "domain",
+            "kind",
+            "model",
+            "serial",
             "description",
             "office_id",
             "rack",
             "room",
-            "status",
-            "network_id",
             "credential",
+            "deleted_at",
+            "deleted_by",
         ]
     }
 
+    fn soft_delete() -> bool {
+        true
+    }
+
     fn name() -> String {
         String::from("devices")
     }
 
     fn query_insert() -> String {
-        format!("INSERT INTO {} (ip, network_id, description, office_id, rack, room, status, credential) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)", Self::name())
+        format!("INSERT INTO {} (id, hostname, domain, kind, model, serial, description, office_id, rack, room, credential) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)", Self::name())
     }
 
     fn get_fields(self) -> Vec<TypeTable> {
         vec![
-            self.ip.into(),
-            self.network_id.into(),
+            self.id.into(),
+            self.hostname.into(),
+            self.domain.into(),
+            self.kind.into(),
+            self.model.into(),
+            self.serial.into(),
             self.description.into(),
             self.office_id.into(),
             self.rack.into(),
             self.room.into(),
-            self.status.into(),
             self.credential.into(),
         ]
     }
 }
 
+impl Table for Address {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "ip",
+            "network_id",
+            "device_id",
+            "status",
+            "mac",
+            "last_seen",
+            "reserved_until",
+            "deleted_at",
+            "deleted_by",
+        ]
+    }
+
+    fn soft_delete() -> bool {
+        true
+    }
+
+    fn name() -> String {
+        String::from("addresses")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (ip, network_id, device_id, status, mac, last_seen, reserved_until) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.ip.into(),
+            self.network_id.into(),
+            self.device_id.into(),
+            self.status.into(),
+            self.mac.into(),
+            self.last_seen.into(),
+            self.reserved_until.into(),
+        ]
+    }
+}
+
+impl Table for DeviceView {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "ip",
+            "network_id",
+            "status",
+            "device_id",
+            "mac",
+            "last_seen",
+            "reserved_until",
+            "hostname",
+            "domain",
+            "kind",
+            "model",
+            "serial",
+            "description",
+            "office_id",
+            "rack",
+            "room",
+            "credential",
+            "deleted_at",
+            "deleted_by",
+        ]
+    }
+
+    fn soft_delete() -> bool {
+        true
+    }
+
+    fn name() -> String {
+        String::from("device_view")
+    }
+
+    // The view is read only, inserting through it only stores the address
+    fn query_insert() -> String {
+        Address::query_insert()
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        self.address().get_fields()
+    }
+}
+
 impl Table for Network {
     fn columns() -> Vec<&'static str> {
         vec![
@@ -73,16 +174,24 @@ impl Table for Network {
             "used",
             "total",
             "vlan",
+            "gateway",
+            "infrastructure",
+            "deleted_at",
+            "deleted_by",
         ]
     }
 
+    fn soft_delete() -> bool {
+        true
+    }
+
     fn name() -> String {
         String::from("networks")
     }
 
     fn query_insert() -> String {
         format!(
-            "INSERT INTO {} (id, network, available, used, total, vlan, description) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            "INSERT INTO {} (id, network, available, used, total, vlan, description, gateway, infrastructure) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
             Self::name()
         )
     }
@@ -96,6 +205,8 @@ impl Table for Network {
             self.free.into(),
             self.vlan.into(),
             self.description.into(),
+            self.gateway.map(|x| x.to_string()).into(),
+            serde_json::json!(self.infrastructure).into(),
         ]
     }
 }
@@ -107,35 +218,104 @@ impl Table for Office {
 
     fn query_insert() -> String {
         format!(
-            "INSERT INTO {} (id, description, address) VALUES ($1, $2, $3)",
+            "INSERT INTO {} (id, name, description, address) VALUES ($1, $2, $3, $4)",
             Office::name()
         )
     }
 
     fn get_fields(self) -> Vec<TypeTable> {
-        vec![self.id.into(), self.description.into(), self.address.into()]
+        vec![
+            self.id.into(),
+            self.name.into(),
+            self.description.into(),
+            self.address.into(),
+        ]
     }
 
     fn columns() -> Vec<&'static str> {
-        todo!()
+        vec![
+            "id",
+            "name",
+            "description",
+            "address",
+            "deleted_at",
+            "deleted_by",
+        ]
+    }
+
+    fn soft_delete() -> bool {
+        true
+    }
+}
+
+impl Table for Audit {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "actor",
+            "timestamp",
+            "entity",
+            "action",
+            "key",
+            "before",
+            "after",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("audit")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, actor, timestamp, entity, action, key, before, after) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.actor.into(),
+            self.timestamp.into(),
+            self.entity.into(),
+            self.action.into(),
+            self.key.into(),
+            self.before.into(),
+            self.after.into(),
+        ]
+    }
+
+    fn inserted(rows: &[Self]) {
+        crate::services::events::publish(rows);
     }
 }
 
 impl<'a> Updatable<'a> for UpdateDevice {
     fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
         let mut pair = HashMap::new();
-
-        if let Some(tmp) = self.ip {
-            pair.insert("ip", tmp.into());
+        if let Some(tmp) = self.hostname {
+            pair.insert("hostname", tmp.into());
         }
 
-        if let Some(tmp) = self.description {
-            let data = if tmp.is_empty() { Some(tmp) } else { None };
-            pair.insert("description", data.into());
+        if let Some(tmp) = self.domain {
+            pair.insert("domain", tmp.into());
         }
 
-        if let Some(tmp) = self.network_id {
-            pair.insert("network_id", tmp.into());
+        let fields = [
+            ("kind", self.kind),
+            ("model", self.model),
+            ("serial", self.serial),
+            ("description", self.description),
+            ("rack", self.rack),
+            ("room", self.room),
+        ];
+
+        for (column, tmp) in fields {
+            if let Some(tmp) = tmp {
+                let data = if tmp.is_empty() { None } else { Some(tmp) };
+                pair.insert(column, data.into());
+            }
         }
 
         if let Some(tmp) = self.office_id {
@@ -144,21 +324,7 @@ impl<'a> Updatable<'a> for UpdateDevice {
             } else {
                 Some(tmp)
             };
-            pair.insert("office", data.into());
-        }
-
-        if let Some(tmp) = self.rack {
-            let data = if tmp.is_empty() { None } else { Some(tmp) };
-            pair.insert("rack", data.into());
-        }
-
-        if let Some(tmp) = self.room {
-            let data = if tmp.is_empty() { None } else { Some(tmp) };
-            pair.insert("room", data.into());
-        }
-
-        if let Some(tmp) = self.status {
-            pair.insert("status", tmp.into());
+            pair.insert("office_id", data.into());
         }
 
         if let Some(cred) = self.credential {
@@ -197,6 +363,14 @@ impl<'a> Updatable<'a> for UpdateNetwork {
             pair.insert("vlan", data.into());
         }
 
+        if let Some(tmp) = self.gateway {
+            pair.insert("gateway", tmp.map(|x| x.to_string()).into());
+        }
+
+        if let Some(tmp) = self.infrastructure {
+            pair.insert("infrastructure", serde_json::json!(tmp).into());
+        }
+
         if !pair.is_empty() {
             Some(pair)
         } else {
@@ -205,9 +379,112 @@ impl<'a> Updatable<'a> for UpdateNetwork {
     }
 }
 
+impl<'a> Updatable<'a> for Network {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("network", self.network.into()),
+            ("description", self.description.into()),
+            ("available", self.available.into()),
+            ("used", self.used.into()),
+            ("total", self.free.into()),
+            ("vlan", self.vlan.into()),
+            ("gateway", self.gateway.map(|x| x.to_string()).into()),
+            (
+                "infrastructure",
+                serde_json::json!(self.infrastructure).into(),
+            ),
+        ]))
+    }
+}
+
+impl<'a> Updatable<'a> for Device {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("hostname", self.hostname.into()),
+            ("domain", self.domain.into()),
+            ("kind", self.kind.into()),
+            ("model", self.model.into()),
+            ("serial", self.serial.into()),
+            ("description", self.description.into()),
+            ("office_id", self.office_id.into()),
+            ("rack", self.rack.into()),
+            ("room", self.room.into()),
+            ("credential", self.credential.into()),
+        ]))
+    }
+}
+
+impl<'a> Updatable<'a> for Address {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("device_id", self.device_id.into()),
+            ("status", self.status.into()),
+            ("mac", self.mac.into()),
+            ("last_seen", self.last_seen.into()),
+            ("reserved_until", self.reserved_until.into()),
+        ]))
+    }
+}
+
+impl Table for IpHistory {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "ip",
+            "network_id",
+            "device_id",
+            "device",
+            "assigned_from",
+            "assigned_to",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("ip_history")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, ip, network_id, device_id, device, assigned_from, assigned_to) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.ip.into(),
+            self.network_id.into(),
+            self.device_id.into(),
+            self.device.into(),
+            self.from.into(),
+            self.to.into(),
+        ]
+    }
+}
+
+impl<'a> Updatable<'a> for CloseIpHistory {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([("assigned_to", self.to.into())]))
+    }
+}
+
+impl<'a> Updatable<'a> for SoftDelete {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("deleted_at", self.deleted_at.into()),
+            ("deleted_by", self.deleted_by.into()),
+        ]))
+    }
+}
+
 impl<'a> Updatable<'a> for UpdateOffice {
     fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
         let mut resp = HashMap::new();
+        if let Some(tmp) = self.name {
+            resp.insert("name", tmp.into());
+        }
+
         if let Some(tmp) = self.address {
             resp.insert("address", tmp.into());
         }
@@ -216,6 +493,311 @@ impl<'a> Updatable<'a> for UpdateOffice {
             resp.insert("description", tmp.into());
         }
 
-        Some(resp)
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
+    }
+}
+
+impl Table for ZoneSerial {
+    fn columns() -> Vec<&'static str> {
+        vec!["zone", "serial", "content"]
+    }
+
+    fn name() -> String {
+        String::from("dns_zones")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (zone, serial, content) VALUES ($1, $2, $3)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![self.zone.into(), self.serial.into(), self.content.into()]
+    }
+}
+
+impl<'a> Updatable<'a> for ZoneSerial {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("serial", self.serial.into()),
+            ("content", self.content.into()),
+        ]))
+    }
+}
+
+impl Table for Scope {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "network_id",
+            "range_start",
+            "range_end",
+            "gateway",
+            "dns_servers",
+            "lease_time",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("dhcp_scopes")
+    }
+
+    fn query_insert() -> String {
+        format!("INSERT INTO {} (id, network_id, range_start, range_end, gateway, dns_servers, lease_time) VALUES ($1, $2, $3, $4, $5, $6, $7)", Self::name())
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.network_id.into(),
+            self.range.start.into(),
+            self.range.end.into(),
+            self.gateway.map(|x| x.to_string()).into(),
+            serde_json::json!(self.dns_servers).into(),
+            self.lease_time.into(),
+        ]
+    }
+}
+
+impl<'a> Updatable<'a> for Scope {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("range_start", self.range.start.into()),
+            ("range_end", self.range.end.into()),
+            ("gateway", self.gateway.map(|x| x.to_string()).into()),
+            ("dns_servers", serde_json::json!(self.dns_servers).into()),
+            ("lease_time", self.lease_time.into()),
+        ]))
+    }
+}
+
+impl Table for Reservation {
+    fn columns() -> Vec<&'static str> {
+        vec!["ip", "network_id", "device_id"]
+    }
+
+    fn name() -> String {
+        String::from("dhcp_reservations")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (ip, network_id, device_id) VALUES ($1, $2, $3)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.ip.into(),
+            self.network_id.into(),
+            self.device_id.into(),
+        ]
+    }
+}
+
+impl Table for Schedule {
+    fn columns() -> Vec<&'static str> {
+        vec!["network_id", "interval", "enabled", "last_run"]
+    }
+
+    fn name() -> String {
+        String::from("scan_schedules")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (network_id, interval, enabled, last_run) VALUES ($1, $2, $3, $4)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.network_id.into(),
+            self.interval.into(),
+            self.enabled.into(),
+            self.last_run.into(),
+        ]
+    }
+}
+
+impl<'a> Updatable<'a> for Schedule {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("interval", self.interval.into()),
+            ("enabled", self.enabled.into()),
+            ("last_run", self.last_run.into()),
+        ]))
+    }
+}
+
+impl Table for ScanResult {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "network_id",
+            "started_at",
+            "finished_at",
+            "scanned",
+            "online",
+            "offline",
+            "alive",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("scan_results")
+    }
+
+    fn query_insert() -> String {
+        format!("INSERT INTO {} (id, network_id, started_at, finished_at, scanned, online, offline, alive) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)", Self::name())
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.network_id.into(),
+            self.started_at.into(),
+            self.finished_at.into(),
+            self.scanned.into(),
+            self.online.into(),
+            self.offline.into(),
+            serde_json::json!(self.alive).into(),
+        ]
+    }
+}
+
+impl Table for Service {
+    fn columns() -> Vec<&'static str> {
+        vec!["device_id", "ip", "network_id", "port", "name", "seen"]
+    }
+
+    fn name() -> String {
+        String::from("device_services")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (device_id, ip, network_id, port, name, seen) VALUES ($1, $2, $3, $4, $5, $6)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.device_id.into(),
+            self.ip.into(),
+            self.network_id.into(),
+            self.port.into(),
+            self.name.into(),
+            self.seen.into(),
+        ]
+    }
+}
+
+impl Table for Webhook {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "url",
+            "secret",
+            "entities",
+            "actions",
+            "network_id",
+            "office_id",
+            "enabled",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("webhooks")
+    }
+
+    fn query_insert() -> String {
+        format!("INSERT INTO {} (id, url, secret, entities, actions, network_id, office_id, enabled) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)", Self::name())
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.url.into(),
+            self.secret.into(),
+            serde_json::json!(self.entities).into(),
+            serde_json::json!(self.actions).into(),
+            self.network_id.into(),
+            self.office_id.into(),
+            self.enabled.into(),
+        ]
+    }
+}
+
+impl<'a> Updatable<'a> for Webhook {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("url", self.url.into()),
+            ("secret", self.secret.into()),
+            ("entities", serde_json::json!(self.entities).into()),
+            ("actions", serde_json::json!(self.actions).into()),
+            ("network_id", self.network_id.into()),
+            ("office_id", self.office_id.into()),
+            ("enabled", self.enabled.into()),
+        ]))
+    }
+}
+
+impl Table for Delivery {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "webhook_id",
+            "event",
+            "attempts",
+            "status",
+            "error",
+            "delivered",
+            "created_at",
+            "last_attempt",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("webhook_deliveries")
+    }
+
+    fn query_insert() -> String {
+        format!("INSERT INTO {} (id, webhook_id, event, attempts, status, error, delivered, created_at, last_attempt) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)", Self::name())
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.webhook_id.into(),
+            self.event.into(),
+            self.attempts.into(),
+            self.status.into(),
+            self.error.into(),
+            self.delivered.into(),
+            self.created_at.into(),
+            self.last_attempt.into(),
+        ]
+    }
+}
+
+impl<'a> Updatable<'a> for Delivery {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            ("attempts", self.attempts.into()),
+            ("status", self.status.into()),
+            ("error", self.error.into()),
+            ("delivered", self.delivered.into()),
+            ("last_attempt", self.last_attempt.into()),
+        ]))
     }
 }
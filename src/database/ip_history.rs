@@ -0,0 +1,48 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    RepositoryInjection,
+};
+use crate::models::ip_history::IpHistory;
+use sqlx::Postgres;
+use std::net::IpAddr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+impl RepositoryInjection<Postgres> {
+    pub async fn get_ip_history(
+        &self,
+        ip: IpAddr,
+        network_id: Option<Uuid>,
+        at: Option<OffsetDateTime>,
+    ) -> Result<Vec<IpHistory>, RepositoryError> {
+        let mut query = format!("SELECT * FROM {} WHERE ip = $1", IpHistory::name());
+        let mut values: Vec<TypeTable> = vec![ip.into()];
+
+        if let Some(network_id) = network_id {
+            values.push(network_id.into());
+            query.push_str(&format!(" AND network_id = ${}", values.len()));
+        }
+
+        if let Some(at) = at {
+            values.push(at.into());
+            query.push_str(&format!(
+                " AND assigned_from <= ${0} AND (assigned_to IS NULL OR assigned_to > ${0})",
+                values.len()
+            ));
+        }
+        query.push_str(" ORDER BY assigned_from");
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(IpHistory::from)
+            .collect())
+    }
+}
@@ -1,8 +1,22 @@
 use crate::models::{
+    audit::Audit,
+    dhcp::{Reservation, ReservationView, Scope},
+    dns::ZoneSerial,
+    ip_history::IpHistory,
     office::Office,
-    {device::Device, network::Network, user::User},
+    scan::{ScanResult, Schedule},
+    trash::Trash,
+    webhook::{Delivery, Webhook},
+    {
+        device::{Address, Device, DeviceView, Service},
+        network::Network,
+        user::User,
+    },
+};
+use libipam::{
+    ipam_services::dhcp::Range,
+    type_net::{host_count::HostCount, port::Port, vlan::Vlan},
 };
-use libipam::type_net::{host_count::HostCount, vlan::Vlan};
 use sqlx::{postgres::PgRow, Row};
 
 impl From<PgRow> for Network {
@@ -15,21 +29,80 @@ impl From<PgRow> for Network {
             used: HostCount::from(value.get::<'_, i64, &str>("available") as u32),
             free: HostCount::from(value.get::<'_, i64, &str>("available") as u32),
             vlan: Some(Vlan::new(value.get::<'_, i32, _>("vlan") as u16)),
+            gateway: value
+                .get::<'_, Option<&str>, _>("gateway")
+                .map(|x| x.parse().unwrap()),
+            broadcast: None,
+            infrastructure: serde_json::from_value(value.get("infrastructure")).unwrap_or_default(),
         }
+        .with_broadcast()
     }
 }
 
 impl From<PgRow> for Device {
     fn from(value: PgRow) -> Self {
         Self {
-            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            id: value.get("id"),
+            hostname: value
+                .get::<'_, Option<&str>, _>("hostname")
+                .map(|x| x.parse().unwrap()),
+            domain: value
+                .get::<'_, Option<&str>, _>("domain")
+                .map(|x| x.parse().unwrap()),
+            kind: value.get("kind"),
+            model: value.get("model"),
+            serial: value.get("serial"),
             description: value.get("description"),
             office_id: value.get("office_id"),
             rack: value.get("rack"),
-            credential: value.get("credential"),
             room: value.get("room"),
+            credential: value.get("credential"),
+        }
+    }
+}
+
+impl From<PgRow> for Address {
+    fn from(value: PgRow) -> Self {
+        Self {
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            network_id: value.get("network_id"),
+            device_id: value.get("device_id"),
             status: value.get("status"),
+            mac: value
+                .get::<'_, Option<&str>, _>("mac")
+                .map(|x| x.parse().unwrap()),
+            last_seen: value.get("last_seen"),
+            reserved_until: value.get("reserved_until"),
+        }
+    }
+}
+
+impl From<PgRow> for DeviceView {
+    fn from(value: PgRow) -> Self {
+        Self {
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
             network_id: value.get("network_id"),
+            status: value.get("status"),
+            device_id: value.get("device_id"),
+            mac: value
+                .get::<'_, Option<&str>, _>("mac")
+                .map(|x| x.parse().unwrap()),
+            last_seen: value.get("last_seen"),
+            reserved_until: value.get("reserved_until"),
+            hostname: value
+                .get::<'_, Option<&str>, _>("hostname")
+                .map(|x| x.parse().unwrap()),
+            domain: value
+                .get::<'_, Option<&str>, _>("domain")
+                .map(|x| x.parse().unwrap()),
+            kind: value.get("kind"),
+            model: value.get("model"),
+            serial: value.get("serial"),
+            description: value.get("description"),
+            office_id: value.get("office_id"),
+            rack: value.get("rack"),
+            room: value.get("room"),
+            credential: value.get("credential"),
         }
     }
 }
@@ -38,7 +111,7 @@ impl From<PgRow> for Office {
     fn from(value: PgRow) -> Self {
         Self {
             id: value.get("id"),
-            name: value.get("description"),
+            name: value.get("name"),
             address: value.get("address"),
             description: value.get("description"),
         }
@@ -55,3 +128,167 @@ impl From<PgRow> for User {
         }
     }
 }
+
+impl From<PgRow> for Audit {
+    fn from(value: PgRow) -> Self {
+        Self {
+            id: value.get("id"),
+            actor: value.get("actor"),
+            timestamp: value.get("timestamp"),
+            entity: value.get("entity"),
+            action: value.get("action"),
+            key: value.get("key"),
+            before: value.get("before"),
+            after: value.get("after"),
+        }
+    }
+}
+
+impl<T: From<PgRow>> From<PgRow> for Trash<T> {
+    fn from(value: PgRow) -> Self {
+        Self {
+            deleted_at: value.get("deleted_at"),
+            deleted_by: value.get("deleted_by"),
+            data: T::from(value),
+        }
+    }
+}
+
+impl From<PgRow> for IpHistory {
+    fn from(value: PgRow) -> Self {
+        Self {
+            id: value.get("id"),
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            network_id: value.get("network_id"),
+            device_id: value.get("device_id"),
+            device: value.get("device"),
+            from: value.get("assigned_from"),
+            to: value.get("assigned_to"),
+        }
+    }
+}
+
+impl From<PgRow> for ZoneSerial {
+    fn from(value: PgRow) -> Self {
+        Self {
+            zone: value.get("zone"),
+            serial: value.get("serial"),
+            content: value.get("content"),
+        }
+    }
+}
+
+impl From<PgRow> for Scope {
+    fn from(value: PgRow) -> Self {
+        Self {
+            id: value.get("id"),
+            network_id: value.get("network_id"),
+            range: Range {
+                start: value.get::<'_, &str, _>("range_start").parse().unwrap(),
+                end: value.get::<'_, &str, _>("range_end").parse().unwrap(),
+            },
+            gateway: value
+                .get::<'_, Option<&str>, _>("gateway")
+                .map(|x| x.parse().unwrap()),
+            dns_servers: serde_json::from_value(value.get("dns_servers")).unwrap_or_default(),
+            lease_time: value.get("lease_time"),
+        }
+    }
+}
+
+impl From<PgRow> for Reservation {
+    fn from(value: PgRow) -> Self {
+        Self {
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            network_id: value.get("network_id"),
+            device_id: value.get("device_id"),
+        }
+    }
+}
+
+impl From<PgRow> for ReservationView {
+    fn from(value: PgRow) -> Self {
+        Self {
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            network_id: value.get("network_id"),
+            device_id: value.get("device_id"),
+            mac: value.get::<'_, &str, _>("mac").parse().unwrap(),
+            hostname: value
+                .get::<'_, Option<&str>, _>("hostname")
+                .map(|x| x.parse().unwrap()),
+            domain: value
+                .get::<'_, Option<&str>, _>("domain")
+                .map(|x| x.parse().unwrap()),
+        }
+    }
+}
+
+impl From<PgRow> for Schedule {
+    fn from(value: PgRow) -> Self {
+        Self {
+            network_id: value.get("network_id"),
+            interval: value.get("interval"),
+            enabled: value.get("enabled"),
+            last_run: value.get("last_run"),
+        }
+    }
+}
+
+impl From<PgRow> for ScanResult {
+    fn from(value: PgRow) -> Self {
+        Self {
+            id: value.get("id"),
+            network_id: value.get("network_id"),
+            started_at: value.get("started_at"),
+            finished_at: value.get("finished_at"),
+            scanned: value.get("scanned"),
+            online: value.get("online"),
+            offline: value.get("offline"),
+            alive: serde_json::from_value(value.get("alive")).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<PgRow> for Service {
+    fn from(value: PgRow) -> Self {
+        Self {
+            device_id: value.get("device_id"),
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            network_id: value.get("network_id"),
+            port: Port::new(value.get::<'_, i32, _>("port") as u16),
+            name: value.get("name"),
+            seen: value.get("seen"),
+        }
+    }
+}
+
+impl From<PgRow> for Webhook {
+    fn from(value: PgRow) -> Self {
+        Self {
+            id: value.get("id"),
+            url: value.get("url"),
+            secret: value.get("secret"),
+            entities: serde_json::from_value(value.get("entities")).unwrap_or_default(),
+            actions: serde_json::from_value(value.get("actions")).unwrap_or_default(),
+            network_id: value.get("network_id"),
+            office_id: value.get("office_id"),
+            enabled: value.get("enabled"),
+        }
+    }
+}
+
+impl From<PgRow> for Delivery {
+    fn from(value: PgRow) -> Self {
+        Self {
+            id: value.get("id"),
+            webhook_id: value.get("webhook_id"),
+            event: value.get("event"),
+            attempts: value.get("attempts"),
+            status: value.get("status"),
+            error: value.get("error"),
+            delivered: value.get("delivered"),
+            created_at: value.get("created_at"),
+            last_attempt: value.get("last_attempt"),
+        }
+    }
+}
@@ -1,7 +1,14 @@
+pub mod audit;
+pub mod device;
+pub mod dhcp;
 pub mod entities;
+pub mod ip_history;
 pub mod mappers;
 pub mod repository;
+pub mod scan;
 pub mod transaction;
+pub mod trash;
+pub mod webhook;
 
 use futures::stream::StreamExt;
 use repository::{
@@ -24,6 +31,17 @@ impl RepositoryInjection<Postgres> {
                 .await?,
         ))
     }
+
+    // A pool that never reaches a server, handlers run until their first query
+    #[cfg(test)]
+    pub fn unreachable() -> Self {
+        Self(
+            PgPoolOptions::new()
+                .acquire_timeout(std::time::Duration::from_millis(100))
+                .connect_lazy("postgres://ipam@127.0.0.1:1/ipam")
+                .unwrap(),
+        )
+    }
 }
 
 impl Repository for RepositoryInjection<Postgres> {
@@ -44,19 +62,8 @@ impl Repository for RepositoryInjection<Postgres> {
                 let query = T::query_insert();
                 let mut tmp = sqlx::query(&query);
                 let data = T::get_fields(data);
-                for i in data {
-                    tmp = match i {
-                        TypeTable::String(s) => tmp.bind(s),
-                        TypeTable::OptionString(opt) => tmp.bind(opt),
-                        TypeTable::Status(status) => tmp.bind(status),
-                        TypeTable::Uuid(e) => tmp.bind(e),
-                        TypeTable::Role(r) => tmp.bind(r),
-                        TypeTable::OptionUuid(e) => tmp.bind(e),
-                        TypeTable::Null => tmp,
-                        TypeTable::I64(e) => tmp.bind(e),
-                        TypeTable::OptionCredential(e) => tmp.bind(e),
-                        TypeTable::OptionVlan(e) => tmp.bind(e),
-                    };
+                for i in &data {
+                    tmp = i.bind(tmp);
                 }
 
                 match tmp.execute(&mut *tx).await {
@@ -71,10 +78,13 @@ impl Repository for RepositoryInjection<Postgres> {
             }
 
             match tx.commit().await {
-                Ok(_) => Ok(QueryResult::Insert {
-                    row_affect: count,
-                    data: resp_data,
-                }),
+                Ok(_) => {
+                    T::inserted(&resp_data);
+                    Ok(QueryResult::Insert {
+                        row_affect: count,
+                        data: resp_data,
+                    })
+                }
                 Err(e) => Err(RepositoryError::Sqlx(e.to_string())),
             }
         };
@@ -91,48 +101,38 @@ impl Repository for RepositoryInjection<Postgres> {
         Box::pin(async {
             let mut query = format!("SELECT * FROM {}", T::name());
             let mut vec_resp = Vec::new();
+            let mut conditions = Vec::new();
+            if T::soft_delete() {
+                conditions.push("deleted_at IS NULL".to_string());
+            }
             tracing::debug!("Get element % Condition select {:?} %", column_data);
             match column_data {
                 Some(col) if !col.is_empty() => {
                     let cols = T::columns();
-                    query.push_str(" WHERE");
 
                     let mut data_pos = HashMap::new();
 
                     let mut pos = 1;
-                    let len = col.len();
                     for i in col.keys() {
                         if !cols.contains(i) {
                             return Err(RepositoryError::ColumnNotFound(i.to_string()));
                         }
                         if col.get(i).unwrap() == &TypeTable::Null {
-                            query.push_str(&format!(" {} IS NULL", i));
+                            conditions.push(format!("{} IS NULL", i));
                         } else {
-                            query.push_str(&format!(" {} = ${}", i, pos));
-                            if pos < len {
-                                query.push_str(" AND");
-                            }
+                            conditions.push(format!("{} = ${}", i, pos));
                             data_pos.insert(pos, col.get(i).unwrap());
                             pos += 1;
                         }
                     }
+                    query.push_str(" WHERE ");
+                    query.push_str(&conditions.join(" AND "));
                     tracing::debug!("{}", query);
                     tracing::debug!("{:?}", data_pos);
                     let mut resp = sqlx::query(&query);
 
                     for i in 1..pos {
-                        resp = match data_pos.get(&i).unwrap() {
-                            TypeTable::OptionUuid(e) => resp.bind(e),
-                            TypeTable::Uuid(e) => resp.bind(e),
-                            TypeTable::String(s) => resp.bind(s),
-                            TypeTable::OptionString(opt) => resp.bind(opt),
-                            TypeTable::Status(status) => resp.bind(status),
-                            TypeTable::Role(role) => resp.bind(role),
-                            TypeTable::OptionCredential(e) => resp.bind(e),
-                            TypeTable::OptionVlan(e) => resp.bind(e),
-                            TypeTable::I64(e) => resp.bind(e),
-                            TypeTable::Null => resp,
-                        };
+                        resp = data_pos.get(&i).unwrap().bind(resp);
                     }
 
                     let mut resp = resp.fetch(&self.0);
@@ -147,6 +147,10 @@ impl Repository for RepositoryInjection<Postgres> {
                     }
                 }
                 None => Ok({
+                    if !conditions.is_empty() {
+                        query.push_str(" WHERE ");
+                        query.push_str(&conditions.join(" AND "));
+                    }
                     let mut fetch = sqlx::query(&query).fetch(&self.0);
                     while let Some(Ok(tmp)) = fetch.next().await {
                         vec_resp.push(tmp.into());
@@ -196,38 +200,26 @@ impl Repository for RepositoryInjection<Postgres> {
                     pos += 1
                 }
 
-                let condition = match condition {
-                    Some(e) => {
-                        query.push_str(" WHERE");
-                        e
-                    }
-                    None => HashMap::new(),
-                };
-
-                let len = condition.len() + pos - 1;
+                let condition = condition.unwrap_or_default();
+                let mut conditions = Vec::new();
                 for i in condition.keys() {
-                    pos_values.insert(pos, condition.get(i).unwrap());
-                    query.push_str(&format!(" {} = ${}", i, pos));
-                    if pos < len {
-                        query.push_str(" AND");
+                    if condition.get(i).unwrap() == &TypeTable::Null {
+                        conditions.push(format!("{} IS NULL", i));
+                    } else {
+                        pos_values.insert(pos, condition.get(i).unwrap());
+                        conditions.push(format!("{} = ${}", i, pos));
+                        pos += 1;
                     }
-                    pos += 1;
+                }
+
+                if !conditions.is_empty() {
+                    query.push_str(" WHERE ");
+                    query.push_str(&conditions.join(" AND "));
                 }
 
                 let mut sql = sqlx::query(&query);
                 for i in 1..pos {
-                    sql = match pos_values.get(&i).unwrap() {
-                        TypeTable::OptionCredential(e) => sql.bind(e),
-                        TypeTable::OptionVlan(e) => sql.bind(e),
-                        TypeTable::String(s) => sql.bind(s),
-                        TypeTable::OptionString(value) => sql.bind(value),
-                        TypeTable::Status(value) => sql.bind(value),
-                        TypeTable::Uuid(e) => sql.bind(e),
-                        TypeTable::Role(value) => sql.bind(value),
-                        TypeTable::OptionUuid(e) => sql.bind(e),
-                        TypeTable::Null => sql,
-                        TypeTable::I64(e) => sql.bind(e),
-                    };
+                    sql = pos_values.get(&i).unwrap().bind(sql);
                 }
 
                 match sql.execute(&self.0).await {
@@ -277,18 +269,7 @@ impl Repository for RepositoryInjection<Postgres> {
                     let mut ex = sqlx::query(&query);
 
                     for i in 1..pos {
-                        ex = match pos_column.get(&i).unwrap() {
-                            TypeTable::OptionCredential(e) => ex.bind(e),
-                            TypeTable::OptionVlan(e) => ex.bind(e),
-                            TypeTable::OptionUuid(e) => ex.bind(e),
-                            TypeTable::String(s) => ex.bind(s),
-                            TypeTable::OptionString(s) => ex.bind(s),
-                            TypeTable::Uuid(e) => ex.bind(e),
-                            TypeTable::Status(status) => ex.bind(status),
-                            TypeTable::Role(role) => ex.bind(role),
-                            TypeTable::I64(e) => ex.bind(e),
-                            TypeTable::Null => ex,
-                        };
+                        ex = pos_column.get(&i).unwrap().bind(ex);
                     }
 
                     match ex.execute(&self.0).await {
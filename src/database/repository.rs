@@ -1,5 +1,6 @@
 use super::PgRow;
 use crate::models::{
+    audit::{Action, Entity},
     device::{Credential, Status},
     user::Role,
 };
@@ -9,9 +10,16 @@ use axum::{
 };
 use error::RepositoryError;
 use ipnet::IpNet;
-use libipam::type_net::{host_count::HostCount, vlan::Vlan};
+use libipam::type_net::{
+    dns::{DomainName, Hostname},
+    host_count::HostCount,
+    mac::MacAddr,
+    port::Port,
+    vlan::Vlan,
+};
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Value};
+use sqlx::{postgres::PgArguments, query::Query, Postgres};
 use std::{
     clone::Clone,
     collections::HashMap,
@@ -19,6 +27,7 @@ use std::{
     net::IpAddr,
     {future::Future, pin::Pin},
 };
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub type ResultRepository<'a, T> =
@@ -55,6 +64,15 @@ pub trait Table {
     fn query_insert() -> String;
     fn get_fields(self) -> Vec<TypeTable>;
     fn columns() -> Vec<&'static str>;
+    fn soft_delete() -> bool {
+        false
+    }
+    // Runs once an insert is committed
+    fn inserted(_rows: &[Self])
+    where
+        Self: Sized,
+    {
+    }
 }
 
 pub trait Updatable<'a> {
@@ -158,9 +176,45 @@ pub enum TypeTable {
     OptionVlan(Option<i32>),
     OptionCredential(Option<Credential>),
     I64(i64),
+    OptionI64(Option<i64>),
+    Bool(bool),
+    Time(OffsetDateTime),
+    OptionTime(Option<OffsetDateTime>),
+    Json(Value),
+    OptionJson(Option<Value>),
+    Entity(Entity),
+    Action(Action),
     Null,
 }
 
+impl TypeTable {
+    pub fn bind<'q>(
+        &'q self,
+        query: Query<'q, Postgres, PgArguments>,
+    ) -> Query<'q, Postgres, PgArguments> {
+        match self {
+            Self::String(value) => query.bind(value),
+            Self::OptionUuid(value) => query.bind(value),
+            Self::Uuid(value) => query.bind(value),
+            Self::OptionString(value) => query.bind(value),
+            Self::Status(value) => query.bind(value),
+            Self::Role(value) => query.bind(value),
+            Self::OptionVlan(value) => query.bind(value),
+            Self::OptionCredential(value) => query.bind(value),
+            Self::I64(value) => query.bind(value),
+            Self::OptionI64(value) => query.bind(value),
+            Self::Bool(value) => query.bind(value),
+            Self::Time(value) => query.bind(value),
+            Self::OptionTime(value) => query.bind(value),
+            Self::Json(value) => query.bind(value),
+            Self::OptionJson(value) => query.bind(value),
+            Self::Entity(value) => query.bind(value),
+            Self::Action(value) => query.bind(value),
+            Self::Null => query,
+        }
+    }
+}
+
 impl From<Option<Vlan>> for TypeTable {
     fn from(value: Option<Vlan>) -> Self {
         Self::OptionVlan(value.map(|vlan| *vlan as i32))
@@ -173,6 +227,30 @@ impl From<HostCount> for TypeTable {
     }
 }
 
+impl From<i64> for TypeTable {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
+
+impl From<Option<i64>> for TypeTable {
+    fn from(value: Option<i64>) -> Self {
+        Self::OptionI64(value)
+    }
+}
+
+impl From<Port> for TypeTable {
+    fn from(value: Port) -> Self {
+        Self::I64(*value as i64)
+    }
+}
+
+impl From<bool> for TypeTable {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
 impl From<Uuid> for TypeTable {
     fn from(value: Uuid) -> Self {
         TypeTable::Uuid(value)
@@ -197,6 +275,42 @@ impl From<IpAddr> for TypeTable {
     }
 }
 
+impl From<MacAddr> for TypeTable {
+    fn from(value: MacAddr) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<Option<MacAddr>> for TypeTable {
+    fn from(value: Option<MacAddr>) -> Self {
+        Self::OptionString(value.map(|x| x.to_string()))
+    }
+}
+
+impl From<Hostname> for TypeTable {
+    fn from(value: Hostname) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<Option<Hostname>> for TypeTable {
+    fn from(value: Option<Hostname>) -> Self {
+        Self::OptionString(value.map(String::from))
+    }
+}
+
+impl From<DomainName> for TypeTable {
+    fn from(value: DomainName) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<Option<DomainName>> for TypeTable {
+    fn from(value: Option<DomainName>) -> Self {
+        Self::OptionString(value.map(String::from))
+    }
+}
+
 impl From<IpNet> for TypeTable {
     fn from(value: IpNet) -> Self {
         Self::String(value.to_string())
@@ -226,3 +340,39 @@ impl From<Status> for TypeTable {
         Self::Status(value)
     }
 }
+
+impl From<OffsetDateTime> for TypeTable {
+    fn from(value: OffsetDateTime) -> Self {
+        Self::Time(value)
+    }
+}
+
+impl From<Option<OffsetDateTime>> for TypeTable {
+    fn from(value: Option<OffsetDateTime>) -> Self {
+        Self::OptionTime(value)
+    }
+}
+
+impl From<Value> for TypeTable {
+    fn from(value: Value) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<Option<Value>> for TypeTable {
+    fn from(value: Option<Value>) -> Self {
+        Self::OptionJson(value)
+    }
+}
+
+impl From<Entity> for TypeTable {
+    fn from(value: Entity) -> Self {
+        Self::Entity(value)
+    }
+}
+
+impl From<Action> for TypeTable {
+    fn from(value: Action) -> Self {
+        Self::Action(value)
+    }
+}
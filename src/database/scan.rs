@@ -0,0 +1,42 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    RepositoryInjection,
+};
+use crate::models::scan::ScanResult;
+use sqlx::Postgres;
+use uuid::Uuid;
+
+impl RepositoryInjection<Postgres> {
+    // Newest sweeps first
+    pub async fn get_scan_results(
+        &self,
+        network_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<ScanResult>, RepositoryError> {
+        let mut query = format!("SELECT * FROM {}", ScanResult::name());
+        let mut values: Vec<TypeTable> = Vec::new();
+
+        if let Some(network_id) = network_id {
+            values.push(network_id.into());
+            query.push_str(&format!(" WHERE network_id = ${}", values.len()));
+        }
+        values.push(limit.into());
+        query.push_str(&format!(
+            " ORDER BY started_at DESC LIMIT ${}",
+            values.len()
+        ));
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(ScanResult::from)
+            .collect())
+    }
+}
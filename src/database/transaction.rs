@@ -1,4 +1,4 @@
-use super::{repository::error::RepositoryError, Table, TypeTable, Updatable};
+use super::{repository::error::RepositoryError, RepositoryInjection, Table, TypeTable, Updatable};
 use sqlx::{Postgres, Transaction as SqlxTransaction};
 use std::{
     collections::HashMap, future::Future, pin::Pin, sync::Arc, task::{Context, Poll}
@@ -8,10 +8,18 @@ use tokio::sync::Mutex;
 pub struct BuilderPgTransaction<'a> {
     transaction: Arc<Mutex<SqlxTransaction<'a, Postgres>>>,
     futures: Vec<TransactionTask<'a>>,
+    // Table::inserted hooks, only run once everything is committed
+    committed: Vec<Box<dyn FnOnce() + Send + 'a>>,
     _state: FutureState,
     pos: usize,
 }
 
+impl RepositoryInjection<Postgres> {
+    pub async fn transaction(&self) -> Result<BuilderPgTransaction<'static>, RepositoryError> {
+        Ok(BuilderPgTransaction::new(Arc::new(Mutex::new(self.begin().await?))))
+    }
+}
+
 #[derive(Debug, Clone)]
 enum FutureState {
     Start,
@@ -24,18 +32,21 @@ impl<'b> BuilderPgTransaction<'b> {
         Self {
             transaction,
             futures: Vec::with_capacity(6),
+            committed: Vec::new(),
             _state: FutureState::Start,
             pos: 0,
         }
     }
 
-    pub async fn execute(self) -> Result<(), RepositoryError> {
+    pub async fn execute(mut self) -> Result<(), RepositoryError> {
         let transaction = self.transaction.clone();
+        let committed = std::mem::take(&mut self.committed);
         let tmp = self.await;
         let transaction = Arc::try_unwrap(transaction).unwrap().into_inner();
         match tmp {
             Ok(()) => {
-                let _ = transaction.commit().await;
+                transaction.commit().await?;
+                committed.into_iter().for_each(|hook| hook());
                 Ok(())
             }
             Err(e) => {
@@ -45,11 +56,13 @@ impl<'b> BuilderPgTransaction<'b> {
         }
     }
 
-    pub fn insert<T>(&'b mut self, data: Vec<T>)
+    pub fn insert<T>(&mut self, data: Vec<T>)
     where
         T: Table + Send + std::fmt::Debug + Clone + 'b,
     {
         let transaction = self.transaction.clone();
+        let rows = data.clone();
+        self.committed.push(Box::new(move || T::inserted(&rows)));
 
         self.futures.push( TransactionTask::new(async move {
                 let mut transaction = transaction.lock().await;
@@ -57,21 +70,10 @@ impl<'b> BuilderPgTransaction<'b> {
                 for i in data {
                     let mut sql = sqlx::query(&q_insert);
                     let fields = i.get_fields();
-                    for element in fields {
-                        sql = match element {
-                            TypeTable::String(value) => sql.bind(value),
-                            TypeTable::OptionUuid(value) => sql.bind(value),
-                            TypeTable::Uuid(value) => sql.bind(value),
-                            TypeTable::OptionString(value) => sql.bind(value),
-                            TypeTable::Status(value) => sql.bind(value),
-                            TypeTable::Role(value) => sql.bind(value),
-                            TypeTable::OptionVlan(value) => sql.bind(value),
-                            TypeTable::OptionCredential(value) => sql.bind(value),
-                            TypeTable::I64(value) => sql.bind(value),
-                            TypeTable::Null => sql,
-                        };
+                    for element in &fields {
+                        sql = element.bind(sql);
                     }
-                    let _ = sql.execute(&mut **transaction).await;
+                    sql.execute(&mut **transaction).await?;
                 }
                 Ok(())
             }),
@@ -114,40 +116,28 @@ impl<'b> BuilderPgTransaction<'b> {
                     pos += 1
                 }
 
-                let condition = match condition {
-                    Some(e) => {
-                        query.push_str(" WHERE");
-                        e
-                    }
-                    None => HashMap::new(),
-                };
-
-                let len = condition.len() + pos - 1;
+                let condition = condition.unwrap_or_default();
+                let mut conditions = Vec::new();
                 for i in condition.keys() {
-                    pos_values.insert(pos, condition.get(i).unwrap());
-                    query.push_str(&format!(" {} = ${}", i, pos));
-                    if pos < len {
-                        query.push_str(" AND");
+                    if condition.get(i).unwrap() == &TypeTable::Null {
+                        conditions.push(format!("{} IS NULL", i));
+                    } else {
+                        pos_values.insert(pos, condition.get(i).unwrap());
+                        conditions.push(format!("{} = ${}", i, pos));
+                        pos += 1;
                     }
-                    pos += 1;
+                }
+
+                if !conditions.is_empty() {
+                    query.push_str(" WHERE ");
+                    query.push_str(&conditions.join(" AND "));
                 }
 
                 let mut sql = sqlx::query(&query);
                 for i in 1..pos {
-                    sql = match pos_values.get(&i).unwrap() {
-                        TypeTable::OptionCredential(e) => sql.bind(e),
-                        TypeTable::OptionVlan(e) => sql.bind(e),
-                        TypeTable::String(s) => sql.bind(s),
-                        TypeTable::OptionString(value) => sql.bind(value),
-                        TypeTable::Status(value) => sql.bind(value),
-                        TypeTable::Uuid(e) => sql.bind(e),
-                        TypeTable::Role(value) => sql.bind(value),
-                        TypeTable::OptionUuid(e) => sql.bind(e),
-                        TypeTable::Null => sql,
-                        TypeTable::I64(e) => sql.bind(e),
-                    };
+                    sql = pos_values.get(&i).unwrap().bind(sql);
                 }
-                let _ = sql.execute(&mut **transaction).await;
+                sql.execute(&mut **transaction).await?;
 
                 Ok(())
             } else {
@@ -194,26 +184,15 @@ impl<'b> BuilderPgTransaction<'b> {
                     let mut ex = sqlx::query(&query);
 
                     for i in 1..pos {
-                        ex = match pos_column.get(&i).unwrap() {
-                            TypeTable::OptionCredential(e) => ex.bind(e),
-                            TypeTable::OptionVlan(e) => ex.bind(e),
-                            TypeTable::OptionUuid(e) => ex.bind(e),
-                            TypeTable::String(s) => ex.bind(s),
-                            TypeTable::OptionString(s) => ex.bind(s),
-                            TypeTable::Uuid(e) => ex.bind(e),
-                            TypeTable::Status(status) => ex.bind(status),
-                            TypeTable::Role(role) => ex.bind(role),
-                            TypeTable::I64(e) => ex.bind(e),
-                            TypeTable::Null => ex,
-                        };
+                        ex = pos_column.get(&i).unwrap().bind(ex);
                     }
 
-                    let _ = ex.execute(&mut **transaction).await;
+                    ex.execute(&mut **transaction).await?;
                     Ok(())
                 }
 
                 None => {
-                    let _ = sqlx::query(&query).execute(&mut **transaction).await;
+                    sqlx::query(&query).execute(&mut **transaction).await?;
                     Ok(())
                 },
                 _ => Err(RepositoryError::ColumnNotFound("".to_string())),
@@ -233,10 +212,17 @@ impl<'b> std::future::Future for BuilderPgTransaction<'b> {
                 FutureState::Start => {
                     this._state = FutureState::Running;
                 }
+                // Tasks run one after the other and the first error stops the rest
                 FutureState::Running => {
                     if let Some(future) = this.futures.get_mut(this.pos) {
-                        let future = unsafe { Pin::new_unchecked(future) };
-                        return future.poll(cx);
+                        match Pin::new(future).poll(cx) {
+                            Poll::Ready(Ok(())) => this.pos += 1,
+                            Poll::Ready(Err(e)) => {
+                                this._state = FutureState::Ready;
+                                return Poll::Ready(Err(e));
+                            }
+                            Poll::Pending => return Poll::Pending,
+                        }
                     } else {
                         this._state = FutureState::Ready;
                     }
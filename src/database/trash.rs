@@ -0,0 +1,77 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    transaction::BuilderPgTransaction,
+    RepositoryInjection,
+};
+use crate::models::trash::{SoftDelete, Trash};
+use sqlx::{postgres::PgRow, Postgres};
+use std::{collections::HashMap, fmt::Debug};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+impl RepositoryInjection<Postgres> {
+    pub async fn get_trash<T>(
+        &self,
+        condition: Option<HashMap<&str, TypeTable>>,
+    ) -> Result<Vec<Trash<T>>, RepositoryError>
+    where
+        T: Table + From<PgRow>,
+    {
+        let mut query = format!("SELECT * FROM {} WHERE deleted_at IS NOT NULL", T::name());
+        let columns = T::columns();
+        let mut values = Vec::new();
+
+        for (column, value) in condition.unwrap_or_default() {
+            if !columns.contains(&column) {
+                return Err(RepositoryError::ColumnNotFound(column.to_string()));
+            }
+            values.push(value);
+            query.push_str(&format!(" AND {} = ${}", column, values.len()));
+        }
+        query.push_str(" ORDER BY deleted_at DESC");
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(Trash::from)
+            .collect())
+    }
+
+    pub async fn purge<T: Table>(&self, before: OffsetDateTime) -> Result<u64, RepositoryError> {
+        let query = format!("DELETE FROM {} WHERE deleted_at < $1", T::name());
+
+        Ok(sqlx::query(&query)
+            .bind(before)
+            .execute(&self.0)
+            .await?
+            .rows_affected())
+    }
+}
+
+impl BuilderPgTransaction<'_> {
+    pub fn soft_delete<T>(
+        &mut self,
+        actor: Uuid,
+        deleted_at: OffsetDateTime,
+        mut condition: HashMap<&'static str, TypeTable>,
+    ) where
+        T: Table + Debug + Clone,
+    {
+        condition.insert("deleted_at", TypeTable::Null);
+        self.update::<T, _>(SoftDelete::delete(actor, deleted_at), Some(condition));
+    }
+
+    pub fn restore<T>(&mut self, condition: HashMap<&'static str, TypeTable>)
+    where
+        T: Table + Debug + Clone,
+    {
+        self.update::<T, _>(SoftDelete::restore(), Some(condition));
+    }
+}
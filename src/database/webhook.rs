@@ -0,0 +1,35 @@
+use super::{
+    repository::{error::RepositoryError, Table, TypeTable},
+    RepositoryInjection,
+};
+use crate::models::webhook::Delivery;
+use sqlx::Postgres;
+use uuid::Uuid;
+
+impl RepositoryInjection<Postgres> {
+    // Newest deliveries first
+    pub async fn get_deliveries(
+        &self,
+        webhook_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Delivery>, RepositoryError> {
+        let query = format!(
+            "SELECT * FROM {} WHERE webhook_id = $1 ORDER BY created_at DESC LIMIT $2",
+            Delivery::name()
+        );
+        let values: Vec<TypeTable> = vec![webhook_id.into(), limit.into()];
+
+        tracing::debug!("{}", query);
+        let mut sql = sqlx::query(&query);
+        for value in &values {
+            sql = value.bind(sql);
+        }
+
+        Ok(sql
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(Delivery::from)
+            .collect())
+    }
+}
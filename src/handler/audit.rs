@@ -0,0 +1,16 @@
+use super::*;
+use crate::models::audit::{Audit, AuditFilter};
+
+pub async fn get(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Query(filter): Query<AuditFilter>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let audit: Vec<Audit> = state.get_audit(filter).await?;
+
+    Ok(Json(json!({
+        "length": audit.len(),
+        "audit": audit
+    })))
+}
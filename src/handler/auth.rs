@@ -1,5 +1,9 @@
 use super::*;
-use crate::{models::user::User, services::Claims};
+use crate::{
+    database::repository::QueryResult,
+    models::{audit::Audit, user::User},
+    services::Claims,
+};
 use axum::{extract::Request, middleware::Next, response::Response};
 use cookie::Cookie;
 use libipam::{
@@ -11,6 +15,7 @@ pub async fn create(
     State(state): State<RepositoryType>,
     uri: Uri,
     _: IsAdministrator,
+    Actor(actor): Actor,
     Json(mut user): Json<User>,
 ) -> Result<impl IntoResponse, ResponseError> {
     let state = state.lock().await;
@@ -27,7 +32,15 @@ pub async fn create(
         }
     };
 
-    Ok(state.insert(vec![user]).await?)
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![user.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &user)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![user],
+    })
 }
 
 pub async fn login(
@@ -69,6 +82,8 @@ pub async fn login(
     }
 }
 
+// Requests without a token go through anonymously, handlers that need the
+// caller ask for it with the IsAdministrator and Actor extractors
 pub async fn verify_token(
     libipam::Token(token): libipam::Token,
     mut req: Request,
@@ -76,10 +91,12 @@ pub async fn verify_token(
 ) -> Result<axum::response::Response, ResponseError> {
     match token.map(authentication::verify_token::<Claims, _>) {
         Ok(Ok(e)) => {
-            req.extensions_mut().insert(e.role);
+            req.extensions_mut().insert(e.role.clone());
+            req.extensions_mut().insert(e);
             Ok(next.run(req).await)
         }
-        _ => Err(ResponseError::unauthorized(
+        Err(_) => Ok(next.run(req).await),
+        Ok(Err(_)) => Err(ResponseError::unauthorized(
             req.uri(),
             Some("invalid username o password".to_string()),
         )),
@@ -1,22 +1,290 @@
 use super::*;
-use crate::models::{device::*, network::Network};
-use models_data_entry::ParamsDevice;
+use crate::database::{
+    repository::{QueryResult, TypeTable},
+    transaction::BuilderPgTransaction,
+};
+use crate::models::{
+    audit::{with_password, Audit},
+    device::*,
+    network::Network,
+};
+use crate::services::{self, ddns::Ddns, oui::Oui, probe, snmp, status::Transitions};
+use axum::Extension;
+use libipam::ipam_services::snmp::SnmpError;
+use models_data_entry::{Assignment, ParamsDevice, Probe};
+use params::{history::QueryHistory, ip_history::QueryIpHistory};
 
+use sqlx::Postgres;
 use std::net::IpAddr;
+use time::OffsetDateTime;
+
+fn address_key(ip: IpAddr, network_id: Uuid) -> HashMap<&'static str, TypeTable> {
+    HashMap::from([("ip", ip.into()), ("network_id", network_id.into())])
+}
+
+async fn check_network(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    ip: IpAddr,
+    network_id: Uuid,
+) -> Result<(), ResponseError> {
+    let network = state
+        .get::<Network>(Some(HashMap::from([("id", network_id.into())])))
+        .await?
+        .remove(0);
+
+    if !network.network.contains(&ip) {
+        return Err(ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("The ip doesn't belong to the network".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    Ok(())
+}
+
+// A trashed address is dropped so its ip can be used again
+async fn reuse_address(
+    state: &RepositoryInjection<Postgres>,
+    tx: &mut BuilderPgTransaction<'_>,
+    ip: IpAddr,
+    network_id: Uuid,
+) -> Result<Option<DeviceView>, RepositoryError> {
+    if !state
+        .get_trash::<Address>(Some(address_key(ip, network_id)))
+        .await?
+        .is_empty()
+    {
+        tx.delete::<Address>(Some(address_key(ip, network_id)));
+    }
+
+    Ok(state
+        .get::<DeviceView>(Some(address_key(ip, network_id)))
+        .await
+        .ok()
+        .and_then(|mut x| x.pop()))
+}
+
+// Gateway, broadcast and infrastructure addresses can't hold devices
+async fn check_designated(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    ip: IpAddr,
+    network_id: Uuid,
+) -> Result<(), ResponseError> {
+    let network = state
+        .get::<Network>(Some(HashMap::from([("id", network_id.into())])))
+        .await?
+        .remove(0);
+
+    match network.designation(&ip) {
+        Some(e) => Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The address is designated by the network".to_string())
+            .detail(format!("{} is {} of {}", ip, e, network.network))
+            .instance(uri.to_string())
+            .build()),
+        None => Ok(()),
+    }
+}
+
+fn check_free(uri: &Uri, current: Option<&DeviceView>) -> Result<(), ResponseError> {
+    match current {
+        Some(e) if e.device_id.is_some() => Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The address is assigned to another device".to_string())
+            .detail(format!("{} is used by {}", e.ip, e.device_id.unwrap()))
+            .instance(uri.to_string())
+            .build()),
+        _ => Ok(()),
+    }
+}
+
+// The address being moved away from keeps its mac until it's released
+async fn check_mac(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    address: &Address,
+    previous: Option<(IpAddr, Uuid)>,
+) -> Result<(), ResponseError> {
+    let Some(mac) = address.mac else {
+        return Ok(());
+    };
+
+    let used = state
+        .get::<Address>(Some(HashMap::from([
+            ("network_id", address.network_id.into()),
+            ("mac", mac.into()),
+        ])))
+        .await
+        .unwrap_or_default();
+
+    match used
+        .iter()
+        .find(|x| x.ip != address.ip && Some((x.ip, x.network_id)) != previous)
+    {
+        Some(e) => Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The mac address is already used in the network".to_string())
+            .detail(format!("{} is used by {}", mac, e.ip))
+            .instance(uri.to_string())
+            .build()),
+        None => Ok(()),
+    }
+}
+
+pub(super) async fn check_name(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    device: &Device,
+) -> Result<(), ResponseError> {
+    let Some(hostname) = &device.hostname else {
+        return Ok(());
+    };
+
+    let used = state
+        .get::<Device>(Some(HashMap::from([
+            ("hostname", hostname.clone().into()),
+            (
+                "domain",
+                device.domain.clone().map_or(TypeTable::Null, Into::into),
+            ),
+        ])))
+        .await
+        .unwrap_or_default();
+
+    match used.iter().find(|x| x.id != device.id) {
+        Some(e) => Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The hostname is already used in the zone".to_string())
+            .detail(format!(
+                "{} is used by {}",
+                device
+                    .domain
+                    .as_ref()
+                    .map_or(hostname.to_string(), |x| x.fqdn(hostname)),
+                e.id
+            ))
+            .instance(uri.to_string())
+            .build()),
+        None => Ok(()),
+    }
+}
+
+fn check_transition(
+    uri: &Uri,
+    transitions: &Transitions,
+    from: &Status,
+    to: &Status,
+) -> Result<(), ResponseError> {
+    if transitions.allows(from, to) {
+        return Ok(());
+    }
+
+    Err(ResponseError::builder()
+        .status(StatusCode::CONFLICT)
+        .title("Invalid status transition".to_string())
+        .detail(format!(
+            "{:?} can't change to {:?}, allowed: {:?}",
+            from,
+            to,
+            transitions.allowed(from)
+        ))
+        .instance(uri.to_string())
+        .build())
+}
+
+fn check_reservation(uri: &Uri, address: &Address) -> Result<(), ResponseError> {
+    let Some(until) = address.reserved_until else {
+        return Ok(());
+    };
+
+    let title = if address.status != Status::Reserved {
+        "Only reserved addresses can expire"
+    } else if until <= OffsetDateTime::now_utc() {
+        "The reservation expiry is in the past"
+    } else {
+        return Ok(());
+    };
+
+    Err(ResponseError::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .title(title.to_string())
+        .instance(uri.to_string())
+        .build())
+}
+
+fn store_address(tx: &mut BuilderPgTransaction<'_>, address: Address, exists: bool) {
+    if exists {
+        let key = address_key(address.ip, address.network_id);
+        tx.update::<Address, _>(address, Some(key));
+    } else {
+        tx.insert(vec![address]);
+    }
+}
 
 pub async fn create(
     State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    Extension(transitions): Extension<Arc<Transitions>>,
     _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
     Json(device): Json<models_data_entry::Device>,
-) -> Result<impl IntoResponse, ResponseError> {
+) -> Result<QueryResult<DeviceView>, ResponseError> {
     let state = state.lock().await;
+    let view: DeviceView = device.into();
+    let mut audit = Vec::new();
+    let mut tx = state.transaction().await?;
 
-    Ok(state.insert::<Device>(vec![device.into()]).await?)
+    check_network(&state, &uri, view.ip, view.network_id).await?;
+    let current = reuse_address(&state, &mut tx, view.ip, view.network_id).await?;
+    check_free(&uri, current.as_ref())?;
+    check_transition(
+        &uri,
+        &transitions,
+        &current
+            .as_ref()
+            .map(|x| x.status.clone())
+            .unwrap_or_default(),
+        &view.status,
+    )?;
+    check_reservation(&uri, &view.address())?;
+    check_mac(&state, &uri, &view.address(), None).await?;
+    if view.device_id.is_some() {
+        check_designated(&state, &uri, view.ip, view.network_id).await?;
+    }
+    if view.device_id.is_some() || view.status == Status::Reserved {
+        super::dhcp::check_pool(&state, &uri, view.ip, view.network_id).await?;
+    }
+
+    if let Some(device) = view.device() {
+        check_name(&state, &uri, &device).await?;
+        audit.push(Audit::insert(Some(actor), &device));
+        tx.insert(vec![device]);
+    }
+    store_address(&mut tx, view.address(), current.is_some());
+
+    audit.push(match &current {
+        Some(before) => Audit::update(Some(actor), before, &view),
+        None => Audit::insert(Some(actor), &view),
+    });
+    tx.insert(audit);
+    services::ip_history::record(&mut tx, current.as_ref(), Some(&view));
+    tx.execute().await?;
+    services::ddns::publish(&state, &ddns, current.as_ref(), Some(&view)).await;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![view],
+    })
 }
 
 pub async fn create_all_devices(
     State(state): State<RepositoryType>,
     _: IsAdministrator,
+    Actor(actor): Actor,
     Path(network_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, ResponseError> {
     let state = state.lock().await;
@@ -25,8 +293,22 @@ pub async fn create_all_devices(
         .await?
         .remove(0);
 
-    match models_data_entry::create_all_devices(network.network, network_id) {
-        Some(e) => Ok(state.insert::<Device>(e).await?),
+    match models_data_entry::create_all_devices(&network) {
+        Some(e) => {
+            let audit = e
+                .iter()
+                .map(|x| Audit::insert(Some(actor), &DeviceView::new(x.clone(), None)))
+                .collect();
+            let mut tx = state.transaction().await?;
+            tx.insert(e.clone());
+            tx.insert::<Audit>(audit);
+            tx.execute().await?;
+
+            Ok(QueryResult::Insert {
+                row_affect: e.len() as u64,
+                data: e,
+            })
+        }
         None => Err(ResponseError::builder()
             .status(StatusCode::NO_CONTENT)
             .build()),
@@ -35,11 +317,14 @@ pub async fn create_all_devices(
 
 pub async fn get_all(
     State(state): State<RepositoryType>,
+    Extension(oui): Extension<Arc<Oui>>,
     Path(network_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, ResponseError> {
     let state = state.lock().await;
     let condition = HashMap::from([("network_id", network_id.into())]);
-    let devices = state.get::<Device>(Some(condition)).await?;
+    let devices = oui
+        .enrich(state.get::<DeviceView>(Some(condition)).await?)
+        .await;
 
     Ok(Json(json!({
         "length": devices.len(),
@@ -47,95 +332,664 @@ pub async fn get_all(
     })))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn update(
     State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    Extension(transitions): Extension<Arc<Transitions>>,
     _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
     Query(params): Query<ParamsDevice>,
-    Json(device): Json<UpdateDevice>,
+    Json(updater): Json<UpdateDeviceView>,
+) -> Result<QueryResult<DeviceView>, ResponseError> {
+    let state = state.lock().await;
+    let mut audit = Vec::new();
+    let mut tx = state.transaction().await?;
+
+    let before = state
+        .get::<DeviceView>(Some(address_key(params.ip, params.network_id)))
+        .await?
+        .remove(0);
+    let ip = updater.ip.unwrap_or(params.ip);
+    let network_id = updater.network_id.unwrap_or(params.network_id);
+    let moved = ip != params.ip || network_id != params.network_id;
+    let status = updater.status.unwrap_or(before.status.clone());
+    check_transition(&uri, &transitions, &before.status, &status)?;
+
+    let target = if moved {
+        check_network(&state, &uri, ip, network_id).await?;
+        let target = reuse_address(&state, &mut tx, ip, network_id).await?;
+        check_free(&uri, target.as_ref())?;
+        target
+    } else {
+        None
+    };
+
+    let mut address = Address {
+        ip,
+        network_id,
+        device_id: before.device_id,
+        mac: updater.mac.unwrap_or(before.mac),
+        last_seen: match &target {
+            Some(e) => e.last_seen,
+            None if moved => None,
+            None => before.last_seen,
+        },
+        reserved_until: match updater.reserved_until {
+            Some(e) => Some(e),
+            None if status == Status::Reserved => before.reserved_until,
+            None => None,
+        },
+        status,
+    };
+    check_reservation(&uri, &address)?;
+    let previous = moved.then_some((params.ip, params.network_id));
+    check_mac(&state, &uri, &address, previous).await?;
+    if address.device_id.is_some() || !updater.device.is_empty() {
+        check_designated(&state, &uri, ip, network_id).await?;
+    }
+    if address.device_id.is_some()
+        || !updater.device.is_empty()
+        || address.status == Status::Reserved
+    {
+        super::dhcp::check_pool(&state, &uri, ip, network_id).await?;
+    }
+
+    let device = match before.device() {
+        Some(current) if !updater.device.is_empty() => {
+            let device = current.updated(&updater.device);
+            check_name(&state, &uri, &device).await?;
+            tx.update::<Device, _>(
+                updater.device,
+                Some(HashMap::from([("id", device.id.into())])),
+            );
+            audit.push(Audit::update(Some(actor), &current, &device));
+            Some(device)
+        }
+        None if !updater.device.is_empty() => {
+            let device = Device::from(updater.device);
+            check_name(&state, &uri, &device).await?;
+            audit.push(Audit::insert(Some(actor), &device));
+            tx.insert(vec![device.clone()]);
+            Some(device)
+        }
+        device => device,
+    };
+    address.device_id = device.as_ref().map(|x| x.id);
+    let after = DeviceView::new(address.clone(), device);
+
+    if moved {
+        let released = Address::free(params.ip, params.network_id);
+        store_address(&mut tx, released.clone(), true);
+        store_address(&mut tx, address, target.is_some());
+        audit.push(Audit::update(
+            Some(actor),
+            &before,
+            &DeviceView::new(released, None),
+        ));
+        audit.push(match &target {
+            Some(target) => Audit::update(Some(actor), target, &after),
+            None => Audit::insert(Some(actor), &after),
+        });
+    } else {
+        store_address(&mut tx, address, true);
+        audit.push(Audit::update(Some(actor), &before, &after));
+    }
+    tx.insert(audit);
+    services::ip_history::record(&mut tx, Some(&before), Some(&after));
+    tx.execute().await?;
+    services::ddns::publish(&state, &ddns, Some(&before), Some(&after)).await;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn search(
+    State(state): State<RepositoryType>,
+    Extension(oui): Extension<Arc<Oui>>,
+    Query(filter): Query<DeviceFilter>,
 ) -> Result<impl IntoResponse, ResponseError> {
     let state = state.lock().await;
+    let devices = oui.enrich(state.search_devices(filter).await?).await;
 
-    let ip = params.ip;
-    let network_id = params.network_id;
+    Ok(Json(json!({
+        "length": devices.len(),
+        "devices": devices
+    })))
+}
+
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    Extension(oui): Extension<Arc<Oui>>,
+    Query(params): Query<ParamsDevice>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
 
-    if device.network_id.is_some() || device.ip.is_some() {
-        let ip_to_delete: IpAddr;
+    let device = oui
+        .enrich(
+            state
+                .get::<DeviceView>(Some(address_key(params.ip, params.network_id)))
+                .await?,
+        )
+        .await;
 
-        let netw_new = state
-            .get::<Network>(Some(HashMap::from([(
-                "id",
-                match device.network_id {
-                    Some(e) => e.into(),
-                    None => network_id.into(),
-                },
-            )])))
+    Ok(Json(json!({
+        "device": device.first()
+    })))
+}
+
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Query(ParamsDevice { ip, network_id }): Query<ParamsDevice>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    check_designated(&state, &uri, ip, network_id).await?;
+
+    let device = state
+        .get::<DeviceView>(Some(address_key(ip, network_id)))
+        .await?
+        .remove(0);
+
+    let mut tx = state.transaction().await?;
+    tx.soft_delete::<Address>(
+        actor,
+        OffsetDateTime::now_utc(),
+        address_key(ip, network_id),
+    );
+    tx.insert(vec![Audit::delete(Some(actor), &device)]);
+    services::ip_history::record(&mut tx, Some(&device), None);
+    tx.execute().await?;
+    services::ddns::publish(&state, &ddns, Some(&device), None).await;
+
+    Ok(QueryResult::<Address>::Delete(1))
+}
+
+pub async fn history(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Query(params): Query<ParamsDevice>,
+    Query(param): Query<QueryHistory>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let key = json!({ "ip": params.ip, "network_id": params.network_id });
+
+    history::revisions::<DeviceView>(&state, key, param.at).await
+}
+
+// The trail has no passwords, a restored device keeps the one it has now
+async fn password(
+    state: &RepositoryInjection<Postgres>,
+    snapshot: &serde_json::Value,
+) -> Result<Option<String>, RepositoryError> {
+    let Some(Ok(id)) = snapshot
+        .get("device_id")
+        .cloned()
+        .map(serde_json::from_value::<Uuid>)
+    else {
+        return Ok(None);
+    };
+    let condition = || HashMap::from([("id", id.into())]);
+    let current = match state.get::<Device>(Some(condition())).await {
+        Ok(mut e) => e.pop(),
+        Err(RepositoryError::RowNotFound) => state
+            .get_trash::<Device>(Some(condition()))
             .await?
-            .remove(0);
+            .pop()
+            .map(|x| x.data),
+        Err(e) => return Err(e),
+    };
+
+    Ok(current.and_then(|x| x.credential).map(|x| x.password))
+}
 
-        if let Some(ip) = device.ip {
-            if !netw_new.network.contains(&ip) {
-                return Err(ResponseError::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .build());
+pub async fn restore(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(revision): Path<Uuid>,
+    Query(params): Query<ParamsDevice>,
+) -> Result<QueryResult<DeviceView>, ResponseError> {
+    let state = state.lock().await;
+    let key = json!({ "ip": params.ip, "network_id": params.network_id });
+    let snapshot = history::snapshot::<DeviceView>(&state, &uri, revision, key).await?;
+    let password = password(&state, &snapshot).await?;
+    let view: DeviceView = history::parse(&uri, with_password(snapshot, password))?;
+    let mut audit = Vec::new();
+    let mut tx = state.transaction().await?;
+    check_mac(&state, &uri, &view.address(), None).await?;
+
+    if let Some(device) = view.device() {
+        check_name(&state, &uri, &device).await?;
+        let id = device.id;
+        let condition = || HashMap::from([("id", id.into())]);
+        let current = state
+            .get::<Device>(Some(condition()))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop());
+
+        audit.push(match current {
+            Some(before) => {
+                tx.update::<Device, _>(device.clone(), Some(condition()));
+                Audit::update(Some(actor), &before, &device)
             }
-            ip_to_delete = ip;
-        } else {
-            if !netw_new.network.contains(&ip) {
-                return Err(ResponseError::builder()
-                    .status(StatusCode::CONFLICT)
-                    .build());
+            None if !state
+                .get_trash::<Device>(Some(condition()))
+                .await?
+                .is_empty() =>
+            {
+                tx.restore::<Device>(condition());
+                tx.update::<Device, _>(device.clone(), Some(condition()));
+                Audit::restore(Some(actor), &device)
             }
-            ip_to_delete = ip;
-        }
+            None => {
+                tx.insert(vec![device.clone()]);
+                Audit::insert(Some(actor), &device)
+            }
+        });
+    }
 
-        state
-            .delete::<Device>(Some(HashMap::from([
-                ("ip", ip_to_delete.into()),
-                ("network_id", netw_new.id.into()),
-            ])))
-            .await?;
+    let current = reuse_address(&state, &mut tx, view.ip, view.network_id).await?;
+    store_address(&mut tx, view.address(), current.is_some());
+
+    audit.push(match &current {
+        Some(before) => Audit::update(Some(actor), before, &view),
+        None => Audit::insert(Some(actor), &view),
+    });
+    tx.insert(audit);
+    services::ip_history::record(&mut tx, current.as_ref(), Some(&view));
+    tx.execute().await?;
+    services::ddns::publish(&state, &ddns, current.as_ref(), Some(&view)).await;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn services(
+    State(state): State<RepositoryType>,
+    Query(filter): Query<ServiceFilter>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let mut condition: HashMap<&str, TypeTable> = HashMap::new();
+    if let Some(device_id) = filter.device_id {
+        condition.insert("device_id", device_id.into());
+    }
+    if let Some(network_id) = filter.network_id {
+        condition.insert("network_id", network_id.into());
+    }
+    if let Some(port) = filter.port {
+        condition.insert("port", port.into());
+    }
+    if let Some(name) = filter.name {
+        condition.insert("name", name.into());
     }
 
-    Ok(state
-        .update::<Device, _>(
-            device,
-            Some(HashMap::from([
-                ("ip", ip.into()),
-                ("network_id", network_id.into()),
-            ])),
-        )
-        .await?)
+    let services = match state
+        .get::<Service>((!condition.is_empty()).then_some(condition))
+        .await
+    {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    Ok(Json(json!({
+        "length": services.len(),
+        "services": services
+    })))
 }
 
-pub async fn get_one(
+// The lock isn't held while connecting to the ports
+pub async fn probe_services(
     State(state): State<RepositoryType>,
+    Extension(settings): Extension<Arc<probe::Settings>>,
+    _: IsAdministrator,
+    uri: Uri,
     Query(params): Query<ParamsDevice>,
+    body: Option<Json<Probe>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let ports = body
+        .and_then(|x| x.0.ports)
+        .filter(|x| !x.is_empty())
+        .unwrap_or(settings.ports.clone());
+    if ports.len() > 1024 {
+        return Err(ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Too many ports".to_string())
+            .detail("At most 1024 ports can be probed at once".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let view = state
+        .lock()
+        .await
+        .get::<DeviceView>(Some(address_key(params.ip, params.network_id)))
+        .await?
+        .remove(0);
+    if view.device_id.is_none() {
+        return Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The address isn't assigned to a device".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let open = probe::probe(&view, &ports, &settings).await;
+    let services = probe::store(&*state.lock().await, &view, &ports, open).await?;
+
+    Ok(Json(json!({
+        "probed": ports.len(),
+        "length": services.len(),
+        "services": services
+    })))
+}
+
+async fn snmp_target(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    params: &ParamsDevice,
+) -> Result<(DeviceView, Device, Credential), ResponseError> {
+    let view = state
+        .get::<DeviceView>(Some(address_key(params.ip, params.network_id)))
+        .await?
+        .remove(0);
+    let Some(device) = view.device() else {
+        return Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The address isn't assigned to a device".to_string())
+            .instance(uri.to_string())
+            .build());
+    };
+    let Some(credential) = device.credential.clone() else {
+        return Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The device has no SNMP credential".to_string())
+            .instance(uri.to_string())
+            .build());
+    };
+
+    Ok((view, device, credential))
+}
+
+fn snmp_failed(uri: &Uri, error: SnmpError) -> ResponseError {
+    ResponseError::builder()
+        .status(StatusCode::BAD_GATEWAY)
+        .title("The SNMP agent couldn't be polled".to_string())
+        .detail(error.to_string())
+        .instance(uri.to_string())
+        .build()
+}
+
+// The lock isn't held while talking to the agent
+pub async fn poll_snmp(
+    State(state): State<RepositoryType>,
+    Extension(settings): Extension<Arc<snmp::Settings>>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Query(params): Query<ParamsDevice>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let (view, device, credential) = snmp_target(&*state.lock().await, &uri, &params).await?;
+
+    let system = snmp::poll(view.ip, &credential, &settings)
+        .await
+        .map_err(|e| snmp_failed(&uri, e))?;
+    let report = snmp::apply(&*state.lock().await, Some(actor), &view, device, system).await?;
+
+    Ok(Json(report))
+}
+
+// Imports the neighbor table of a router or switch
+pub async fn snmp_neighbors(
+    State(state): State<RepositoryType>,
+    Extension(settings): Extension<Arc<snmp::Settings>>,
+    Extension(transitions): Extension<Arc<Transitions>>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Query(params): Query<ParamsDevice>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let (view, _, credential) = snmp_target(&*state.lock().await, &uri, &params).await?;
+
+    let neighbors = snmp::neighbors(view.ip, &credential, &settings)
+        .await
+        .map_err(|e| snmp_failed(&uri, e))?;
+    let report =
+        services::neighbors::import(&*state.lock().await, Some(actor), neighbors, &transitions)
+            .await?;
+
+    Ok(Json(report))
+}
+
+pub async fn transitions(Extension(transitions): Extension<Arc<Transitions>>) -> impl IntoResponse {
+    Json(json!({ "transitions": *transitions }))
+}
+
+pub async fn ip_history(
+    State(state): State<RepositoryType>,
+    Query(param): Query<QueryIpHistory>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let history = state
+        .get_ip_history(param.ip, param.network_id, param.at)
+        .await?;
+
+    Ok(Json(json!({
+        "length": history.len(),
+        "history": history
+    })))
+}
+
+pub async fn get_device(
+    State(state): State<RepositoryType>,
+    Extension(oui): Extension<Arc<Oui>>,
+    Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, ResponseError> {
     let state = state.lock().await;
 
     let device = state
-        .get::<Device>(Some(HashMap::from([
-            ("ip", params.ip.into()),
-            ("network_id", params.network_id.into()),
-        ])))
-        .await?;
+        .get::<Device>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let addresses = oui
+        .enrich(
+            state
+                .get::<Address>(Some(HashMap::from([("device_id", id.into())])))
+                .await
+                .unwrap_or_default(),
+        )
+        .await;
 
     Ok(Json(json!({
-        "device": device.first()
+        "device": device,
+        "addresses": addresses
     })))
 }
 
-pub async fn delete(
+pub async fn update_device(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(updater): Json<UpdateDevice>,
+) -> Result<QueryResult<Device>, ResponseError> {
+    let state = state.lock().await;
+
+    let before = state
+        .get::<Device>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let views_before = state
+        .get::<DeviceView>(Some(HashMap::from([("device_id", id.into())])))
+        .await
+        .unwrap_or_default();
+    let after = before.updated(&updater);
+    check_name(&state, &uri, &after).await?;
+    let views = views_before
+        .iter()
+        .map(|x| DeviceView::new(x.address(), Some(after.clone())))
+        .collect::<Vec<_>>();
+
+    let mut tx = state.transaction().await?;
+    tx.update::<Device, _>(updater, Some(HashMap::from([("id", id.into())])));
+    let mut audit = vec![Audit::update(Some(actor), &before, &after)];
+    audit.extend(
+        views_before
+            .iter()
+            .zip(&views)
+            .map(|(before, after)| Audit::update(Some(actor), before, after)),
+    );
+    tx.insert(audit);
+    tx.execute().await?;
+
+    for (before, after) in views_before.iter().zip(&views) {
+        services::ddns::publish(&state, &ddns, Some(before), Some(after)).await;
+    }
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn delete_device(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Device>, ResponseError> {
+    let state = state.lock().await;
+    let now = OffsetDateTime::now_utc();
+
+    let device = state
+        .get::<Device>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let views = state
+        .get::<DeviceView>(Some(HashMap::from([("device_id", id.into())])))
+        .await
+        .unwrap_or_default();
+
+    let mut tx = state.transaction().await?;
+    tx.soft_delete::<Device>(actor, now, HashMap::from([("id", id.into())]));
+    tx.soft_delete::<Address>(actor, now, HashMap::from([("device_id", id.into())]));
+
+    let mut audit = vec![Audit::delete(Some(actor), &device)];
+    audit.extend(views.iter().map(|x| Audit::delete(Some(actor), x)));
+    tx.insert(audit);
+
+    for i in &views {
+        services::ip_history::record(&mut tx, Some(i), None);
+    }
+    tx.execute().await?;
+
+    for i in &views {
+        services::ddns::publish(&state, &ddns, Some(i), None).await;
+    }
+
+    Ok(QueryResult::Delete(1))
+}
+
+pub async fn assign(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(params): Json<Assignment>,
+) -> Result<QueryResult<DeviceView>, ResponseError> {
+    let state = state.lock().await;
+
+    let device = state
+        .get::<Device>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let mut tx = state.transaction().await?;
+    check_network(&state, &uri, params.ip, params.network_id).await?;
+    let current = reuse_address(&state, &mut tx, params.ip, params.network_id).await?;
+    check_free(&uri, current.as_ref())?;
+
+    let address = Address {
+        ip: params.ip,
+        network_id: params.network_id,
+        device_id: Some(id),
+        status: current
+            .as_ref()
+            .map(|x| x.status.clone())
+            .unwrap_or_default(),
+        mac: params.mac,
+        last_seen: current.as_ref().and_then(|x| x.last_seen),
+        reserved_until: current.as_ref().and_then(|x| x.reserved_until),
+    };
+    check_mac(&state, &uri, &address, None).await?;
+    check_designated(&state, &uri, address.ip, address.network_id).await?;
+    super::dhcp::check_pool(&state, &uri, address.ip, address.network_id).await?;
+    let view = DeviceView::new(address.clone(), Some(device));
+    store_address(&mut tx, address, current.is_some());
+
+    let audit = match &current {
+        Some(before) => Audit::update(Some(actor), before, &view),
+        None => Audit::insert(Some(actor), &view),
+    };
+    tx.insert(vec![audit]);
+    services::ip_history::record(&mut tx, current.as_ref(), Some(&view));
+    tx.execute().await?;
+    services::ddns::publish(&state, &ddns, current.as_ref(), Some(&view)).await;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![view],
+    })
+}
+
+pub async fn unassign(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ParamsDevice>,
+) -> Result<QueryResult<DeviceView>, ResponseError> {
+    let state = state.lock().await;
+
+    let before = state
+        .get::<DeviceView>(Some(address_key(params.ip, params.network_id)))
+        .await?
+        .remove(0);
+    if before.device_id != Some(id) {
+        return Err(ResponseError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("The address isn't assigned to the device".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let address = Address::free(params.ip, params.network_id);
+    let after = DeviceView::new(address.clone(), None);
+    let mut tx = state.transaction().await?;
+    store_address(&mut tx, address, true);
+    tx.insert(vec![Audit::update(Some(actor), &before, &after)]);
+    services::ip_history::record(&mut tx, Some(&before), Some(&after));
+    tx.execute().await?;
+    services::ddns::publish(&state, &ddns, Some(&before), Some(&after)).await;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn device_history(
     State(state): State<RepositoryType>,
     _: IsAdministrator,
-    Query((ip, network_id)): Query<(IpAddr, Uuid)>,
+    Path(id): Path<Uuid>,
+    Query(param): Query<QueryHistory>,
 ) -> Result<impl IntoResponse, ResponseError> {
     let state = state.lock().await;
 
-    Ok(state
-        .delete::<Device>(Some(HashMap::from([
-            ("ip", ip.into()),
-            ("network_id", network_id.into()),
-        ])))
-        .await?)
+    history::revisions::<Device>(&state, json!({ "id": id }), param.at).await
 }
@@ -0,0 +1,330 @@
+use super::*;
+use crate::{
+    database::repository::QueryResult,
+    models::{
+        audit::Audit,
+        device::{Address, Status},
+        dhcp::*,
+        network::Network,
+    },
+};
+use libipam::ipam_services::dhcp::Range;
+use models_data_entry::ParamsDevice;
+use params::dhcp::QueryDhcp;
+use sqlx::Postgres;
+use std::net::IpAddr;
+
+async fn scopes(
+    state: &RepositoryInjection<Postgres>,
+    network_id: Uuid,
+) -> Result<Vec<Scope>, ResponseError> {
+    match state
+        .get::<Scope>(Some(HashMap::from([("network_id", network_id.into())])))
+        .await
+    {
+        Err(RepositoryError::RowNotFound) => Ok(Vec::new()),
+        e => Ok(e?),
+    }
+}
+
+async fn check_scope(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    scope: &Scope,
+) -> Result<(), ResponseError> {
+    let network = state
+        .get::<Network>(Some(HashMap::from([("id", scope.network_id.into())])))
+        .await?
+        .remove(0);
+    let bad_request = |title: &str, detail: String| {
+        ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title(title.to_string())
+            .detail(detail)
+            .instance(uri.to_string())
+            .build()
+    };
+    let conflict = |title: &str, detail: String| {
+        ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title(title.to_string())
+            .detail(detail)
+            .instance(uri.to_string())
+            .build()
+    };
+
+    let range = Range::new(scope.range.start, scope.range.end, &network.network)
+        .map_err(|e| bad_request("Invalid range", e.to_string()))?;
+
+    if let Some(gateway) = scope.gateway {
+        if !network.network.contains(&gateway) || range.contains(&gateway) {
+            return Err(bad_request(
+                "Invalid gateway",
+                format!(
+                    "{} must be inside {} and outside of {}",
+                    gateway, network.network, range
+                ),
+            ));
+        }
+    }
+
+    if scope.lease_time <= 0 {
+        return Err(bad_request(
+            "Invalid lease time",
+            "The lease time must be positive".to_string(),
+        ));
+    }
+
+    if let Some(e) = scopes(state, scope.network_id)
+        .await?
+        .iter()
+        .find(|x| x.id != scope.id && x.range.overlaps(&range))
+    {
+        return Err(conflict(
+            "The range overlaps another scope",
+            format!("{} overlaps {} of the scope {}", range, e.range, e.id),
+        ));
+    }
+
+    if let Some(ip) = network.designated().iter().find(|x| range.contains(x)) {
+        return Err(conflict(
+            "The range collides with designated addresses",
+            format!(
+                "{} is {} of {}",
+                ip,
+                network.designation(ip).unwrap_or_default(),
+                network.network
+            ),
+        ));
+    }
+
+    let used: Vec<String> = state
+        .get::<Address>(Some(HashMap::from([(
+            "network_id",
+            scope.network_id.into(),
+        )])))
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|x| x.device_id.is_some() || x.status == Status::Reserved)
+        .filter(|x| range.contains(&x.ip))
+        .map(|x| x.ip.to_string())
+        .collect();
+    if !used.is_empty() {
+        return Err(conflict(
+            "The range collides with static addresses",
+            format!("{} are assigned or reserved", used.join(", ")),
+        ));
+    }
+
+    Ok(())
+}
+
+// Static and reserved addresses can't be handed out by a dhcp range
+pub(super) async fn check_pool(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    ip: IpAddr,
+    network_id: Uuid,
+) -> Result<(), ResponseError> {
+    match scopes(state, network_id)
+        .await?
+        .iter()
+        .find(|x| x.range.contains(&ip))
+    {
+        Some(e) => Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The address is inside a DHCP range".to_string())
+            .detail(format!(
+                "{} belongs to {} of the scope {}",
+                ip, e.range, e.id
+            ))
+            .instance(uri.to_string())
+            .build()),
+        None => Ok(()),
+    }
+}
+
+pub async fn get_scopes(
+    State(state): State<RepositoryType>,
+    Query(param): Query<QueryDhcp>,
+) -> Result<QueryResult<Scope>, ResponseError> {
+    let state = state.lock().await;
+    let condition = param
+        .network_id
+        .map(|x| HashMap::from([("network_id", x.into())]));
+
+    Ok(state.get::<Scope>(condition).await?.into())
+}
+
+pub async fn create_scope(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Json(scope): Json<models_data_entry::Scope>,
+) -> Result<QueryResult<Scope>, ResponseError> {
+    let state = state.lock().await;
+    let scope: Scope = scope.into();
+    check_scope(&state, &uri, &scope).await?;
+
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![scope.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &scope)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![scope],
+    })
+}
+
+pub async fn update_scope(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(updater): Json<UpdateScope>,
+) -> Result<QueryResult<Scope>, ResponseError> {
+    let state = state.lock().await;
+
+    let before = state
+        .get::<Scope>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let after = before.updated(updater);
+    check_scope(&state, &uri, &after).await?;
+
+    let mut tx = state.transaction().await?;
+    tx.update::<Scope, _>(after.clone(), Some(HashMap::from([("id", id.into())])));
+    tx.insert(vec![Audit::update(Some(actor), &before, &after)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn delete_scope(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Scope>, ResponseError> {
+    let state = state.lock().await;
+
+    let scope = state
+        .get::<Scope>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+
+    let mut tx = state.transaction().await?;
+    tx.delete::<Scope>(Some(HashMap::from([("id", id.into())])));
+    tx.insert(vec![Audit::delete(Some(actor), &scope)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Delete(1))
+}
+
+pub async fn get_reservations(
+    State(state): State<RepositoryType>,
+    Query(param): Query<QueryDhcp>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let reservations = state.get_reservations(param.network_id).await?;
+
+    Ok(Json(json!({
+        "length": reservations.len(),
+        "reservations": reservations
+    })))
+}
+
+pub async fn create_reservation(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Json(params): Json<ParamsDevice>,
+) -> Result<QueryResult<Reservation>, ResponseError> {
+    let state = state.lock().await;
+    let key = || {
+        HashMap::from([
+            ("ip", params.ip.into()),
+            ("network_id", params.network_id.into()),
+        ])
+    };
+    let conflict = |title: &str, ip: IpAddr| {
+        ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title(title.to_string())
+            .detail(format!("{} can't be reserved", ip))
+            .instance(uri.to_string())
+            .build()
+    };
+
+    let address = state.get::<Address>(Some(key())).await?.remove(0);
+    let Some(device_id) = address.device_id else {
+        return Err(conflict(
+            "The address isn't assigned to a device",
+            address.ip,
+        ));
+    };
+    if address.mac.is_none() {
+        return Err(conflict(
+            "The address doesn't have a mac address",
+            address.ip,
+        ));
+    }
+    if state.get::<Reservation>(Some(key())).await.is_ok() {
+        return Err(conflict("The address is already reserved", address.ip));
+    }
+    if let Some(e) = scopes(&state, address.network_id)
+        .await?
+        .iter()
+        .find(|x| x.range.contains(&address.ip))
+    {
+        return Err(conflict(
+            &format!("The address is inside the range {}", e.range),
+            address.ip,
+        ));
+    }
+
+    let reservation = Reservation {
+        ip: address.ip,
+        network_id: address.network_id,
+        device_id,
+    };
+
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![reservation.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &reservation)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![reservation],
+    })
+}
+
+pub async fn delete_reservation(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Query(params): Query<ParamsDevice>,
+) -> Result<QueryResult<Reservation>, ResponseError> {
+    let state = state.lock().await;
+    let key = || {
+        HashMap::from([
+            ("ip", params.ip.into()),
+            ("network_id", params.network_id.into()),
+        ])
+    };
+
+    let reservation = state.get::<Reservation>(Some(key())).await?.remove(0);
+
+    let mut tx = state.transaction().await?;
+    tx.delete::<Reservation>(Some(key()));
+    tx.insert(vec![Audit::delete(Some(actor), &reservation)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Delete(1))
+}
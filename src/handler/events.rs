@@ -0,0 +1,49 @@
+use super::*;
+use crate::{
+    models::audit::EventFilter,
+    services::events::{redact, subscribe},
+};
+use axum::{
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::{stream, Stream};
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+// Streams every committed change the caller may see. A "lagged" event tells
+// the client it missed some and should reload
+pub async fn events(
+    _: Actor,
+    Extension(role): Extension<Role>,
+    Query(filter): Query<EventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let events = stream::unfold(
+        (subscribe(), role, filter),
+        |(mut rx, role, filter)| async move {
+            loop {
+                let event = match rx.recv().await {
+                    Ok(e) => e,
+                    Err(RecvError::Lagged(e)) => {
+                        let event = Event::default()
+                            .event("lagged")
+                            .json_data(json!({ "missed": e }));
+                        return Some((event, (rx, role, filter)));
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                let Some(event) = redact(event, &role).filter(|x| filter.matches(x)) else {
+                    continue;
+                };
+
+                let event = Event::default()
+                    .event(format!("{:?}", event.action).to_lowercase())
+                    .id(event.id.to_string())
+                    .json_data(&event);
+                return Some((event, (rx, role, filter)));
+            }
+        },
+    );
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
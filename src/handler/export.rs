@@ -0,0 +1,81 @@
+use super::*;
+use crate::services::{dns, kea};
+use axum::http::header;
+use libipam::{
+    ipam_services::{
+        dns::is_reverse_zone,
+        kea::{dhcp4, dhcp6},
+    },
+    type_net::dns::DomainName,
+};
+use params::export::QueryKea;
+
+pub async fn dns_zones(
+    State(state): State<RepositoryType>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let (forward, reverse) = dns::zones(&state).await?;
+
+    Ok(Json(json!({
+        "forward": forward,
+        "reverse": reverse,
+    })))
+}
+
+pub async fn dns_zone(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(zone): Path<String>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let zone = zone.trim_end_matches('.').to_lowercase();
+
+    if !is_reverse_zone(&zone) {
+        if let Err(e) = zone.parse::<DomainName>() {
+            return Err(ResponseError::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .title("Invalid zone".to_string())
+                .detail(e.to_string())
+                .instance(uri.to_string())
+                .build());
+        }
+    }
+
+    let Some(primary) = dns::primary_ns() else {
+        return Err(ResponseError::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .title("The primary name server isn't configured".to_string())
+            .detail("Set DNS_PRIMARY_NS to export zones".to_string())
+            .instance(uri.to_string())
+            .build());
+    };
+
+    let state = state.lock().await;
+    match dns::zone(&state, &zone, &primary).await? {
+        Some(e) => Ok(([(header::CONTENT_TYPE, "text/plain")], e.render())),
+        None => Err(ResponseError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Zone not found".to_string())
+            .detail(format!("There aren't records for the zone {}", zone))
+            .instance(uri.to_string())
+            .build()),
+    }
+}
+
+pub async fn kea(
+    State(state): State<RepositoryType>,
+    Query(param): Query<QueryKea>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let subnets = kea::subnets(&state).await?;
+    let mut resp = serde_json::Map::new();
+
+    if param.family.is_none_or(|x| x == 4) {
+        resp.insert("Dhcp4".to_string(), dhcp4(&subnets));
+    }
+
+    if param.family.is_none_or(|x| x == 6) {
+        resp.insert("Dhcp6".to_string(), dhcp6(&subnets));
+    }
+
+    Ok(Json(resp))
+}
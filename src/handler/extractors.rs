@@ -1,9 +1,12 @@
-use super::{ResponseError, Role};
+use super::{ResponseError, Role, Uuid};
+use crate::services::Claims;
 use axum::{extract::FromRequestParts, http::request::Parts};
 use std::{future::Future, pin::Pin};
 
 pub struct IsAdministrator;
 
+pub struct Actor(pub Uuid);
+
 impl<S> FromRequestParts<S> for IsAdministrator {
     type Rejection = ResponseError;
     fn from_request_parts<'a, 'b, 'c>(
@@ -28,3 +31,25 @@ impl<S> FromRequestParts<S> for IsAdministrator {
         Box::pin(resp)
     }
 }
+
+impl<S> FromRequestParts<S> for Actor {
+    type Rejection = ResponseError;
+    fn from_request_parts<'a, 'b, 'c>(
+        parts: &'a mut Parts,
+        _state: &'b S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'c>>
+    where
+        'a: 'c,
+        'b: 'c,
+        Self: 'c,
+    {
+        let resp = async {
+            match parts.extensions.get::<Claims>() {
+                Some(claims) => Ok(Self(claims.id)),
+                None => Err(ResponseError::unauthorized(&parts.uri, None)),
+            }
+        };
+
+        Box::pin(resp)
+    }
+}
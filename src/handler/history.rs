@@ -0,0 +1,87 @@
+use super::*;
+use crate::{
+    database::RepositoryInjection,
+    models::audit::{Audit, AuditFilter, Auditable},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::Postgres;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+pub async fn revisions<T: Auditable>(
+    state: &RepositoryInjection<Postgres>,
+    key: Value,
+    at: Option<OffsetDateTime>,
+) -> Result<Json<Value>, ResponseError> {
+    let revisions = state
+        .get_audit(AuditFilter {
+            entity: Some(T::ENTITY),
+            key: Some(key),
+            until: at,
+            ..Default::default()
+        })
+        .await?;
+
+    Ok(Json(match at {
+        Some(at) => {
+            let last = revisions.last();
+            json!({
+                "at": at.format(&Rfc3339).ok(),
+                "revision": last.map(|x| x.id),
+                "state": last.and_then(|x| x.after.clone()),
+            })
+        }
+        None => json!({
+            "length": revisions.len(),
+            "revisions": revisions,
+        }),
+    }))
+}
+
+pub async fn snapshot<T: Auditable>(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    id: Uuid,
+    key: Value,
+) -> Result<Value, ResponseError> {
+    let revision = state
+        .get::<Audit>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+
+    if revision.entity != T::ENTITY || revision.key != key {
+        return Err(ResponseError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Revision not found".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    revision.after.ok_or_else(|| {
+        ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("The revision doesn't contain a state to restore".to_string())
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+pub fn parse<T: DeserializeOwned>(uri: &Uri, snapshot: Value) -> Result<T, ResponseError> {
+    serde_json::from_value(snapshot).map_err(|e| {
+        ResponseError::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .title("Invalid revision".to_string())
+            .detail(e.to_string())
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+pub async fn revision<T: Auditable + DeserializeOwned>(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    id: Uuid,
+    key: Value,
+) -> Result<T, ResponseError> {
+    parse(uri, snapshot::<T>(state, uri, id, key).await?)
+}
@@ -0,0 +1,87 @@
+use super::*;
+use crate::{
+    models::import::ImportReport,
+    services::{self, ddns::Ddns, status::Transitions},
+};
+use axum::Extension;
+use libipam::ipam_services::{leases, neighbors};
+use params::import::QueryImport;
+
+pub async fn leases(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    body: String,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let report = services::leases::import(&state, actor, leases::parse(&body)).await?;
+
+    Ok(Json(report))
+}
+
+pub async fn neighbors(
+    State(state): State<RepositoryType>,
+    Extension(transitions): Extension<Arc<Transitions>>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    body: String,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let report =
+        services::neighbors::import(&state, Some(actor), neighbors::parse(&body), &transitions)
+            .await?;
+
+    Ok(Json(report))
+}
+
+// A refused import answers 422 with the errors of every row
+fn imported(report: ImportReport) -> impl IntoResponse {
+    let status = match report.dry_run || report.committed() {
+        true => StatusCode::OK,
+        false => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+
+    (status, Json(report))
+}
+
+pub async fn devices(
+    State(state): State<RepositoryType>,
+    Extension(ddns): Extension<Ddns>,
+    Extension(transitions): Extension<Arc<Transitions>>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Query(param): Query<QueryImport>,
+    body: String,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let report = services::import::devices(
+        &state,
+        &ddns,
+        &transitions,
+        actor,
+        param.rows(&body),
+        param.dry_run.unwrap_or_default(),
+    )
+    .await?;
+
+    Ok(imported(report))
+}
+
+pub async fn networks(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Query(param): Query<QueryImport>,
+    body: String,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let report = services::import::networks(
+        &state,
+        actor,
+        param.rows(&body),
+        param.dry_run.unwrap_or_default(),
+    )
+    .await?;
+
+    Ok(imported(report))
+}
@@ -1,13 +1,28 @@
+pub mod audit;
 pub mod auth;
 pub mod device;
+pub mod dhcp;
 pub mod error;
+pub mod events;
+pub mod export;
 pub mod extractors;
+pub mod import;
+mod history;
 mod models_data_entry;
 pub mod network;
+pub mod office;
 mod params;
+pub mod report;
+pub mod scan;
+pub mod tools;
+pub mod trash;
+pub mod webhook;
 
 use crate::{
-    database::{repository::Repository, RepositoryInjection},
+    database::{
+        repository::{error::RepositoryError, Repository},
+        RepositoryInjection,
+    },
     models::{self, user::Role},
 };
 
@@ -16,7 +31,7 @@ use axum::{
     http::{StatusCode, Uri},
     response::IntoResponse,
 };
-use extractors::IsAdministrator;
+use extractors::{Actor, IsAdministrator};
 use libipam::response_error::ResponseError;
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
@@ -24,3 +39,103 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 
 type RepositoryType = Arc<Mutex<RepositoryInjection<sqlx::postgres::Postgres>>>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::services::{
+        ddns::{Ddns, Disabled},
+        Claims,
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request},
+        routing::delete,
+        Extension, Router,
+    };
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let state: RepositoryType = Arc::new(Mutex::new(RepositoryInjection::unreachable()));
+        let ddns: Ddns = Arc::new(Disabled);
+
+        Router::new()
+            .route("/network", delete(network::delete).patch(network::update))
+            .route("/device/delete", delete(device::delete))
+            .layer(Extension(ddns))
+            .with_state(state)
+    }
+
+    async fn send(method: Method, uri: &str, body: &'static str) -> String {
+        let mut req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        req.extensions_mut().insert(Role::Admin);
+        req.extensions_mut().insert(Claims {
+            exp: 0,
+            id: Uuid::new_v4(),
+            role: Role::Admin,
+        });
+
+        let resp = app().oneshot(req).await.unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8_lossy(&body).to_string()
+    }
+
+    // Past the extractors the handler's first lookup finds no row, a rejected
+    // query string never gets that far
+    #[tokio::test]
+    async fn network_delete_reaches_handler() {
+        let uri = format!("/network?id={}", Uuid::new_v4());
+        assert!(send(Method::DELETE, &uri, "")
+            .await
+            .contains("Row not found"));
+        assert!(send(Method::DELETE, "/network", "")
+            .await
+            .starts_with("Failed to deserialize query string"));
+    }
+
+    #[tokio::test]
+    async fn device_delete_reaches_handler() {
+        let uri = format!(
+            "/device/delete?ip=192.168.0.10&network_id={}",
+            Uuid::new_v4()
+        );
+        assert!(send(Method::DELETE, &uri, "")
+            .await
+            .contains("Row not found"));
+        assert!(send(Method::DELETE, "/device/delete?ip=192.168.0.10", "")
+            .await
+            .starts_with("Failed to deserialize query string"));
+    }
+
+    #[tokio::test]
+    async fn network_update_reaches_handler() {
+        let uri = format!("/network?id={}", Uuid::new_v4());
+        let body = r#"{"description":"Servers"}"#;
+        assert!(send(Method::PATCH, &uri, body)
+            .await
+            .contains("Row not found"));
+        assert!(send(Method::PATCH, "/network", body)
+            .await
+            .starts_with("Failed to deserialize query string"));
+    }
+
+    #[tokio::test]
+    async fn network_designation_reaches_handler() {
+        let uri = format!("/network?id={}", Uuid::new_v4());
+        for body in [
+            r#"{"gateway":"192.168.0.1","infrastructure":["192.168.0.2"]}"#,
+            r#"{"gateway":null}"#,
+        ] {
+            assert!(send(Method::PATCH, &uri, body)
+                .await
+                .contains("Row not found"));
+        }
+    }
+}
@@ -1,6 +1,20 @@
-use super::models::{device, network};
+use super::models::{
+    audit::{Action, Entity},
+    device, dhcp, network, office, scan, webhook,
+};
 use ipnet::IpNet;
-use libipam::type_net::vlan::Vlan;
+use libipam::{
+    ipam_services::{
+        designation::{gateway, GatewayPolicy},
+        dhcp::Range,
+    },
+    type_net::{
+        dns::{DomainName, Hostname},
+        mac::MacAddr,
+        port::Port,
+        vlan::Vlan,
+    },
+};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 use uuid::Uuid;
@@ -16,6 +30,58 @@ pub struct Network {
     pub network: IpNet,
     pub description: Option<String>,
     pub vlan: Option<Vlan>,
+    pub gateway: Option<IpAddr>,
+    #[serde(default)]
+    pub gateway_policy: GatewayPolicy,
+    #[serde(default)]
+    pub infrastructure: Vec<IpAddr>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Office {
+    pub name: String,
+    pub address: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Scope {
+    pub network_id: Uuid,
+    pub range: Range,
+    pub gateway: Option<IpAddr>,
+    #[serde(default)]
+    pub dns_servers: Vec<IpAddr>,
+    pub lease_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Schedule {
+    pub network_id: Uuid,
+    pub interval: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Webhook {
+    pub url: String,
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    pub network_id: Option<Uuid>,
+    pub office_id: Option<Uuid>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Probe {
+    pub ports: Option<Vec<Port>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParamsNetwork {
+    pub id: Uuid,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,17 +90,76 @@ pub struct ParamsDevice {
     pub network_id: uuid::Uuid,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Assignment {
+    pub ip: IpAddr,
+    pub network_id: uuid::Uuid,
+    pub mac: Option<MacAddr>,
+}
+
 impl From<Network> for network::Network {
     fn from(value: Network) -> Self {
-        let avl = 2_u32.pow(32 - value.network.prefix_len() as u32) - 2;
+        Self::new(
+            value.network,
+            value.description,
+            value.vlan,
+            value
+                .gateway
+                .or(gateway(&value.network, value.gateway_policy)),
+            value.infrastructure,
+        )
+    }
+}
+
+impl From<Scope> for dhcp::Scope {
+    fn from(value: Scope) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            network_id: value.network_id,
+            range: value.range,
+            gateway: value.gateway,
+            dns_servers: value.dns_servers,
+            lease_time: value.lease_time.unwrap_or(86400),
+        }
+    }
+}
+
+impl From<Schedule> for scan::Schedule {
+    fn from(value: Schedule) -> Self {
+        Self {
+            network_id: value.network_id,
+            interval: value.interval.unwrap_or(3600),
+            enabled: value.enabled.unwrap_or(true),
+            last_run: None,
+        }
+    }
+}
+
+// Without a secret one is generated and returned once on creation
+impl From<Webhook> for webhook::Webhook {
+    fn from(value: Webhook) -> Self {
         Self {
             id: Uuid::new_v4(),
-            network: value.network,
+            url: value.url,
+            secret: value.secret.filter(|x| !x.is_empty()).unwrap_or_else(|| {
+                format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+            }),
+            entities: value.entities,
+            actions: value.actions,
+            network_id: value.network_id,
+            office_id: value.office_id,
+            enabled: value.enabled.unwrap_or(true),
+        }
+    }
+}
+
+impl From<Office> for office::Office {
+    fn from(value: Office) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: value.name,
+            address: value.address,
             description: value.description,
-            available: avl.into(),
-            used: 0.into(),
-            free: avl.into(),
-            vlan: value.vlan,
         }
     }
 }
@@ -42,44 +167,57 @@ impl From<Network> for network::Network {
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Device {
     pub ip: IpAddr,
+    pub hostname: Option<Hostname>,
+    pub domain: Option<DomainName>,
+    pub kind: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
     pub description: Option<String>,
     pub office_id: Option<Uuid>,
     pub rack: Option<String>,
     pub room: Option<String>,
     pub status: Option<device::Status>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub reserved_until: Option<time::OffsetDateTime>,
     pub network_id: uuid::Uuid,
+    pub mac: Option<MacAddr>,
     pub credential: Option<device::Credential>,
 }
 
-impl From<Device> for device::Device {
+impl From<Device> for device::DeviceView {
     fn from(value: Device) -> Self {
         Self {
-            status: device::Status::default(),
             ip: value.ip,
+            network_id: value.network_id,
+            status: value.status.unwrap_or_default(),
+            device_id: Some(Uuid::new_v4()),
+            mac: value.mac,
+            last_seen: None,
+            reserved_until: value.reserved_until,
+            hostname: value.hostname,
+            domain: value.domain,
+            kind: value.kind,
+            model: value.model,
+            serial: value.serial,
             description: value.description,
             office_id: value.office_id,
             rack: value.rack,
             room: value.room,
-            network_id: value.network_id,
             credential: value.credential,
         }
     }
 }
 
-pub fn create_all_devices(network: IpNet, id: Uuid) -> Option<Vec<device::Device>> {
-    let ips = network.hosts().collect::<Vec<IpAddr>>();
+pub fn create_all_devices(network: &network::Network) -> Option<Vec<device::Address>> {
+    let ips = network.network.hosts().collect::<Vec<IpAddr>>();
+    let designated = network.designated();
     let mut resp = Vec::new();
     for ip in ips {
-        resp.push(device::Device {
-            ip,
-            description: None,
-            office_id: None,
-            rack: None,
-            room: None,
-            status: device::Status::default(),
-            network_id: id,
-            credential: None,
-        });
+        let mut address = device::Address::free(ip, network.id);
+        if designated.contains(&ip) {
+            address.status = device::Status::Reserved;
+        }
+        resp.push(address);
     }
 
     if !resp.is_empty() {
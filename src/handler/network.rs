@@ -2,18 +2,121 @@ use super::RepositoryType;
 use super::*;
 use crate::{
     database::{repository::QueryResult, transaction::BuilderPgTransaction},
-    models::network::*,
+    models::{
+        audit::Audit,
+        device::{Address, DeviceView, Status},
+        network::*,
+    },
+    services::ip_history,
 };
-use params::network::QueryNetwork;
+use libipam::ipam_services::designation::is_unusable;
+use models_data_entry::ParamsNetwork;
+use params::{history::QueryHistory, network::QueryNetwork};
+use sqlx::Postgres;
+use time::OffsetDateTime;
+
+async fn check_designation(
+    state: &RepositoryInjection<Postgres>,
+    uri: &Uri,
+    network: &Network,
+) -> Result<(), ResponseError> {
+    for ip in network.designated() {
+        if !network.network.contains(&ip) || is_unusable(&network.network, &ip) {
+            return Err(ResponseError::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .title("Invalid designated address".to_string())
+                .detail(format!(
+                    "{} isn't a usable address of {}",
+                    ip, network.network
+                ))
+                .instance(uri.to_string())
+                .build());
+        }
+    }
+
+    let designated = network.designated();
+    let used = state
+        .get::<Address>(Some(HashMap::from([("network_id", network.id.into())])))
+        .await
+        .unwrap_or_default();
+    match used
+        .iter()
+        .find(|x| x.device_id.is_some() && designated.contains(&x.ip))
+    {
+        Some(e) => Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The designated address is assigned to a device".to_string())
+            .detail(format!("{} is used by {}", e.ip, e.device_id.unwrap()))
+            .instance(uri.to_string())
+            .build()),
+        None => Ok(()),
+    }
+}
+
+// Designated addresses are kept reserved, released ones go back to unknown
+async fn designate(
+    state: &RepositoryInjection<Postgres>,
+    tx: &mut BuilderPgTransaction<'_>,
+    actor: Uuid,
+    before: &Network,
+    after: &Network,
+) {
+    let (previous, designated) = (before.designated(), after.designated());
+    let mut audit = Vec::new();
+
+    for i in state
+        .get::<DeviceView>(Some(HashMap::from([("network_id", after.id.into())])))
+        .await
+        .unwrap_or_default()
+    {
+        let status = match (previous.contains(&i.ip), designated.contains(&i.ip)) {
+            (_, true) => Status::Reserved,
+            (true, false) if i.status == Status::Reserved => Status::Unknown,
+            _ => continue,
+        };
+        if i.device_id.is_some() || i.status == status {
+            continue;
+        }
+
+        let view = DeviceView {
+            status,
+            ..i.clone()
+        };
+        tx.update::<Address, _>(
+            view.address(),
+            Some(HashMap::from([
+                ("ip", i.ip.into()),
+                ("network_id", i.network_id.into()),
+            ])),
+        );
+        audit.push(Audit::update(Some(actor), &i, &view));
+    }
+
+    if !audit.is_empty() {
+        tx.insert(audit);
+    }
+}
 
 pub async fn create(
     State(state): State<RepositoryType>,
     _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
     Json(netw): Json<models_data_entry::Network>,
 ) -> Result<QueryResult<Network>, ResponseError> {
     let state = state.lock().await;
+    let network: Network = netw.into();
+    check_designation(&state, &uri, &network).await?;
 
-    Ok(state.insert::<Network>(vec![netw.into()]).await?)
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![network.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &network)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![network],
+    })
 }
 
 pub async fn get(
@@ -28,27 +131,122 @@ pub async fn get(
 pub async fn update(
     State(state): State<RepositoryType>,
     _: IsAdministrator,
-    Query(id): Query<Uuid>,
+    Actor(actor): Actor,
+    uri: Uri,
+    Query(ParamsNetwork { id }): Query<ParamsNetwork>,
     Json(updater): Json<UpdateNetwork>,
 ) -> Result<QueryResult<Network>, ResponseError> {
     let state = state.lock().await;
-    let tr = state.begin().await.unwrap();
-    let state = Arc::new(Mutex::new(tr));
-    let tmp = BuilderPgTransaction::new(state.clone()).await;
-    println!("{:?}", Arc::strong_count(&state));
-    let tr = Arc::try_unwrap(state).unwrap().into_inner();
-    tr.commit().await.unwrap();
-    Ok(QueryResult::Update(50))
+
+    let before = state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let after = before.updated(&updater);
+    check_designation(&state, &uri, &after).await?;
+
+    let mut tx = state.transaction().await?;
+    tx.update::<Network, _>(updater, Some(HashMap::from([("id", id.into())])));
+    tx.insert(vec![Audit::update(Some(actor), &before, &after)]);
+    designate(&state, &mut tx, actor, &before, &after).await;
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
 }
 
 pub async fn delete(
     State(state): State<RepositoryType>,
     _: IsAdministrator,
-    Query(id): Query<Uuid>,
+    Actor(actor): Actor,
+    Query(ParamsNetwork { id }): Query<ParamsNetwork>,
 ) -> Result<QueryResult<Network>, ResponseError> {
     let state = state.lock().await;
+    let now = OffsetDateTime::now_utc();
+
+    let network = state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let devices = state
+        .get::<DeviceView>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .unwrap_or_default();
+
+    let mut tx = state.transaction().await?;
+    tx.soft_delete::<Network>(actor, now, HashMap::from([("id", id.into())]));
+    tx.soft_delete::<Address>(actor, now, HashMap::from([("network_id", id.into())]));
+
+    let mut audit = vec![Audit::delete(Some(actor), &network)];
+    audit.extend(devices.iter().map(|x| Audit::delete(Some(actor), x)));
+    tx.insert(audit);
+
+    for i in &devices {
+        ip_history::record(&mut tx, Some(i), None);
+    }
+    tx.execute().await?;
+
+    Ok(QueryResult::Delete(1))
+}
+
+pub async fn history(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Path(id): Path<Uuid>,
+    Query(param): Query<QueryHistory>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+
+    history::revisions::<Network>(&state, json!({ "id": id }), param.at).await
+}
+
+pub async fn restore(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path((id, revision)): Path<(Uuid, Uuid)>,
+) -> Result<QueryResult<Network>, ResponseError> {
+    let state = state.lock().await;
+    let network = history::revision::<Network>(&state, &uri, revision, json!({ "id": id }))
+        .await?
+        .with_broadcast();
+    check_designation(&state, &uri, &network).await?;
+
+    let condition = || HashMap::from([("id", id.into())]);
+    let current = state
+        .get::<Network>(Some(condition()))
+        .await
+        .ok()
+        .and_then(|mut x| x.pop());
+
+    let mut tx = state.transaction().await?;
+    let resp = match current {
+        Some(before) => {
+            tx.update::<Network, _>(network.clone(), Some(condition()));
+            tx.insert(vec![Audit::update(Some(actor), &before, &network)]);
+            designate(&state, &mut tx, actor, &before, &network).await;
+            QueryResult::Update(1)
+        }
+        None if !state
+            .get_trash::<Network>(Some(condition()))
+            .await?
+            .is_empty() =>
+        {
+            tx.restore::<Network>(condition());
+            tx.update::<Network, _>(network.clone(), Some(condition()));
+            tx.insert(vec![Audit::restore(Some(actor), &network)]);
+            QueryResult::Update(1)
+        }
+        None => {
+            tx.insert(vec![network.clone()]);
+            tx.insert(vec![Audit::insert(Some(actor), &network)]);
+            QueryResult::Insert {
+                row_affect: 1,
+                data: vec![network],
+            }
+        }
+    };
+    tx.execute().await?;
 
-    Ok(state
-        .delete::<Network>(Some(HashMap::from([("id", id.into())])))
-        .await?)
+    Ok(resp)
 }
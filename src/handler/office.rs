@@ -0,0 +1,82 @@
+use super::*;
+use crate::{
+    database::repository::QueryResult,
+    models::{audit::Audit, office::*},
+};
+use time::OffsetDateTime;
+
+pub async fn create(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Json(office): Json<models_data_entry::Office>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+    let office: Office = office.into();
+
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![office.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &office)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![office],
+    })
+}
+
+pub async fn get(
+    State(state): State<RepositoryType>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+
+    Ok(state.get::<Office>(None).await?.into())
+}
+
+pub async fn update(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+    Json(updater): Json<UpdateOffice>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+
+    let before = state
+        .get::<Office>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let after = before.updated(&updater);
+
+    let mut tx = state.transaction().await?;
+    tx.update::<Office, _>(updater, Some(HashMap::from([("id", id.into())])));
+    tx.insert(vec![Audit::update(Some(actor), &before, &after)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+
+    let office = state
+        .get::<Office>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+
+    let mut tx = state.transaction().await?;
+    tx.soft_delete::<Office>(
+        actor,
+        OffsetDateTime::now_utc(),
+        HashMap::from([("id", id.into())]),
+    );
+    tx.insert(vec![Audit::delete(Some(actor), &office)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Delete(1))
+}
@@ -20,6 +20,7 @@ pub mod network {
         pub id: Option<Uuid>,
         pub description: Option<String>,
         pub network: Option<IpNet>,
+        #[allow(dead_code)]
         pub order: Option<Ordering>,
     }
 
@@ -45,3 +46,93 @@ pub mod network {
         }
     }
 }
+
+pub mod history {
+    use super::*;
+    use time::OffsetDateTime;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryHistory {
+        #[serde(default, with = "time::serde::rfc3339::option")]
+        pub at: Option<OffsetDateTime>,
+    }
+}
+
+pub mod trash {
+    use super::*;
+    use crate::models::audit::Entity;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryTrash {
+        pub entity: Option<Entity>,
+    }
+}
+
+pub mod ip_history {
+    use super::*;
+    use std::net::IpAddr;
+    use time::OffsetDateTime;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryIpHistory {
+        pub ip: IpAddr,
+        pub network_id: Option<Uuid>,
+        #[serde(default, with = "time::serde::rfc3339::option")]
+        pub at: Option<OffsetDateTime>,
+    }
+}
+
+pub mod dhcp {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryDhcp {
+        pub network_id: Option<Uuid>,
+    }
+}
+
+pub mod scan {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryScan {
+        pub network_id: Option<Uuid>,
+        pub limit: Option<i64>,
+    }
+}
+
+pub mod import {
+    use super::*;
+    use libipam::ipam_services::csv::{self, Row};
+
+    // map renames spreadsheet columns, e.g. "IP Address=ip,Host=hostname"
+    #[derive(Debug, Deserialize)]
+    pub struct QueryImport {
+        pub dry_run: Option<bool>,
+        pub map: Option<String>,
+    }
+
+    impl QueryImport {
+        pub fn rows(&self, body: &str) -> Vec<Row> {
+            csv::parse(body, &csv::mapping(self.map.as_deref().unwrap_or_default()))
+        }
+    }
+}
+
+pub mod webhook {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryDeliveries {
+        pub limit: Option<i64>,
+    }
+}
+
+pub mod export {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    pub struct QueryKea {
+        pub family: Option<u8>,
+    }
+}
@@ -0,0 +1,15 @@
+use super::*;
+use crate::services;
+use serde_json::json;
+
+pub async fn conflicts(
+    State(state): State<RepositoryType>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let report = services::conflicts::report(&state).await?;
+
+    Ok(Json(json!({
+        "length": report.len(),
+        "conflicts": report
+    })))
+}
@@ -0,0 +1,245 @@
+use super::*;
+use crate::{
+    database::repository::QueryResult,
+    models::{audit::Audit, network::Network, scan::*},
+    services::{
+        scanner::{self, Jobs, Settings},
+        status::Transitions,
+    },
+};
+use axum::{
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::{stream, Stream};
+use params::scan::QueryScan;
+use sqlx::Postgres;
+use tokio::sync::watch;
+
+// Bigger networks (i.e. a v6 /64) can't be swept host by host
+const MAX_HOSTS: usize = 65536;
+
+fn check_schedule(uri: &Uri, schedule: &Schedule) -> Result<(), ResponseError> {
+    if schedule.interval <= 0 {
+        return Err(ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid interval".to_string())
+            .detail("The interval must be a positive number of seconds".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    Ok(())
+}
+
+async fn schedule(
+    state: &RepositoryInjection<Postgres>,
+    network_id: Uuid,
+) -> Result<Option<Schedule>, RepositoryError> {
+    match state
+        .get::<Schedule>(Some(HashMap::from([("network_id", network_id.into())])))
+        .await
+    {
+        Err(RepositoryError::RowNotFound) => Ok(None),
+        e => Ok(e?.pop()),
+    }
+}
+
+pub async fn get_schedules(
+    State(state): State<RepositoryType>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let schedules = match state.get::<Schedule>(None).await {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    Ok(Json(json!({
+        "length": schedules.len(),
+        "schedules": schedules
+    })))
+}
+
+pub async fn create_schedule(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Json(schedule): Json<models_data_entry::Schedule>,
+) -> Result<QueryResult<Schedule>, ResponseError> {
+    let state = state.lock().await;
+    let schedule: Schedule = schedule.into();
+    check_schedule(&uri, &schedule)?;
+    state
+        .get::<Network>(Some(HashMap::from([("id", schedule.network_id.into())])))
+        .await?;
+
+    if self::schedule(&state, schedule.network_id).await?.is_some() {
+        return Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The network is already scheduled".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![schedule.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &schedule)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![schedule],
+    })
+}
+
+pub async fn update_schedule(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(network_id): Path<Uuid>,
+    Json(updater): Json<UpdateSchedule>,
+) -> Result<QueryResult<Schedule>, ResponseError> {
+    let state = state.lock().await;
+
+    let before = state
+        .get::<Schedule>(Some(HashMap::from([("network_id", network_id.into())])))
+        .await?
+        .remove(0);
+    let after = before.updated(updater);
+    check_schedule(&uri, &after)?;
+
+    let mut tx = state.transaction().await?;
+    tx.update::<Schedule, _>(
+        after.clone(),
+        Some(HashMap::from([("network_id", network_id.into())])),
+    );
+    tx.insert(vec![Audit::update(Some(actor), &before, &after)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn delete_schedule(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(network_id): Path<Uuid>,
+) -> Result<QueryResult<Schedule>, ResponseError> {
+    let state = state.lock().await;
+
+    let schedule = state
+        .get::<Schedule>(Some(HashMap::from([("network_id", network_id.into())])))
+        .await?
+        .remove(0);
+
+    let mut tx = state.transaction().await?;
+    tx.delete::<Schedule>(Some(HashMap::from([("network_id", network_id.into())])));
+    tx.insert(vec![Audit::delete(Some(actor), &schedule)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Delete(1))
+}
+
+pub async fn get_results(
+    State(state): State<RepositoryType>,
+    Query(param): Query<QueryScan>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let results = state
+        .get_scan_results(param.network_id, param.limit.unwrap_or(50).clamp(1, 1000))
+        .await?;
+
+    Ok(Json(json!({
+        "length": results.len(),
+        "results": results
+    })))
+}
+
+pub async fn start(
+    State(state): State<RepositoryType>,
+    Extension(jobs): Extension<Jobs>,
+    Extension(settings): Extension<Settings>,
+    Extension(transitions): Extension<Arc<Transitions>>,
+    _: IsAdministrator,
+    uri: Uri,
+    Path(network_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let network = state
+        .lock()
+        .await
+        .get::<Network>(Some(HashMap::from([("id", network_id.into())])))
+        .await?
+        .remove(0);
+
+    if network.network.hosts().nth(MAX_HOSTS).is_some() {
+        return Err(ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("The network is too large to scan".to_string())
+            .detail(format!(
+                "{} has more than {} hosts",
+                network.network, MAX_HOSTS
+            ))
+            .instance(uri.to_string())
+            .build());
+    }
+    if let Some(id) = scanner::running(&jobs, network_id) {
+        return Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The network is already being scanned".to_string())
+            .detail(format!("The scan {} is running", id))
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let job = scanner::start(state.clone(), &jobs, &network, settings, transitions);
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "job": job }))))
+}
+
+fn job(jobs: &Jobs, uri: &Uri, id: Uuid) -> Result<watch::Receiver<ScanJob>, ResponseError> {
+    jobs.lock().unwrap().get(&id).cloned().ok_or_else(|| {
+        ResponseError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Scan not found".to_string())
+            .detail(format!("The scan {} doesn't exist or expired", id))
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+pub async fn get_job(
+    Extension(jobs): Extension<Jobs>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let job = job(&jobs, &uri, id)?.borrow().clone();
+
+    Ok(Json(json!({ "job": job })))
+}
+
+// Emits the job on every change until it finishes
+pub async fn job_events(
+    Extension(jobs): Extension<Jobs>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ResponseError> {
+    let rx = job(&jobs, &uri, id)?;
+    let events = stream::unfold((rx, true), |(mut rx, first)| async move {
+        if !first && rx.changed().await.is_err() {
+            return None;
+        }
+        let job = rx.borrow_and_update().clone();
+        let event = Event::default()
+            .event(match job.state {
+                JobState::Running => "progress",
+                _ => "finished",
+            })
+            .json_data(&job);
+
+        Some((event, (rx, false)))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,43 @@
+use super::*;
+use crate::services::oui::Oui;
+use axum::Extension;
+use libipam::type_net::mac::MacAddr;
+
+pub async fn oui(
+    Extension(oui): Extension<Arc<Oui>>,
+    uri: Uri,
+    Path(mac): Path<MacAddr>,
+) -> Result<impl IntoResponse, ResponseError> {
+    match oui.lookup(&mac).await {
+        Some(vendor) => Ok(Json(json!({
+            "mac": mac,
+            "oui": mac.oui(),
+            "vendor": vendor
+        }))),
+        None => Err(ResponseError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Vendor not found".to_string())
+            .detail(format!("The OUI {} isn't registered", mac.oui()))
+            .instance(uri.to_string())
+            .build()),
+    }
+}
+
+pub async fn reload_oui(
+    _: IsAdministrator,
+    Extension(oui): Extension<Arc<Oui>>,
+    uri: Uri,
+) -> Result<impl IntoResponse, ResponseError> {
+    match oui.reload().await {
+        Ok(e) => Ok(Json(json!({
+            "status": 200,
+            "vendors": e
+        }))),
+        Err(e) => Err(ResponseError::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .title("The OUI database can't be loaded".to_string())
+            .detail(e.to_string())
+            .instance(uri.to_string())
+            .build()),
+    }
+}
@@ -0,0 +1,220 @@
+use super::*;
+use crate::{
+    database::repository::QueryResult,
+    models::{
+        audit::*,
+        device::{Address, Device, DeviceView},
+        network::Network,
+        office::Office,
+    },
+    services::ip_history,
+};
+use models_data_entry::ParamsDevice;
+use params::trash::QueryTrash;
+
+pub async fn get(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Query(param): Query<QueryTrash>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let mut resp = serde_json::Map::new();
+
+    if param.entity.is_none_or(|x| x == Entity::Network) {
+        resp.insert(
+            "networks".to_string(),
+            json!(state.get_trash::<Network>(None).await?),
+        );
+    }
+
+    if param.entity.is_none_or(|x| x == Entity::Device) {
+        resp.insert(
+            "devices".to_string(),
+            json!(state.get_trash::<Device>(None).await?),
+        );
+    }
+
+    if param.entity.is_none_or(|x| x == Entity::Address) {
+        resp.insert(
+            "addresses".to_string(),
+            json!(state.get_trash::<DeviceView>(None).await?),
+        );
+    }
+
+    if param.entity.is_none_or(|x| x == Entity::Office) {
+        resp.insert(
+            "offices".to_string(),
+            json!(state.get_trash::<Office>(None).await?),
+        );
+    }
+
+    Ok(Json(resp))
+}
+
+pub async fn restore_network(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Network>, ResponseError> {
+    let state = state.lock().await;
+
+    let network = state
+        .get_trash::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .pop()
+        .ok_or(RepositoryError::RowNotFound)?;
+    let devices = state
+        .get_trash::<DeviceView>(Some(HashMap::from([
+            ("network_id", id.into()),
+            ("deleted_at", network.deleted_at.into()),
+        ])))
+        .await?;
+
+    let mut tx = state.transaction().await?;
+    tx.restore::<Network>(HashMap::from([("id", id.into())]));
+    tx.restore::<Address>(HashMap::from([
+        ("network_id", id.into()),
+        ("deleted_at", network.deleted_at.into()),
+    ]));
+
+    let mut audit = vec![Audit::restore(Some(actor), &network.data)];
+    audit.extend(devices.iter().map(|x| Audit::restore(Some(actor), &x.data)));
+    tx.insert(audit);
+
+    for i in &devices {
+        ip_history::record(&mut tx, None, Some(&i.data));
+    }
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn restore_device(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Device>, ResponseError> {
+    let state = state.lock().await;
+
+    let device = state
+        .get_trash::<Device>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .pop()
+        .ok_or(RepositoryError::RowNotFound)?;
+    super::device::check_name(&state, &uri, &device.data).await?;
+    let addresses = state
+        .get_trash::<DeviceView>(Some(HashMap::from([
+            ("device_id", id.into()),
+            ("deleted_at", device.deleted_at.into()),
+        ])))
+        .await?;
+
+    let mut tx = state.transaction().await?;
+    tx.restore::<Device>(HashMap::from([("id", id.into())]));
+
+    let mut audit = vec![Audit::restore(Some(actor), &device.data)];
+    for i in addresses {
+        // Addresses of a deleted network come back with the network
+        if state
+            .get::<Network>(Some(HashMap::from([("id", i.data.network_id.into())])))
+            .await
+            .is_err()
+        {
+            continue;
+        }
+
+        tx.restore::<Address>(HashMap::from([
+            ("ip", i.data.ip.into()),
+            ("network_id", i.data.network_id.into()),
+        ]));
+        ip_history::record(&mut tx, None, Some(&i.data));
+        audit.push(Audit::restore(Some(actor), &i.data));
+    }
+    tx.insert(audit);
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn restore_address(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Query(params): Query<ParamsDevice>,
+) -> Result<QueryResult<Address>, ResponseError> {
+    let state = state.lock().await;
+
+    let address = state
+        .get_trash::<DeviceView>(Some(HashMap::from([
+            ("ip", params.ip.into()),
+            ("network_id", params.network_id.into()),
+        ])))
+        .await?
+        .pop()
+        .ok_or(RepositoryError::RowNotFound)?;
+
+    if state
+        .get::<Network>(Some(HashMap::from([("id", params.network_id.into())])))
+        .await
+        .is_err()
+    {
+        return Err(ResponseError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("The network of the device is deleted".to_string())
+            .detail("Restore the network first".to_string())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    if let Some(device_id) = address.data.device_id {
+        if state
+            .get::<Device>(Some(HashMap::from([("id", device_id.into())])))
+            .await
+            .is_err()
+        {
+            return Err(ResponseError::builder()
+                .status(StatusCode::CONFLICT)
+                .title("The device of the address is deleted".to_string())
+                .detail("Restore the device first".to_string())
+                .instance(uri.to_string())
+                .build());
+        }
+    }
+
+    let mut tx = state.transaction().await?;
+    tx.restore::<Address>(HashMap::from([
+        ("ip", params.ip.into()),
+        ("network_id", params.network_id.into()),
+    ]));
+    tx.insert(vec![Audit::restore(Some(actor), &address.data)]);
+    ip_history::record(&mut tx, None, Some(&address.data));
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn restore_office(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+
+    let office = state
+        .get_trash::<Office>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .pop()
+        .ok_or(RepositoryError::RowNotFound)?;
+
+    let mut tx = state.transaction().await?;
+    tx.restore::<Office>(HashMap::from([("id", id.into())]));
+    tx.insert(vec![Audit::restore(Some(actor), &office.data)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
@@ -0,0 +1,152 @@
+use super::*;
+use crate::{
+    database::repository::QueryResult,
+    models::{audit::Audit, webhook::*},
+    services::webhooks::Dispatcher,
+};
+use axum::Extension;
+use params::webhook::QueryDeliveries;
+use serde_json::json;
+
+fn check_url(uri: &Uri, webhook: &Webhook) -> Result<(), ResponseError> {
+    match reqwest::Url::parse(&webhook.url) {
+        Ok(e) if matches!(e.scheme(), "http" | "https") => Ok(()),
+        _ => Err(ResponseError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid url".to_string())
+            .detail(format!("{} isn't an http or https url", webhook.url))
+            .instance(uri.to_string())
+            .build()),
+    }
+}
+
+pub async fn get(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    let webhooks = match state.get::<Webhook>(None).await {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    Ok(Json(json!({
+        "length": webhooks.len(),
+        "webhooks": webhooks
+    })))
+}
+
+pub async fn create(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Json(webhook): Json<models_data_entry::Webhook>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    let state = state.lock().await;
+    let webhook: Webhook = webhook.into();
+    check_url(&uri, &webhook)?;
+
+    let mut tx = state.transaction().await?;
+    tx.insert(vec![webhook.clone()]);
+    tx.insert(vec![Audit::insert(Some(actor), &webhook)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![webhook],
+    })
+}
+
+pub async fn update(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(updater): Json<UpdateWebhook>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    let state = state.lock().await;
+
+    let before = state
+        .get::<Webhook>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let after = before.updated(updater);
+    check_url(&uri, &after)?;
+
+    let mut tx = state.transaction().await?;
+    tx.update::<Webhook, _>(after.clone(), Some(HashMap::from([("id", id.into())])));
+    tx.insert(vec![Audit::update(Some(actor), &before, &after)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Update(1))
+}
+
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Actor(actor): Actor,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    let state = state.lock().await;
+
+    let webhook = state
+        .get::<Webhook>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+
+    let mut tx = state.transaction().await?;
+    tx.delete::<Webhook>(Some(HashMap::from([("id", id.into())])));
+    tx.insert(vec![Audit::delete(Some(actor), &webhook)]);
+    tx.execute().await?;
+
+    Ok(QueryResult::Delete(1))
+}
+
+pub async fn deliveries(
+    State(state): State<RepositoryType>,
+    _: IsAdministrator,
+    Path(id): Path<Uuid>,
+    Query(param): Query<QueryDeliveries>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let state = state.lock().await;
+    state
+        .get::<Webhook>(Some(HashMap::from([("id", id.into())])))
+        .await?;
+    let deliveries = state
+        .get_deliveries(id, param.limit.unwrap_or(50).clamp(1, 1000))
+        .await?;
+
+    Ok(Json(json!({
+        "length": deliveries.len(),
+        "deliveries": deliveries
+    })))
+}
+
+// Sends the event again as a new delivery, even if the webhook is disabled
+pub async fn redeliver(
+    State(state): State<RepositoryType>,
+    Extension(dispatcher): Extension<Dispatcher>,
+    _: IsAdministrator,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let (webhook, delivery) = {
+        let state = state.lock().await;
+        let previous = state
+            .get::<Delivery>(Some(HashMap::from([("id", id.into())])))
+            .await?
+            .remove(0);
+        let webhook = state
+            .get::<Webhook>(Some(HashMap::from([("id", previous.webhook_id.into())])))
+            .await?
+            .remove(0);
+        (webhook, Delivery::new(previous.webhook_id, previous.event))
+    };
+
+    dispatcher
+        .dispatch(&state, webhook, delivery.clone())
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "delivery": delivery }))))
+}
@@ -435,7 +435,7 @@ pub mod type_net {
     pub mod port {
         use serde::{Deserialize, Serialize};
 
-        #[derive(Debug, Clone, Deserialize, Serialize, sqlx::Type)]
+        #[derive(Debug, Clone, Copy, Deserialize, Serialize, sqlx::Type)]
         pub struct Port(u16);
 
         impl std::ops::Deref for Port {
@@ -475,6 +475,20 @@ pub mod type_net {
             }
         }
 
+        impl std::str::FromStr for Port {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.trim().parse().map(Port)
+            }
+        }
+
+        impl std::fmt::Display for Port {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
         #[cfg(test)]
         mod test {
             use super::Port;
@@ -663,6 +677,7 @@ pub mod type_net {
             }
 
             #[test]
+            #[allow(clippy::bool_assert_comparison)]
             fn test_prefix_partial_partial_ord_with_prefix() {
                 let ipnet: IpNet = "172.30.0.30/24".parse().unwrap();
                 let pref = Prefix::from(&ipnet);
@@ -677,6 +692,7 @@ pub mod type_net {
             }
 
             #[test]
+            #[allow(clippy::bool_assert_comparison)]
             fn test_prefix_partial_partial_ord_with_integer() {
                 let ipnet: IpNet = "172.30.0.30/24".parse().unwrap();
                 let pref = Prefix::from(&ipnet);
@@ -759,6 +775,372 @@ pub mod type_net {
         }
         impl std::error::Error for OutOfRange {}
     }
+
+    pub mod mac {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use std::str::FromStr;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct MacAddr([u8; 6]);
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct Oui([u8; 3]);
+
+        impl MacAddr {
+            pub fn new(octets: [u8; 6]) -> Self {
+                Self(octets)
+            }
+
+            pub fn octets(&self) -> [u8; 6] {
+                self.0
+            }
+
+            pub fn oui(&self) -> Oui {
+                Oui([self.0[0], self.0[1], self.0[2]])
+            }
+        }
+
+        impl Oui {
+            pub fn new(octets: [u8; 3]) -> Self {
+                Self(octets)
+            }
+
+            pub fn octets(&self) -> [u8; 3] {
+                self.0
+            }
+        }
+
+        // aa:bb:cc:dd:ee:ff, aa-bb-cc-dd-ee-ff, aabb.ccdd.eeff and aabbccddeeff,
+        // a separated group may drop its leading zero (0:1b:...)
+        fn parse<const N: usize>(value: &str) -> Result<[u8; N], InvalidMac> {
+            let value = value.trim();
+            let digits = if value.contains([':', '-']) {
+                let groups: Vec<&str> = value.split([':', '-']).collect();
+                if groups.len() != N || groups.iter().any(|x| x.is_empty() || x.len() > 2) {
+                    return Err(InvalidMac);
+                }
+                groups.iter().map(|x| format!("{:0>2}", x)).collect()
+            } else if value.contains('.') {
+                let groups: Vec<&str> = value.split('.').collect();
+                if groups.iter().any(|x| x.len() != 4) {
+                    return Err(InvalidMac);
+                }
+                groups.concat()
+            } else {
+                value.to_string()
+            };
+
+            if digits.len() != N * 2 || !digits.chars().all(|x| x.is_ascii_hexdigit()) {
+                return Err(InvalidMac);
+            }
+
+            let mut resp = [0; N];
+            for (i, octet) in resp.iter_mut().enumerate() {
+                *octet =
+                    u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).map_err(|_| InvalidMac)?;
+            }
+            Ok(resp)
+        }
+
+        fn write(f: &mut std::fmt::Formatter<'_>, octets: &[u8]) -> std::fmt::Result {
+            let octets: Vec<String> = octets.iter().map(|x| format!("{:02x}", x)).collect();
+            write!(f, "{}", octets.join(":"))
+        }
+
+        impl FromStr for MacAddr {
+            type Err = InvalidMac;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse(s).map(Self)
+            }
+        }
+
+        impl FromStr for Oui {
+            type Err = InvalidMac;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse(s).map(Self)
+            }
+        }
+
+        impl std::fmt::Display for MacAddr {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write(f, &self.0)
+            }
+        }
+
+        impl std::fmt::Display for Oui {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write(f, &self.0)
+            }
+        }
+
+        impl Serialize for MacAddr {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for MacAddr {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl Serialize for Oui {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Oui {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+
+        #[derive(Debug, PartialEq)]
+        pub struct InvalidMac;
+
+        impl std::fmt::Display for InvalidMac {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "Invalid mac address")
+            }
+        }
+        impl std::error::Error for InvalidMac {}
+
+        #[cfg(test)]
+        mod test {
+            use super::{InvalidMac, MacAddr, Oui};
+
+            #[test]
+            fn mac_parse_common_notations() {
+                let mac = MacAddr::new([0x00, 0x1b, 0x44, 0x11, 0x3a, 0xb7]);
+                assert_eq!(mac, "00:1b:44:11:3a:b7".parse().unwrap());
+                assert_eq!(mac, "00-1B-44-11-3A-B7".parse().unwrap());
+                assert_eq!(mac, "001b.4411.3ab7".parse().unwrap());
+                assert_eq!(mac, "001B44113AB7".parse().unwrap());
+                assert_eq!(mac, "0:1b:44:11:3a:b7".parse().unwrap());
+            }
+
+            #[test]
+            fn mac_parse_invalid() {
+                assert_eq!(Err(InvalidMac), "00:1b:44:11:3a".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "00:1b:44:11:3a:b7:01".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "00:1b:44:11:3a:zz".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "001b.4411.3ab".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "001b44113ab7ff".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "00::44:11:3a:b7".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "+0:1b:44:11:3a:b7".parse::<MacAddr>());
+                assert_eq!(Err(InvalidMac), "".parse::<MacAddr>());
+            }
+
+            #[test]
+            fn mac_canonical_format() {
+                let mac: MacAddr = "001B.4411.3AB7".parse().unwrap();
+                assert_eq!("00:1b:44:11:3a:b7", mac.to_string());
+            }
+
+            #[test]
+            fn mac_serde_canonical() {
+                let mac: MacAddr = serde_json::from_str("\"00-1B-44-11-3A-B7\"").unwrap();
+                assert_eq!(
+                    "\"00:1b:44:11:3a:b7\"",
+                    serde_json::to_string(&mac).unwrap()
+                );
+                assert!(serde_json::from_str::<MacAddr>("\"00-1B\"").is_err());
+            }
+
+            #[test]
+            fn mac_oui_prefix() {
+                let mac: MacAddr = "00:1b:44:11:3a:b7".parse().unwrap();
+                assert_eq!(mac.oui(), "00-1B-44".parse::<Oui>().unwrap());
+                assert_eq!(mac.oui(), "001b44".parse::<Oui>().unwrap());
+                assert_eq!("00:1b:44", mac.oui().to_string());
+            }
+        }
+    }
+
+    pub mod dns {
+        use serde::{Deserialize, Serialize};
+        use std::str::FromStr;
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct Hostname(String);
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct DomainName(String);
+
+        // RFC 1123 label: letters, digits and hyphens, at most 63 characters,
+        // it can't start or end with a hyphen
+        fn is_label(label: &str) -> bool {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|x| x.is_ascii_alphanumeric() || x == '-')
+        }
+
+        impl DomainName {
+            pub const MAX: usize = 253;
+
+            pub fn fqdn(&self, hostname: &Hostname) -> String {
+                format!("{}.{}", hostname, self)
+            }
+        }
+
+        impl FromStr for Hostname {
+            type Err = InvalidName;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                if is_label(s) {
+                    Ok(Self(s.to_ascii_lowercase()))
+                } else {
+                    Err(InvalidName(s.to_string()))
+                }
+            }
+        }
+
+        impl FromStr for DomainName {
+            type Err = InvalidName;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let name = s.strip_suffix('.').unwrap_or(s);
+                if name.len() <= DomainName::MAX && name.split('.').all(is_label) {
+                    Ok(Self(name.to_ascii_lowercase()))
+                } else {
+                    Err(InvalidName(s.to_string()))
+                }
+            }
+        }
+
+        impl TryFrom<String> for Hostname {
+            type Error = InvalidName;
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                value.parse()
+            }
+        }
+
+        impl TryFrom<String> for DomainName {
+            type Error = InvalidName;
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                value.parse()
+            }
+        }
+
+        impl From<Hostname> for String {
+            fn from(value: Hostname) -> Self {
+                value.0
+            }
+        }
+
+        impl From<DomainName> for String {
+            fn from(value: DomainName) -> Self {
+                value.0
+            }
+        }
+
+        impl std::ops::Deref for Hostname {
+            type Target = str;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl std::ops::Deref for DomainName {
+            type Target = str;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Display for Hostname {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl std::fmt::Display for DomainName {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        #[derive(Debug, PartialEq)]
+        pub struct InvalidName(pub String);
+
+        impl std::fmt::Display for InvalidName {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{} isn't a valid dns name", self.0)
+            }
+        }
+        impl std::error::Error for InvalidName {}
+
+        #[cfg(test)]
+        mod test {
+            use super::{DomainName, Hostname};
+
+            #[test]
+            fn hostname_valid() {
+                assert!("printer-01".parse::<Hostname>().is_ok());
+                assert!("1st-floor".parse::<Hostname>().is_ok());
+                assert!("a".repeat(63).parse::<Hostname>().is_ok());
+            }
+
+            #[test]
+            fn hostname_invalid() {
+                assert!("".parse::<Hostname>().is_err());
+                assert!("-printer".parse::<Hostname>().is_err());
+                assert!("printer-".parse::<Hostname>().is_err());
+                assert!("printer_01".parse::<Hostname>().is_err());
+                assert!("printer.example.com".parse::<Hostname>().is_err());
+                assert!("a".repeat(64).parse::<Hostname>().is_err());
+            }
+
+            #[test]
+            fn hostname_is_lowercase() {
+                let hostname: Hostname = "Printer-01".parse().unwrap();
+                assert_eq!("printer-01", hostname.to_string());
+            }
+
+            #[test]
+            fn domain_name_valid() {
+                let domain: DomainName = "Office.Example.COM.".parse().unwrap();
+                assert_eq!("office.example.com", domain.to_string());
+                assert!("localdomain".parse::<DomainName>().is_ok());
+            }
+
+            #[test]
+            fn domain_name_invalid() {
+                assert!("".parse::<DomainName>().is_err());
+                assert!(".".parse::<DomainName>().is_err());
+                assert!("example..com".parse::<DomainName>().is_err());
+                assert!("-example.com".parse::<DomainName>().is_err());
+                assert!("exa mple.com".parse::<DomainName>().is_err());
+                assert!(vec!["a".repeat(63); 4]
+                    .join(".")
+                    .parse::<DomainName>()
+                    .is_err());
+            }
+
+            #[test]
+            fn domain_name_fqdn() {
+                let domain: DomainName = "example.com".parse().unwrap();
+                let hostname: Hostname = "printer".parse().unwrap();
+                assert_eq!("printer.example.com", domain.fqdn(&hostname));
+            }
+
+            #[test]
+            fn dns_names_serde() {
+                let hostname: Hostname = serde_json::from_str("\"Printer\"").unwrap();
+                assert_eq!("\"printer\"", serde_json::to_string(&hostname).unwrap());
+                assert!(serde_json::from_str::<Hostname>("\"bad_name\"").is_err());
+                assert!(serde_json::from_str::<DomainName>("\"example..com\"").is_err());
+            }
+        }
+    }
 }
 
 pub mod ipam_services {
@@ -858,6 +1240,3626 @@ pub mod ipam_services {
         }
     }
 
+    pub mod icmp {
+        use futures::{stream, StreamExt};
+        use ipnet::IpNet;
+        use socket2::{Domain, Protocol, Socket, Type};
+        use std::{
+            collections::HashMap,
+            io,
+            net::{IpAddr, SocketAddr},
+            sync::{
+                atomic::{AtomicU16, Ordering},
+                Arc, Mutex,
+            },
+            time::{Duration, Instant},
+        };
+        use tokio::{net::UdpSocket, sync::oneshot, task::JoinHandle};
+
+        const ECHO_REQUEST_V4: u8 = 8;
+        const ECHO_REPLY_V4: u8 = 0;
+        const ECHO_REQUEST_V6: u8 = 128;
+        const ECHO_REPLY_V6: u8 = 129;
+        const MIN_BACKOFF: Duration = Duration::from_millis(10);
+        const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+        #[derive(Debug, PartialEq)]
+        pub enum PingError {
+            Unsupported,
+            Io(String),
+            Timeout,
+        }
+
+        impl std::fmt::Display for PingError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Unsupported => write!(f, "ICMP sockets aren't available"),
+                    Self::Io(e) => write!(f, "{}", e),
+                    Self::Timeout => write!(f, "The host didn't answer"),
+                }
+            }
+        }
+
+        impl std::error::Error for PingError {}
+
+        #[derive(Debug, PartialEq)]
+        pub struct Echo<'a> {
+            pub ident: u16,
+            pub seq: u16,
+            pub payload: &'a [u8],
+        }
+
+        pub fn checksum(data: &[u8]) -> u16 {
+            let mut sum: u32 = data
+                .chunks(2)
+                .map(|x| u16::from_be_bytes([x[0], x.get(1).copied().unwrap_or(0)]) as u32)
+                .sum();
+            while sum >> 16 != 0 {
+                sum = (sum & 0xffff) + (sum >> 16);
+            }
+            !(sum as u16)
+        }
+
+        // The kernel fills in the ICMPv6 checksum since it needs the pseudo header
+        pub fn echo_request(v6: bool, ident: u16, seq: u16, payload: &[u8]) -> Vec<u8> {
+            let kind = if v6 { ECHO_REQUEST_V6 } else { ECHO_REQUEST_V4 };
+            let mut packet = vec![kind, 0, 0, 0];
+            packet.extend(ident.to_be_bytes());
+            packet.extend(seq.to_be_bytes());
+            packet.extend(payload);
+
+            if !v6 {
+                let sum = checksum(&packet);
+                packet[2..4].copy_from_slice(&sum.to_be_bytes());
+            }
+            packet
+        }
+
+        // Raw IPv4 sockets hand over the ip header too
+        pub fn echo_reply(v6: bool, packet: &[u8]) -> Option<Echo<'_>> {
+            let packet = match packet.first() {
+                Some(e) if !v6 && e >> 4 == 4 => packet.get((e & 0x0f) as usize * 4..)?,
+                _ => packet,
+            };
+            let kind = if v6 { ECHO_REPLY_V6 } else { ECHO_REPLY_V4 };
+
+            if packet.len() < 8 || packet[0] != kind || packet[1] != 0 {
+                return None;
+            }
+
+            Some(Echo {
+                ident: u16::from_be_bytes([packet[4], packet[5]]),
+                seq: u16::from_be_bytes([packet[6], packet[7]]),
+                payload: &packet[8..],
+            })
+        }
+
+        // Unprivileged echo sockets need net.ipv4.ping_group_range, raw ones CAP_NET_RAW
+        fn open(v6: bool) -> io::Result<UdpSocket> {
+            let (domain, protocol) = if v6 {
+                (Domain::IPV6, Protocol::ICMPV6)
+            } else {
+                (Domain::IPV4, Protocol::ICMPV4)
+            };
+            let socket = Socket::new(domain, Type::DGRAM, Some(protocol))
+                .or_else(|_| Socket::new(domain, Type::RAW, Some(protocol)))?;
+            socket.set_nonblocking(true)?;
+
+            UdpSocket::from_std(socket.into())
+        }
+
+        type Waiting = Arc<Mutex<HashMap<(IpAddr, u16), oneshot::Sender<Instant>>>>;
+
+        // Datagram sockets rewrite the identifier, so replies are matched by
+        // source, sequence and a token in the payload
+        async fn receive(socket: Arc<UdpSocket>, v6: bool, token: [u8; 8], waiting: Waiting) {
+            let mut buf = [0u8; 1500];
+            let mut backoff = Duration::ZERO;
+
+            loop {
+                // A socket that keeps failing would spin the task, wait longer each time
+                let (len, from) = match socket.recv_from(&mut buf).await {
+                    Ok(e) => {
+                        backoff = Duration::ZERO;
+                        e
+                    }
+                    Err(e) => {
+                        backoff = (backoff * 2).clamp(MIN_BACKOFF, MAX_BACKOFF);
+                        tracing::warn!("ICMP receive failed, retrying in {:?}: {}", backoff, e);
+                        tokio::time::sleep(backoff).await;
+                        continue;
+                    }
+                };
+                let now = Instant::now();
+
+                if let Some(echo) =
+                    echo_reply(v6, &buf[..len]).filter(|x| x.payload.starts_with(&token))
+                {
+                    if let Some(tx) = waiting.lock().unwrap().remove(&(from.ip(), echo.seq)) {
+                        let _ = tx.send(now);
+                    }
+                }
+            }
+        }
+
+        pub struct Pinger {
+            v4: Option<Arc<UdpSocket>>,
+            v6: Option<Arc<UdpSocket>>,
+            ident: u16,
+            token: [u8; 8],
+            seq: AtomicU16,
+            waiting: Waiting,
+            tasks: Vec<JoinHandle<()>>,
+        }
+
+        impl Pinger {
+            // Must be called inside a tokio runtime, the receivers run on it
+            pub fn new() -> Result<Self, PingError> {
+                let v4 = open(false).ok().map(Arc::new);
+                let v6 = open(true).ok().map(Arc::new);
+                if v4.is_none() && v6.is_none() {
+                    return Err(PingError::Unsupported);
+                }
+
+                let random = uuid::Uuid::new_v4();
+                let random = random.as_bytes();
+                let token: [u8; 8] = random[..8].try_into().unwrap();
+                let waiting = Waiting::default();
+                let tasks = [(&v4, false), (&v6, true)]
+                    .into_iter()
+                    .filter_map(|(socket, v6)| {
+                        socket
+                            .as_ref()
+                            .map(|x| tokio::spawn(receive(x.clone(), v6, token, waiting.clone())))
+                    })
+                    .collect();
+
+                Ok(Self {
+                    v4,
+                    v6,
+                    ident: u16::from_be_bytes([random[8], random[9]]),
+                    token,
+                    seq: AtomicU16::new(0),
+                    waiting,
+                    tasks,
+                })
+            }
+
+            pub async fn ping(&self, ip: IpAddr, timeout: Duration) -> Result<Duration, PingError> {
+                let socket = match ip {
+                    IpAddr::V4(_) => &self.v4,
+                    IpAddr::V6(_) => &self.v6,
+                }
+                .as_ref()
+                .ok_or(PingError::Unsupported)?;
+                let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+                let (tx, rx) = oneshot::channel();
+                self.waiting.lock().unwrap().insert((ip, seq), tx);
+
+                let packet = echo_request(ip.is_ipv6(), self.ident, seq, &self.token);
+                let start = Instant::now();
+                let resp = match socket.send_to(&packet, SocketAddr::new(ip, 0)).await {
+                    Ok(_) => match tokio::time::timeout(timeout, rx).await {
+                        Ok(Ok(e)) => Ok(e.duration_since(start)),
+                        _ => Err(PingError::Timeout),
+                    },
+                    Err(e) => Err(PingError::Io(e.to_string())),
+                };
+                self.waiting.lock().unwrap().remove(&(ip, seq));
+
+                resp
+            }
+
+            // Round trip of each address, None for the ones that didn't answer
+            pub async fn batch(
+                &self,
+                ips: impl IntoIterator<Item = IpAddr>,
+                concurrency: usize,
+                timeout: Duration,
+            ) -> Vec<(IpAddr, Option<Duration>)> {
+                let mut resp: Vec<(IpAddr, Option<Duration>)> = stream::iter(ips)
+                    .map(|ip| async move { (ip, self.ping(ip, timeout).await.ok()) })
+                    .buffer_unordered(concurrency.max(1))
+                    .collect()
+                    .await;
+                resp.sort();
+
+                resp
+            }
+
+            pub async fn sweep(
+                &self,
+                network: &IpNet,
+                concurrency: usize,
+                timeout: Duration,
+            ) -> Vec<(IpAddr, Option<Duration>)> {
+                self.batch(network.hosts(), concurrency, timeout).await
+            }
+        }
+
+        impl Drop for Pinger {
+            fn drop(&mut self) {
+                self.tasks.iter().for_each(JoinHandle::abort);
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+            use tokio::runtime::Runtime;
+
+            #[test]
+            fn icmp_echo_packet() {
+                let packet = echo_request(false, 0x1234, 7, b"token");
+                assert_eq!(
+                    &[8, 0, 0, 0, 0x12, 0x34, 0, 7][..],
+                    &[&packet[..2], &[0, 0], &packet[4..8]].concat()[..]
+                );
+                assert_eq!(0, checksum(&packet));
+
+                let mut reply = packet.clone();
+                reply[0] = ECHO_REPLY_V4;
+                assert_eq!(
+                    Some(Echo {
+                        ident: 0x1234,
+                        seq: 7,
+                        payload: b"token"
+                    }),
+                    echo_reply(false, &reply)
+                );
+
+                let mut header = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, 1, 0, 0];
+                header.extend([127, 0, 0, 1, 127, 0, 0, 1]);
+                header.extend(&reply);
+                assert_eq!(Some(7), echo_reply(false, &header).map(|x| x.seq));
+
+                assert_eq!(None, echo_reply(false, &packet));
+                assert_eq!(None, echo_reply(true, &reply));
+                assert_eq!(128, echo_request(true, 1, 1, &[])[0]);
+            }
+
+            #[test]
+            fn icmp_loopback() {
+                let runtime = Runtime::new().unwrap();
+                let _guard = runtime.enter();
+                let Ok(pinger) = Pinger::new() else {
+                    return;
+                };
+
+                let resp = runtime.block_on(pinger.batch(
+                    ["127.0.0.1".parse().unwrap(), "127.0.0.2".parse().unwrap()],
+                    2,
+                    Duration::from_millis(500),
+                ));
+                assert_eq!(2, resp.len());
+                assert!(resp.iter().all(|x| x.1.is_some()));
+            }
+        }
+    }
+
+    pub mod probe {
+        use crate::type_net::port::Port;
+        use futures::{stream, StreamExt};
+        use std::{
+            net::{IpAddr, SocketAddr},
+            time::Duration,
+        };
+        use tokio::net::TcpStream;
+
+        pub const DEFAULT_PORTS: [u16; 16] = [
+            21, 22, 23, 25, 53, 80, 110, 143, 161, 389, 443, 445, 3306, 3389, 5432, 8080,
+        ];
+
+        pub fn service(port: &Port) -> Option<&'static str> {
+            Some(match **port {
+                21 => "ftp",
+                22 => "ssh",
+                23 => "telnet",
+                25 => "smtp",
+                53 => "dns",
+                80 => "http",
+                110 => "pop3",
+                143 => "imap",
+                161 => "snmp",
+                389 => "ldap",
+                443 => "https",
+                445 => "smb",
+                3306 => "mysql",
+                3389 => "rdp",
+                5432 => "postgresql",
+                8080 => "http-alt",
+                8443 => "https-alt",
+                _ => return None,
+            })
+        }
+
+        // Connect scan, a port is open when the handshake finishes in time
+        pub async fn open_ports(
+            ip: IpAddr,
+            ports: &[Port],
+            timeout: Duration,
+            concurrency: usize,
+        ) -> Vec<Port> {
+            let mut resp: Vec<Port> = stream::iter(ports.iter().copied())
+                .map(|port| async move {
+                    let connect = TcpStream::connect(SocketAddr::new(ip, *port));
+                    matches!(tokio::time::timeout(timeout, connect).await, Ok(Ok(_)))
+                        .then_some(port)
+                })
+                .buffer_unordered(concurrency.max(1))
+                .filter_map(|x| async move { x })
+                .collect()
+                .await;
+            resp.sort_by_key(|x| **x);
+            resp.dedup();
+
+            resp
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+            use tokio::runtime::Runtime;
+
+            #[test]
+            fn probe_open_ports() {
+                let runtime = Runtime::new().unwrap();
+                let (open, closed) = runtime.block_on(async {
+                    let open = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                    let port = closed.local_addr().unwrap().port();
+                    drop(closed);
+                    (open, port)
+                });
+                let port = Port::new(open.local_addr().unwrap().port());
+
+                let resp = runtime.block_on(open_ports(
+                    "127.0.0.1".parse().unwrap(),
+                    &[Port::new(closed), port, port],
+                    Duration::from_millis(500),
+                    4,
+                ));
+                assert_eq!(vec![port], resp);
+                assert_eq!(Some("ssh"), service(&"22".parse().unwrap()));
+                assert_eq!(None, service(&Port::new(1)));
+            }
+        }
+    }
+
+    pub mod snmp {
+        use super::neighbors::Neighbor;
+        use crate::type_net::mac::MacAddr;
+        use hmac::{Hmac, Mac};
+        use md5::Md5;
+        use sha1::Sha1;
+        use sha2::{Digest, Sha256};
+        use std::{
+            collections::BTreeMap,
+            net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+            ops::{Bound, Range},
+            str::FromStr,
+            time::{Duration, Instant},
+        };
+        use tokio::{net::UdpSocket, task::JoinHandle};
+
+        const INTEGER: u8 = 0x02;
+        const OCTET_STRING: u8 = 0x04;
+        const NULL: u8 = 0x05;
+        const OBJECT_ID: u8 = 0x06;
+        const SEQUENCE: u8 = 0x30;
+        const IP_ADDRESS: u8 = 0x40;
+        const COUNTER32: u8 = 0x41;
+        const GAUGE32: u8 = 0x42;
+        const TIMETICKS: u8 = 0x43;
+        const COUNTER64: u8 = 0x46;
+        const NO_SUCH_OBJECT: u8 = 0x80;
+        const NO_SUCH_INSTANCE: u8 = 0x81;
+        const END_OF_MIB_VIEW: u8 = 0x82;
+
+        pub const GET: u8 = 0xa0;
+        pub const GET_NEXT: u8 = 0xa1;
+        pub const RESPONSE: u8 = 0xa2;
+        pub const GET_BULK: u8 = 0xa5;
+        pub const REPORT: u8 = 0xa8;
+
+        const FLAG_AUTH: u8 = 0x01;
+        const FLAG_PRIV: u8 = 0x02;
+        const FLAG_REPORTABLE: u8 = 0x04;
+        const SECURITY_USM: i64 = 3;
+        const MAX_SIZE: i64 = 65507;
+        const RETRIES: u32 = 1;
+        const BULK_REPETITIONS: i64 = 25;
+
+        pub const SYS_DESCR: &str = "1.3.6.1.2.1.1.1.0";
+        pub const SYS_NAME: &str = "1.3.6.1.2.1.1.5.0";
+        pub const IF_DESCR: &str = "1.3.6.1.2.1.2.2.1.2";
+        pub const IF_PHYS_ADDRESS: &str = "1.3.6.1.2.1.2.2.1.6";
+        pub const IF_NAME: &str = "1.3.6.1.2.1.31.1.1.1.1";
+        pub const IP_AD_ENT_IF_INDEX: &str = "1.3.6.1.2.1.4.20.1.2";
+        pub const IP_ADDRESS_IF_INDEX: &str = "1.3.6.1.2.1.4.34.1.3";
+        pub const IP_NET_TO_MEDIA_PHYS_ADDRESS: &str = "1.3.6.1.2.1.4.22.1.2";
+        pub const IP_NET_TO_PHYSICAL_PHYS_ADDRESS: &str = "1.3.6.1.2.1.4.35.1.4";
+        const NOT_IN_TIME_WINDOWS: &str = "1.3.6.1.6.3.15.1.1.2.0";
+        const UNKNOWN_ENGINE_IDS: &str = "1.3.6.1.6.3.15.1.1.4.0";
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum SnmpError {
+            Io(String),
+            Timeout,
+            Malformed,
+            Status(i64),
+            Report(Oid),
+            Unsupported(&'static str),
+        }
+
+        impl std::fmt::Display for SnmpError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Io(e) => write!(f, "{}", e),
+                    Self::Timeout => write!(f, "The agent didn't answer"),
+                    Self::Malformed => write!(f, "The answer of the agent is malformed"),
+                    Self::Status(e) => write!(f, "The agent answered with error status {}", e),
+                    Self::Report(e) => write!(f, "The agent reported {}", e),
+                    Self::Unsupported(e) => write!(f, "Unsupported {}", e),
+                }
+            }
+        }
+
+        impl std::error::Error for SnmpError {}
+
+        impl From<std::io::Error> for SnmpError {
+            fn from(value: std::io::Error) -> Self {
+                Self::Io(value.to_string())
+            }
+        }
+
+        fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+            let mut resp = vec![tag];
+            if value.len() < 0x80 {
+                resp.push(value.len() as u8);
+            } else {
+                let len = value.len().to_be_bytes();
+                let skip = len.iter().take_while(|x| **x == 0).count();
+                resp.push(0x80 | (len.len() - skip) as u8);
+                resp.extend(&len[skip..]);
+            }
+            resp.extend(value);
+            resp
+        }
+
+        fn integer(tag: u8, value: i64) -> Vec<u8> {
+            let bytes = value.to_be_bytes();
+            let mut start = 0;
+            while start < 7
+                && ((bytes[start] == 0 && bytes[start + 1] & 0x80 == 0)
+                    || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0))
+            {
+                start += 1;
+            }
+            tlv(tag, &bytes[start..])
+        }
+
+        fn unsigned(tag: u8, value: u64) -> Vec<u8> {
+            let bytes = value.to_be_bytes();
+            let skip = bytes.iter().take(7).take_while(|x| **x == 0).count();
+            let mut content = Vec::new();
+            if bytes[skip] & 0x80 != 0 {
+                content.push(0);
+            }
+            content.extend(&bytes[skip..]);
+            tlv(tag, &content)
+        }
+
+        // Splits off the first element: tag, content and what follows it
+        fn read(buf: &[u8]) -> Result<(u8, &[u8], &[u8]), SnmpError> {
+            let (&tag, buf) = buf.split_first().ok_or(SnmpError::Malformed)?;
+            let (&first, buf) = buf.split_first().ok_or(SnmpError::Malformed)?;
+            let (len, buf) = match first {
+                e if e & 0x80 == 0 => (e as usize, buf),
+                e if (1..=4).contains(&(e & 0x7f)) && buf.len() >= (e & 0x7f) as usize => {
+                    let (len, buf) = buf.split_at((e & 0x7f) as usize);
+                    (len.iter().fold(0, |acc, x| acc << 8 | *x as usize), buf)
+                }
+                _ => return Err(SnmpError::Malformed),
+            };
+
+            if buf.len() < len {
+                return Err(SnmpError::Malformed);
+            }
+            Ok((tag, &buf[..len], &buf[len..]))
+        }
+
+        fn expect(buf: &[u8], tag: u8) -> Result<(&[u8], &[u8]), SnmpError> {
+            match read(buf)? {
+                (e, content, rest) if e == tag => Ok((content, rest)),
+                _ => Err(SnmpError::Malformed),
+            }
+        }
+
+        fn read_integer(content: &[u8]) -> Result<i64, SnmpError> {
+            if content.is_empty() || content.len() > 8 {
+                return Err(SnmpError::Malformed);
+            }
+            let init = if content[0] & 0x80 != 0 { -1 } else { 0 };
+            Ok(content.iter().fold(init, |acc, x| acc << 8 | *x as i64))
+        }
+
+        fn read_unsigned(content: &[u8]) -> Result<u64, SnmpError> {
+            if content.is_empty() || content.len() > 9 {
+                return Err(SnmpError::Malformed);
+            }
+            Ok(content.iter().fold(0, |acc, x| acc << 8 | *x as u64))
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct Oid(pub Vec<u32>);
+
+        impl Oid {
+            pub fn starts_with(&self, other: &Oid) -> bool {
+                self.0.starts_with(&other.0)
+            }
+
+            pub fn suffix(&self, prefix: &Oid) -> Option<&[u32]> {
+                self.0.strip_prefix(prefix.0.as_slice())
+            }
+
+            pub fn child(&self, arcs: &[u32]) -> Oid {
+                Oid([&self.0[..], arcs].concat())
+            }
+
+            fn encode(&self) -> Vec<u8> {
+                let (first, rest) = match self.0.as_slice() {
+                    [a, b, rest @ ..] => (a * 40 + b, rest),
+                    [a] => (a * 40, &[][..]),
+                    [] => (0, &[][..]),
+                };
+                let mut content = Vec::new();
+
+                for mut arc in std::iter::once(first).chain(rest.iter().copied()) {
+                    let mut bytes = vec![(arc & 0x7f) as u8];
+                    arc >>= 7;
+                    while arc > 0 {
+                        bytes.push((arc & 0x7f) as u8 | 0x80);
+                        arc >>= 7;
+                    }
+                    content.extend(bytes.iter().rev());
+                }
+                tlv(OBJECT_ID, &content)
+            }
+
+            fn decode(content: &[u8]) -> Result<Self, SnmpError> {
+                let mut arcs = Vec::new();
+                let mut arc: u32 = 0;
+
+                for (i, x) in content.iter().enumerate() {
+                    arc = arc
+                        .checked_mul(128)
+                        .ok_or(SnmpError::Malformed)?
+                        .saturating_add((x & 0x7f) as u32);
+                    if x & 0x80 == 0 {
+                        arcs.push(arc);
+                        arc = 0;
+                    } else if i == content.len() - 1 {
+                        return Err(SnmpError::Malformed);
+                    }
+                }
+
+                let Some((&first, rest)) = arcs.split_first() else {
+                    return Err(SnmpError::Malformed);
+                };
+                let head = match first {
+                    0..=79 => [first / 40, first % 40],
+                    _ => [2, first - 80],
+                };
+                Ok(Oid([&head[..], rest].concat()))
+            }
+        }
+
+        impl FromStr for Oid {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.trim_start_matches('.')
+                    .split('.')
+                    .map(u32::from_str)
+                    .collect::<Result<_, _>>()
+                    .map(Oid)
+            }
+        }
+
+        impl std::fmt::Display for Oid {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let arcs: Vec<String> = self.0.iter().map(u32::to_string).collect();
+                write!(f, "{}", arcs.join("."))
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Value {
+            Integer(i64),
+            String(Vec<u8>),
+            Null,
+            Oid(Oid),
+            IpAddress(Ipv4Addr),
+            Counter32(u32),
+            Gauge32(u32),
+            TimeTicks(u32),
+            Counter64(u64),
+            NoSuchObject,
+            NoSuchInstance,
+            EndOfMibView,
+        }
+
+        impl Value {
+            fn encode(&self) -> Vec<u8> {
+                match self {
+                    Self::Integer(e) => integer(INTEGER, *e),
+                    Self::String(e) => tlv(OCTET_STRING, e),
+                    Self::Null => tlv(NULL, &[]),
+                    Self::Oid(e) => e.encode(),
+                    Self::IpAddress(e) => tlv(IP_ADDRESS, &e.octets()),
+                    Self::Counter32(e) => unsigned(COUNTER32, *e as u64),
+                    Self::Gauge32(e) => unsigned(GAUGE32, *e as u64),
+                    Self::TimeTicks(e) => unsigned(TIMETICKS, *e as u64),
+                    Self::Counter64(e) => unsigned(COUNTER64, *e),
+                    Self::NoSuchObject => tlv(NO_SUCH_OBJECT, &[]),
+                    Self::NoSuchInstance => tlv(NO_SUCH_INSTANCE, &[]),
+                    Self::EndOfMibView => tlv(END_OF_MIB_VIEW, &[]),
+                }
+            }
+
+            fn decode(tag: u8, content: &[u8]) -> Result<Self, SnmpError> {
+                Ok(match tag {
+                    INTEGER => Self::Integer(read_integer(content)?),
+                    OCTET_STRING => Self::String(content.to_vec()),
+                    NULL => Self::Null,
+                    OBJECT_ID => Self::Oid(Oid::decode(content)?),
+                    IP_ADDRESS => Self::IpAddress(
+                        <[u8; 4]>::try_from(content)
+                            .map_err(|_| SnmpError::Malformed)?
+                            .into(),
+                    ),
+                    COUNTER32 => Self::Counter32(read_unsigned(content)? as u32),
+                    GAUGE32 => Self::Gauge32(read_unsigned(content)? as u32),
+                    TIMETICKS => Self::TimeTicks(read_unsigned(content)? as u32),
+                    COUNTER64 => Self::Counter64(read_unsigned(content)?),
+                    NO_SUCH_OBJECT => Self::NoSuchObject,
+                    NO_SUCH_INSTANCE => Self::NoSuchInstance,
+                    END_OF_MIB_VIEW => Self::EndOfMibView,
+                    _ => return Err(SnmpError::Malformed),
+                })
+            }
+
+            pub fn as_int(&self) -> Option<i64> {
+                match self {
+                    Self::Integer(e) => Some(*e),
+                    Self::Counter32(e) | Self::Gauge32(e) | Self::TimeTicks(e) => Some(*e as i64),
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(&self) -> Option<String> {
+                match self {
+                    Self::String(e) => Some(
+                        String::from_utf8_lossy(e)
+                            .trim_end_matches('\0')
+                            .trim()
+                            .to_string(),
+                    ),
+                    _ => None,
+                }
+            }
+        }
+
+        // For GETBULK error_status and error_index carry the non repeaters
+        // and the max repetitions
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Pdu {
+            pub kind: u8,
+            pub request_id: i64,
+            pub error_status: i64,
+            pub error_index: i64,
+            pub varbinds: Vec<(Oid, Value)>,
+        }
+
+        impl Pdu {
+            fn encode(&self) -> Vec<u8> {
+                let varbinds: Vec<u8> = self
+                    .varbinds
+                    .iter()
+                    .flat_map(|(oid, value)| {
+                        tlv(SEQUENCE, &[oid.encode(), value.encode()].concat())
+                    })
+                    .collect();
+
+                tlv(
+                    self.kind,
+                    &[
+                        integer(INTEGER, self.request_id),
+                        integer(INTEGER, self.error_status),
+                        integer(INTEGER, self.error_index),
+                        tlv(SEQUENCE, &varbinds),
+                    ]
+                    .concat(),
+                )
+            }
+
+            fn decode(buf: &[u8]) -> Result<Self, SnmpError> {
+                let (kind, content, _) = read(buf)?;
+                if kind & 0xe0 != 0xa0 {
+                    return Err(SnmpError::Malformed);
+                }
+                let (request_id, rest) = expect(content, INTEGER)?;
+                let (error_status, rest) = expect(rest, INTEGER)?;
+                let (error_index, rest) = expect(rest, INTEGER)?;
+                let (mut list, _) = expect(rest, SEQUENCE)?;
+                let mut varbinds = Vec::new();
+
+                while !list.is_empty() {
+                    let (varbind, rest) = expect(list, SEQUENCE)?;
+                    let (oid, varbind) = expect(varbind, OBJECT_ID)?;
+                    let (tag, value, _) = read(varbind)?;
+                    varbinds.push((Oid::decode(oid)?, Value::decode(tag, value)?));
+                    list = rest;
+                }
+
+                Ok(Self {
+                    kind,
+                    request_id: read_integer(request_id)?,
+                    error_status: read_integer(error_status)?,
+                    error_index: read_integer(error_index)?,
+                    varbinds,
+                })
+            }
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+        pub enum AuthProtocol {
+            Md5,
+            Sha1,
+            Sha256,
+        }
+
+        impl AuthProtocol {
+            fn hash(&self, data: &[u8]) -> Vec<u8> {
+                match self {
+                    Self::Md5 => Md5::digest(data).to_vec(),
+                    Self::Sha1 => Sha1::digest(data).to_vec(),
+                    Self::Sha256 => Sha256::digest(data).to_vec(),
+                }
+            }
+
+            fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+                fn sign<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+                    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key).unwrap();
+                    mac.update(data);
+                    mac.finalize().into_bytes().to_vec()
+                }
+
+                match self {
+                    Self::Md5 => sign::<Hmac<Md5>>(key, data),
+                    Self::Sha1 => sign::<Hmac<Sha1>>(key, data),
+                    Self::Sha256 => sign::<Hmac<Sha256>>(key, data),
+                }
+            }
+
+            fn mac_len(&self) -> usize {
+                match self {
+                    Self::Sha256 => 24,
+                    _ => 12,
+                }
+            }
+
+            // RFC 3414 A.2, the password is stretched to 1MB and bound to the engine
+            pub fn localize(&self, password: &[u8], engine_id: &[u8]) -> Vec<u8> {
+                let stretched: Vec<u8> = password.iter().cycle().take(1 << 20).copied().collect();
+                let key = self.hash(&stretched);
+                self.hash(&[&key[..], engine_id, &key[..]].concat())
+            }
+        }
+
+        impl FromStr for AuthProtocol {
+            type Err = SnmpError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_lowercase().as_str() {
+                    "md5" => Ok(Self::Md5),
+                    "sha" | "sha1" => Ok(Self::Sha1),
+                    "sha256" => Ok(Self::Sha256),
+                    _ => Err(SnmpError::Unsupported("authentication protocol")),
+                }
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Security {
+            Community(String),
+            Usm {
+                user: String,
+                auth: Option<(AuthProtocol, String)>,
+            },
+        }
+
+        #[derive(Debug, Clone, Default, PartialEq)]
+        struct Usm {
+            engine_id: Vec<u8>,
+            boots: i64,
+            time: i64,
+            user: Vec<u8>,
+            auth: Vec<u8>,
+        }
+
+        impl Usm {
+            fn encode(&self) -> Vec<u8> {
+                tlv(
+                    SEQUENCE,
+                    &[
+                        tlv(OCTET_STRING, &self.engine_id),
+                        integer(INTEGER, self.boots),
+                        integer(INTEGER, self.time),
+                        tlv(OCTET_STRING, &self.user),
+                        tlv(OCTET_STRING, &self.auth),
+                        tlv(OCTET_STRING, &[]),
+                    ]
+                    .concat(),
+                )
+            }
+
+            // Also hands back the auth parameters as a slice of the message
+            fn decode(buf: &[u8]) -> Result<(Self, &[u8]), SnmpError> {
+                let (content, _) = expect(buf, SEQUENCE)?;
+                let (engine_id, rest) = expect(content, OCTET_STRING)?;
+                let (boots, rest) = expect(rest, INTEGER)?;
+                let (time, rest) = expect(rest, INTEGER)?;
+                let (user, rest) = expect(rest, OCTET_STRING)?;
+                let (auth, _) = expect(rest, OCTET_STRING)?;
+
+                Ok((
+                    Self {
+                        engine_id: engine_id.to_vec(),
+                        boots: read_integer(boots)?,
+                        time: read_integer(time)?,
+                        user: user.to_vec(),
+                        auth: auth.to_vec(),
+                    },
+                    auth,
+                ))
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Message {
+            V2c {
+                community: Vec<u8>,
+                pdu: Pdu,
+            },
+            V3 {
+                id: i64,
+                flags: u8,
+                usm: Usm,
+                pdu: Pdu,
+            },
+        }
+
+        impl Message {
+            fn pdu(&self) -> &Pdu {
+                match self {
+                    Self::V2c { pdu, .. } | Self::V3 { pdu, .. } => pdu,
+                }
+            }
+
+            fn encode(&self, key: Option<(AuthProtocol, &[u8])>) -> Vec<u8> {
+                let (id, flags, usm, pdu) = match self {
+                    Self::V2c { community, pdu } => {
+                        return tlv(
+                            SEQUENCE,
+                            &[
+                                integer(INTEGER, 1),
+                                tlv(OCTET_STRING, community),
+                                pdu.encode(),
+                            ]
+                            .concat(),
+                        )
+                    }
+                    Self::V3 {
+                        id,
+                        flags,
+                        usm,
+                        pdu,
+                    } => (id, flags, usm, pdu),
+                };
+
+                let mut usm = usm.clone();
+                if let Some((auth, _)) = key {
+                    usm.auth = vec![0; auth.mac_len()];
+                }
+                let header = [
+                    integer(INTEGER, *id),
+                    integer(INTEGER, MAX_SIZE),
+                    tlv(OCTET_STRING, &[*flags]),
+                    integer(INTEGER, SECURITY_USM),
+                ]
+                .concat();
+                let scoped = [
+                    tlv(OCTET_STRING, &usm.engine_id),
+                    tlv(OCTET_STRING, &[]),
+                    pdu.encode(),
+                ]
+                .concat();
+                let mut message = tlv(
+                    SEQUENCE,
+                    &[
+                        integer(INTEGER, 3),
+                        tlv(SEQUENCE, &header),
+                        tlv(OCTET_STRING, &usm.encode()),
+                        tlv(SEQUENCE, &scoped),
+                    ]
+                    .concat(),
+                );
+
+                if let (Some((auth, key)), Ok((_, range))) = (key, Self::decode(&message)) {
+                    let mac = auth.hmac(key, &message);
+                    message[range].copy_from_slice(&mac[..auth.mac_len()]);
+                }
+                message
+            }
+
+            // Also returns where the auth parameters are to check the signature
+            fn decode(buf: &[u8]) -> Result<(Self, Range<usize>), SnmpError> {
+                let (content, _) = expect(buf, SEQUENCE)?;
+                let (version, rest) = expect(content, INTEGER)?;
+
+                match read_integer(version)? {
+                    0 | 1 => {
+                        let (community, rest) = expect(rest, OCTET_STRING)?;
+                        let message = Self::V2c {
+                            community: community.to_vec(),
+                            pdu: Pdu::decode(rest)?,
+                        };
+                        Ok((message, 0..0))
+                    }
+                    3 => {
+                        let (header, rest) = expect(rest, SEQUENCE)?;
+                        let (id, header) = expect(header, INTEGER)?;
+                        let (_, header) = expect(header, INTEGER)?;
+                        let (flags, header) = expect(header, OCTET_STRING)?;
+                        let (model, _) = expect(header, INTEGER)?;
+                        let flags = *flags.first().ok_or(SnmpError::Malformed)?;
+                        if read_integer(model)? != SECURITY_USM {
+                            return Err(SnmpError::Unsupported("security model"));
+                        }
+                        if flags & FLAG_PRIV != 0 {
+                            return Err(SnmpError::Unsupported("privacy"));
+                        }
+
+                        let (params, rest) = expect(rest, OCTET_STRING)?;
+                        let (usm, auth) = Usm::decode(params)?;
+                        let offset = auth.as_ptr() as usize - buf.as_ptr() as usize;
+                        let (scoped, _) = expect(rest, SEQUENCE)?;
+                        let (_, scoped) = expect(scoped, OCTET_STRING)?;
+                        let (_, scoped) = expect(scoped, OCTET_STRING)?;
+
+                        let message = Self::V3 {
+                            id: read_integer(id)?,
+                            flags,
+                            usm,
+                            pdu: Pdu::decode(scoped)?,
+                        };
+                        Ok((message, offset..offset + auth.len()))
+                    }
+                    _ => Err(SnmpError::Unsupported("version")),
+                }
+            }
+        }
+
+        fn verify(buf: &[u8], range: Range<usize>, auth: AuthProtocol, key: &[u8]) -> bool {
+            if range.len() != auth.mac_len() {
+                return false;
+            }
+            let mut unsigned = buf.to_vec();
+            unsigned[range.clone()].fill(0);
+
+            auth.hmac(key, &unsigned)[..auth.mac_len()] == buf[range]
+        }
+
+        struct Engine {
+            id: Vec<u8>,
+            boots: i64,
+            time: i64,
+            at: Instant,
+            key: Option<(AuthProtocol, Vec<u8>)>,
+        }
+
+        pub struct Session {
+            socket: UdpSocket,
+            security: Security,
+            timeout: Duration,
+            request_id: i64,
+            engine: Option<Engine>,
+        }
+
+        impl Session {
+            // SNMPv3 sessions discover the engine of the agent first
+            pub async fn connect(
+                target: SocketAddr,
+                security: Security,
+                timeout: Duration,
+            ) -> Result<Self, SnmpError> {
+                let socket = UdpSocket::bind(match target {
+                    SocketAddr::V4(_) => "0.0.0.0:0",
+                    SocketAddr::V6(_) => "[::]:0",
+                })
+                .await?;
+                socket.connect(target).await?;
+                let mut session = Self {
+                    socket,
+                    security,
+                    timeout,
+                    request_id: 0,
+                    engine: None,
+                };
+
+                if let Security::Usm { auth, .. } = &session.security {
+                    if auth.as_ref().is_some_and(|x| x.1.len() < 8) {
+                        return Err(SnmpError::Unsupported(
+                            "passwords shorter than 8 characters",
+                        ));
+                    }
+                    session.discover().await?;
+                }
+                Ok(session)
+            }
+
+            async fn discover(&mut self) -> Result<(), SnmpError> {
+                self.engine = Some(Engine {
+                    id: Vec::new(),
+                    boots: 0,
+                    time: 0,
+                    at: Instant::now(),
+                    key: None,
+                });
+
+                let Message::V3 { usm, .. } = self.exchange(GET, &[], 0).await? else {
+                    return Err(SnmpError::Malformed);
+                };
+                if usm.engine_id.is_empty() {
+                    return Err(SnmpError::Malformed);
+                }
+
+                let key = match &self.security {
+                    Security::Usm {
+                        auth: Some((auth, password)),
+                        ..
+                    } => Some((*auth, auth.localize(password.as_bytes(), &usm.engine_id))),
+                    _ => None,
+                };
+                self.engine = Some(Engine {
+                    id: usm.engine_id,
+                    boots: usm.boots,
+                    time: usm.time,
+                    at: Instant::now(),
+                    key,
+                });
+                Ok(())
+            }
+
+            fn message(&self, pdu: Pdu) -> Vec<u8> {
+                match (&self.security, &self.engine) {
+                    (Security::Usm { user, .. }, Some(engine)) => {
+                        let discovery = engine.id.is_empty();
+                        let key = engine.key.as_ref().map(|(x, key)| (*x, key.as_slice()));
+                        let message = Message::V3 {
+                            id: pdu.request_id,
+                            flags: FLAG_REPORTABLE | if key.is_some() { FLAG_AUTH } else { 0 },
+                            usm: Usm {
+                                engine_id: engine.id.clone(),
+                                boots: engine.boots,
+                                time: engine.time + engine.at.elapsed().as_secs() as i64,
+                                user: if discovery {
+                                    Vec::new()
+                                } else {
+                                    user.as_bytes().to_vec()
+                                },
+                                auth: Vec::new(),
+                            },
+                            pdu,
+                        };
+                        message.encode(key)
+                    }
+                    (Security::Community(community), _) => Message::V2c {
+                        community: community.as_bytes().to_vec(),
+                        pdu,
+                    }
+                    .encode(None),
+                    (Security::Usm { .. }, None) => Message::V2c {
+                        community: Vec::new(),
+                        pdu,
+                    }
+                    .encode(None),
+                }
+            }
+
+            // Unsigned answers are only trusted for reports
+            fn accept(&self, buf: &[u8], request_id: i64) -> Option<Message> {
+                let (message, range) = Message::decode(buf).ok()?;
+                if message.pdu().request_id != request_id {
+                    return None;
+                }
+
+                match (&message, self.engine.as_ref().and_then(|x| x.key.as_ref())) {
+                    (Message::V3 { flags, pdu, .. }, Some((auth, key))) => {
+                        let signed = flags & FLAG_AUTH != 0 && verify(buf, range, *auth, key);
+                        (signed || pdu.kind == REPORT).then_some(message)
+                    }
+                    _ => Some(message),
+                }
+            }
+
+            async fn exchange(
+                &mut self,
+                kind: u8,
+                oids: &[Oid],
+                repetitions: i64,
+            ) -> Result<Message, SnmpError> {
+                for _ in 0..=RETRIES {
+                    self.request_id = (self.request_id + 1) & 0x7fff_ffff;
+                    let pdu = Pdu {
+                        kind,
+                        request_id: self.request_id,
+                        error_status: 0,
+                        error_index: repetitions,
+                        varbinds: oids.iter().map(|x| (x.clone(), Value::Null)).collect(),
+                    };
+                    self.socket.send(&self.message(pdu)).await?;
+
+                    let deadline = tokio::time::Instant::now() + self.timeout;
+                    let mut buf = vec![0u8; 65535];
+                    while let Ok(len) =
+                        tokio::time::timeout_at(deadline, self.socket.recv(&mut buf)).await
+                    {
+                        if let Some(e) = self.accept(&buf[..len?], self.request_id) {
+                            return Ok(e);
+                        }
+                    }
+                }
+
+                Err(SnmpError::Timeout)
+            }
+
+            async fn request(
+                &mut self,
+                kind: u8,
+                oids: &[Oid],
+                repetitions: i64,
+            ) -> Result<Vec<(Oid, Value)>, SnmpError> {
+                let mut synced = false;
+
+                loop {
+                    let message = self.exchange(kind, oids, repetitions).await?;
+                    let pdu = message.pdu();
+
+                    if pdu.kind == REPORT {
+                        let oid = pdu
+                            .varbinds
+                            .first()
+                            .map(|x| x.0.clone())
+                            .unwrap_or(Oid(vec![]));
+                        // The agent rebooted or the clocks drifted apart
+                        if let (false, Message::V3 { usm, .. }, Some(engine)) =
+                            (synced, &message, self.engine.as_mut())
+                        {
+                            if oid.to_string() == NOT_IN_TIME_WINDOWS {
+                                engine.boots = usm.boots;
+                                engine.time = usm.time;
+                                engine.at = Instant::now();
+                                synced = true;
+                                continue;
+                            }
+                        }
+                        return Err(SnmpError::Report(oid));
+                    }
+                    if pdu.error_status != 0 {
+                        return Err(SnmpError::Status(pdu.error_status));
+                    }
+
+                    return Ok(message.pdu().varbinds.clone());
+                }
+            }
+
+            pub async fn get(&mut self, oids: &[Oid]) -> Result<Vec<(Oid, Value)>, SnmpError> {
+                self.request(GET, oids, 0).await
+            }
+
+            // Stops at the end of the subtree or when the agent doesn't move forward
+            pub async fn walk(&mut self, root: &Oid) -> Result<Vec<(Oid, Value)>, SnmpError> {
+                let mut resp = Vec::new();
+                let mut last = root.clone();
+
+                loop {
+                    let varbinds = self
+                        .request(GET_BULK, std::slice::from_ref(&last), BULK_REPETITIONS)
+                        .await?;
+                    if varbinds.is_empty() {
+                        return Ok(resp);
+                    }
+
+                    for (oid, value) in varbinds {
+                        if !oid.starts_with(root) || oid <= last || value == Value::EndOfMibView {
+                            return Ok(resp);
+                        }
+                        last = oid.clone();
+                        resp.push((oid, value));
+                    }
+                }
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, serde::Serialize)]
+        pub struct Interface {
+            pub index: u32,
+            pub name: Option<String>,
+            pub mac: Option<MacAddr>,
+        }
+
+        #[derive(Debug, Clone, PartialEq, serde::Serialize)]
+        pub struct InterfaceAddress {
+            pub ip: IpAddr,
+            pub index: u32,
+        }
+
+        #[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+        pub struct System {
+            pub name: Option<String>,
+            pub description: Option<String>,
+            pub interfaces: Vec<Interface>,
+            pub addresses: Vec<InterfaceAddress>,
+        }
+
+        impl System {
+            pub fn interface(&self, index: u32) -> Option<&Interface> {
+                self.interfaces.iter().find(|x| x.index == index)
+            }
+        }
+
+        fn oid(value: &str) -> Oid {
+            value.parse().unwrap()
+        }
+
+        fn octets<const N: usize>(arcs: &[u32]) -> Option<[u8; N]> {
+            let octets: Vec<u8> = arcs
+                .iter()
+                .map(|x| u8::try_from(*x).ok())
+                .collect::<Option<_>>()?;
+            octets.try_into().ok()
+        }
+
+        fn usable(ip: &IpAddr) -> bool {
+            match ip {
+                IpAddr::V4(e) => !e.is_loopback() && !e.is_unspecified() && !e.is_link_local(),
+                IpAddr::V6(e) => {
+                    !e.is_loopback() && !e.is_unspecified() && e.segments()[0] & 0xffc0 != 0xfe80
+                }
+            }
+        }
+
+        // ipAddressTable covers both families, older agents only have ipAddrTable
+        pub async fn poll(session: &mut Session) -> Result<System, SnmpError> {
+            let system = session.get(&[oid(SYS_NAME), oid(SYS_DESCR)]).await?;
+            let text = |i: usize| {
+                system
+                    .get(i)
+                    .and_then(|x| x.1.as_str())
+                    .filter(|x| !x.is_empty())
+            };
+            let mut interfaces: BTreeMap<u32, Interface> = BTreeMap::new();
+
+            for root in [IF_DESCR, IF_NAME, IF_PHYS_ADDRESS] {
+                let root = oid(root);
+                for (oid, value) in session.walk(&root).await? {
+                    let Some(&[index]) = oid.suffix(&root) else {
+                        continue;
+                    };
+                    let interface = interfaces.entry(index).or_insert(Interface {
+                        index,
+                        name: None,
+                        mac: None,
+                    });
+
+                    match (root.to_string().as_str(), &value) {
+                        (IF_PHYS_ADDRESS, Value::String(e)) => {
+                            interface.mac = <[u8; 6]>::try_from(e.as_slice())
+                                .ok()
+                                .filter(|x| x != &[0; 6])
+                                .map(MacAddr::new);
+                        }
+                        (IF_DESCR, _) | (IF_NAME, _) => {
+                            interface.name = value
+                                .as_str()
+                                .filter(|x| !x.is_empty())
+                                .or(interface.name.take());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let mut addresses = Vec::new();
+            let root = oid(IP_ADDRESS_IF_INDEX);
+            for (oid, value) in session.walk(&root).await? {
+                let ip = match oid.suffix(&root) {
+                    Some([1, 4, rest @ ..]) => octets::<4>(rest).map(|x| IpAddr::V4(x.into())),
+                    Some([2, 16, rest @ ..]) => {
+                        octets::<16>(rest).map(|x| IpAddr::V6(Ipv6Addr::from(x)))
+                    }
+                    _ => None,
+                };
+                if let (Some(ip), Some(index)) = (ip, value.as_int()) {
+                    addresses.push(InterfaceAddress {
+                        ip,
+                        index: index as u32,
+                    });
+                }
+            }
+            if addresses.is_empty() {
+                let root = oid(IP_AD_ENT_IF_INDEX);
+                for (oid, value) in session.walk(&root).await? {
+                    let ip = oid.suffix(&root).and_then(octets::<4>);
+                    if let (Some(ip), Some(index)) = (ip, value.as_int()) {
+                        addresses.push(InterfaceAddress {
+                            ip: IpAddr::V4(ip.into()),
+                            index: index as u32,
+                        });
+                    }
+                }
+            }
+            addresses.retain(|x| usable(&x.ip));
+            addresses.sort_by_key(|x| x.ip);
+            addresses.dedup_by_key(|x| x.ip);
+
+            Ok(System {
+                name: text(0),
+                description: text(1),
+                interfaces: interfaces.into_values().collect(),
+                addresses,
+            })
+        }
+
+        // ipNetToPhysicalTable is indexed by ifIndex.type.length.address,
+        // the deprecated ipNetToMediaTable by ifIndex.address
+        pub async fn neighbors(session: &mut Session) -> Result<Vec<Neighbor>, SnmpError> {
+            let mut resp = Vec::new();
+            let mac = |value: &Value| match value {
+                Value::String(e) => <[u8; 6]>::try_from(e.as_slice()).ok().map(MacAddr::new),
+                _ => None,
+            };
+
+            let root = oid(IP_NET_TO_PHYSICAL_PHYS_ADDRESS);
+            for (oid, value) in session.walk(&root).await? {
+                let ip = match oid.suffix(&root) {
+                    Some([_, 1, 4, rest @ ..]) => octets::<4>(rest).map(|x| IpAddr::V4(x.into())),
+                    Some([_, 2, 16, rest @ ..]) => {
+                        octets::<16>(rest).map(|x| IpAddr::V6(Ipv6Addr::from(x)))
+                    }
+                    _ => None,
+                };
+                if let (Some(ip), Some(mac)) = (ip, mac(&value)) {
+                    resp.push(Neighbor { ip, mac });
+                }
+            }
+            if resp.is_empty() {
+                let root = oid(IP_NET_TO_MEDIA_PHYS_ADDRESS);
+                for (oid, value) in session.walk(&root).await? {
+                    let ip = match oid.suffix(&root) {
+                        Some([_, rest @ ..]) => octets::<4>(rest),
+                        _ => None,
+                    };
+                    if let (Some(ip), Some(mac)) = (ip, mac(&value)) {
+                        resp.push(Neighbor {
+                            ip: IpAddr::V4(ip.into()),
+                            mac,
+                        });
+                    }
+                }
+            }
+
+            Ok(resp)
+        }
+
+        // Answers from a fixed tree, for tests and local development
+        pub struct Agent {
+            community: String,
+            user: Option<(String, Option<(AuthProtocol, String)>)>,
+            engine_id: Vec<u8>,
+            tree: BTreeMap<Oid, Value>,
+        }
+
+        impl Agent {
+            pub fn new(tree: impl IntoIterator<Item = (Oid, Value)>) -> Self {
+                Self {
+                    community: "public".to_string(),
+                    user: None,
+                    engine_id: b"\x80\x00\x1f\x88\x04ipam-agent".to_vec(),
+                    tree: tree.into_iter().collect(),
+                }
+            }
+
+            pub fn community(mut self, community: &str) -> Self {
+                self.community = community.to_string();
+                self
+            }
+
+            pub fn user(mut self, user: &str, auth: Option<(AuthProtocol, &str)>) -> Self {
+                self.user = Some((user.to_string(), auth.map(|(x, y)| (x, y.to_string()))));
+                self
+            }
+
+            pub async fn bind(
+                self,
+                addr: SocketAddr,
+            ) -> std::io::Result<(SocketAddr, JoinHandle<()>)> {
+                let socket = UdpSocket::bind(addr).await?;
+                let local = socket.local_addr()?;
+                let handle = tokio::spawn(async move {
+                    let start = Instant::now();
+                    let mut buf = vec![0u8; 65535];
+
+                    loop {
+                        let Ok((len, from)) = socket.recv_from(&mut buf).await else {
+                            continue;
+                        };
+                        if let Some(e) = self.answer(&buf[..len], start.elapsed().as_secs() as i64)
+                        {
+                            let _ = socket.send_to(&e, from).await;
+                        }
+                    }
+                });
+
+                Ok((local, handle))
+            }
+
+            fn answer(&self, buf: &[u8], time: i64) -> Option<Vec<u8>> {
+                let (message, range) = Message::decode(buf).ok()?;
+
+                let (id, flags, usm, pdu) = match message {
+                    Message::V2c { community, pdu } => {
+                        return (community == self.community.as_bytes()).then(|| {
+                            Message::V2c {
+                                community,
+                                pdu: self.process(&pdu),
+                            }
+                            .encode(None)
+                        })
+                    }
+                    Message::V3 {
+                        id,
+                        flags,
+                        usm,
+                        pdu,
+                    } => (id, flags, usm, pdu),
+                };
+                let (user, auth) = self.user.as_ref()?;
+                let engine = Usm {
+                    engine_id: self.engine_id.clone(),
+                    boots: 1,
+                    time,
+                    ..Default::default()
+                };
+
+                if usm.engine_id != self.engine_id {
+                    let report = Pdu {
+                        kind: REPORT,
+                        request_id: pdu.request_id,
+                        error_status: 0,
+                        error_index: 0,
+                        varbinds: vec![(oid(UNKNOWN_ENGINE_IDS), Value::Counter32(1))],
+                    };
+                    return Some(
+                        Message::V3 {
+                            id,
+                            flags: 0,
+                            usm: engine,
+                            pdu: report,
+                        }
+                        .encode(None),
+                    );
+                }
+                if usm.user != user.as_bytes() {
+                    return None;
+                }
+
+                let key = match auth {
+                    Some((auth, password)) => {
+                        let key = auth.localize(password.as_bytes(), &self.engine_id);
+                        if flags & FLAG_AUTH == 0 || !verify(buf, range, *auth, &key) {
+                            return None;
+                        }
+                        Some((*auth, key))
+                    }
+                    None => None,
+                };
+                let message = Message::V3 {
+                    id,
+                    flags: flags & FLAG_AUTH,
+                    usm: Usm {
+                        user: usm.user,
+                        ..engine
+                    },
+                    pdu: self.process(&pdu),
+                };
+                Some(message.encode(key.as_ref().map(|(x, key)| (*x, key.as_slice()))))
+            }
+
+            fn process(&self, pdu: &Pdu) -> Pdu {
+                let next = |oid: &Oid| {
+                    self.tree
+                        .range((Bound::Excluded(oid.clone()), Bound::Unbounded))
+                        .next()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                };
+                let mut varbinds = Vec::new();
+
+                for (oid, _) in &pdu.varbinds {
+                    match pdu.kind {
+                        GET => varbinds.push((
+                            oid.clone(),
+                            self.tree.get(oid).cloned().unwrap_or(Value::NoSuchObject),
+                        )),
+                        GET_NEXT => {
+                            varbinds.push(next(oid).unwrap_or((oid.clone(), Value::EndOfMibView)))
+                        }
+                        GET_BULK => {
+                            let mut last = oid.clone();
+                            for _ in 0..pdu.error_index.max(1) {
+                                let Some(e) = next(&last) else {
+                                    varbinds.push((last, Value::EndOfMibView));
+                                    break;
+                                };
+                                last = e.0.clone();
+                                varbinds.push(e);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                Pdu {
+                    kind: RESPONSE,
+                    request_id: pdu.request_id,
+                    error_status: 0,
+                    error_index: 0,
+                    varbinds,
+                }
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+            use tokio::runtime::Runtime;
+
+            fn hex(value: &str) -> Vec<u8> {
+                (0..value.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+                    .collect()
+            }
+
+            fn tree() -> Vec<(Oid, Value)> {
+                let text = |x: &str| Value::String(x.as_bytes().to_vec());
+                vec![
+                    (oid(SYS_DESCR), text("RouterOS CCR2004")),
+                    (oid(SYS_NAME), text("core-router")),
+                    (oid("1.3.6.1.2.1.2.2.1.2.1"), text("lo")),
+                    (oid("1.3.6.1.2.1.2.2.1.2.2"), text("Ethernet 1")),
+                    (oid("1.3.6.1.2.1.2.2.1.6.1"), Value::String(Vec::new())),
+                    (
+                        oid("1.3.6.1.2.1.2.2.1.6.2"),
+                        Value::String(vec![0x00, 0x0c, 0x42, 0x01, 0x02, 0x03]),
+                    ),
+                    (oid("1.3.6.1.2.1.31.1.1.1.1.2"), text("ether1")),
+                    (oid("1.3.6.1.2.1.4.34.1.3.1.4.10.0.0.1"), Value::Integer(2)),
+                    (oid("1.3.6.1.2.1.4.34.1.3.1.4.127.0.0.1"), Value::Integer(1)),
+                    (
+                        oid("1.3.6.1.2.1.4.34.1.3.2.16.32.1.13.184.0.0.0.0.0.0.0.0.0.0.0.1"),
+                        Value::Integer(2),
+                    ),
+                    (
+                        oid("1.3.6.1.2.1.4.22.1.2.2.10.0.0.20"),
+                        Value::String(vec![0x3c, 0x5a, 0xb4, 0x00, 0x00, 0x01]),
+                    ),
+                    (oid("1.3.6.1.6.3.10.2.1.3.0"), Value::Integer(7)),
+                ]
+            }
+
+            #[test]
+            fn snmp_ber() {
+                assert_eq!(vec![2, 2, 0, 0x80], integer(INTEGER, 128));
+                assert_eq!(vec![2, 1, 0xff], integer(INTEGER, -1));
+                assert_eq!(Ok(-129), read_integer(&integer(INTEGER, -129)[2..]));
+                assert_eq!(vec![0x41, 2, 0, 0xff], unsigned(COUNTER32, 255));
+
+                let sys_name = oid(SYS_NAME);
+                let encoded = sys_name.encode();
+                assert_eq!(hex("06082b06010201010500"), encoded);
+                assert_eq!(Ok(sys_name.clone()), Oid::decode(&encoded[2..]));
+                assert_eq!(SYS_NAME, sys_name.to_string());
+                assert_eq!(
+                    Ok(Oid(vec![1, 3, 6, 1, 4, 1, 2021])),
+                    Oid::decode(&oid("1.3.6.1.4.1.2021").encode()[2..])
+                );
+
+                let pdu = Pdu {
+                    kind: RESPONSE,
+                    request_id: 0x1234_5678,
+                    error_status: 0,
+                    error_index: 0,
+                    varbinds: tree(),
+                };
+                assert_eq!(Ok(pdu.clone()), Pdu::decode(&pdu.encode()));
+                let message = Message::V2c {
+                    community: b"public".to_vec(),
+                    pdu,
+                };
+                assert_eq!(
+                    Ok((message.clone(), 0..0)),
+                    Message::decode(&message.encode(None))
+                );
+            }
+
+            #[test]
+            fn snmp_localized_key() {
+                let engine = hex("000000000000000000000002");
+                assert_eq!(
+                    hex("526f5eed9fcce26f8964c2930787d82b"),
+                    AuthProtocol::Md5.localize(b"maplesyrup", &engine)
+                );
+                assert_eq!(
+                    hex("6695febc9288e36282235fc7151f128497b38f3f"),
+                    AuthProtocol::Sha1.localize(b"maplesyrup", &engine)
+                );
+            }
+
+            #[test]
+            fn snmp_poll_v2c() {
+                let runtime = Runtime::new().unwrap();
+                let (addr, _agent) = runtime
+                    .block_on(
+                        Agent::new(tree())
+                            .community("s3cret")
+                            .bind("127.0.0.1:0".parse().unwrap()),
+                    )
+                    .unwrap();
+                let timeout = Duration::from_millis(200);
+
+                let system = runtime.block_on(async {
+                    let mut session =
+                        Session::connect(addr, Security::Community("s3cret".into()), timeout)
+                            .await?;
+                    poll(&mut session).await
+                });
+                assert_eq!(
+                    Ok(System {
+                        name: Some("core-router".into()),
+                        description: Some("RouterOS CCR2004".into()),
+                        interfaces: vec![
+                            Interface {
+                                index: 1,
+                                name: Some("lo".into()),
+                                mac: None
+                            },
+                            Interface {
+                                index: 2,
+                                name: Some("ether1".into()),
+                                mac: Some("00:0c:42:01:02:03".parse().unwrap())
+                            },
+                        ],
+                        addresses: vec![
+                            InterfaceAddress {
+                                ip: "10.0.0.1".parse().unwrap(),
+                                index: 2
+                            },
+                            InterfaceAddress {
+                                ip: "2001:db8::1".parse().unwrap(),
+                                index: 2
+                            },
+                        ],
+                    }),
+                    system
+                );
+
+                let resp = runtime.block_on(async {
+                    let mut session =
+                        Session::connect(addr, Security::Community("s3cret".into()), timeout)
+                            .await?;
+                    neighbors(&mut session).await
+                });
+                assert_eq!(
+                    Ok(vec![Neighbor {
+                        ip: "10.0.0.20".parse().unwrap(),
+                        mac: "3c:5a:b4:00:00:01".parse().unwrap()
+                    }]),
+                    resp
+                );
+
+                let resp = runtime.block_on(async {
+                    let mut session =
+                        Session::connect(addr, Security::Community("public".into()), timeout)
+                            .await?;
+                    session.get(&[oid(SYS_NAME)]).await
+                });
+                assert_eq!(Err(SnmpError::Timeout), resp);
+            }
+
+            #[test]
+            fn snmp_poll_v3() {
+                let runtime = Runtime::new().unwrap();
+                let (addr, _agent) = runtime
+                    .block_on(
+                        Agent::new(tree())
+                            .user("ipam", Some((AuthProtocol::Sha1, "maplesyrup")))
+                            .bind("127.0.0.1:0".parse().unwrap()),
+                    )
+                    .unwrap();
+                let timeout = Duration::from_millis(200);
+                let security = |password: &str| Security::Usm {
+                    user: "ipam".into(),
+                    auth: Some((AuthProtocol::Sha1, password.to_string())),
+                };
+
+                let system = runtime.block_on(async {
+                    let mut session =
+                        Session::connect(addr, security("maplesyrup"), timeout).await?;
+                    poll(&mut session).await
+                });
+                assert_eq!(Some("core-router".to_string()), system.unwrap().name);
+
+                let resp = runtime.block_on(async {
+                    let mut session =
+                        Session::connect(addr, security("pancakes!"), timeout).await?;
+                    session.get(&[oid(SYS_NAME)]).await
+                });
+                assert_eq!(Err(SnmpError::Timeout), resp);
+            }
+        }
+    }
+
+    pub mod dns {
+        use ipnet::IpNet;
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+        use time::Date;
+
+        pub fn reverse_name(ip: &IpAddr) -> String {
+            match ip {
+                IpAddr::V4(ip) => {
+                    let octets: Vec<String> = ip.octets().iter().rev().map(u8::to_string).collect();
+                    format!("{}.in-addr.arpa", octets.join("."))
+                }
+                IpAddr::V6(ip) => {
+                    let nibbles: Vec<String> = ip
+                        .octets()
+                        .iter()
+                        .flat_map(|x| [x >> 4, x & 0x0f])
+                        .rev()
+                        .map(|x| format!("{:x}", x))
+                        .collect();
+                    format!("{}.ip6.arpa", nibbles.join("."))
+                }
+            }
+        }
+
+        // Networks that aren't on an octet (nibble for ipv6) boundary
+        // belong to the reverse zone of the enclosing boundary
+        pub fn reverse_zone(network: &IpNet) -> String {
+            let labels = match network {
+                IpNet::V4(e) => (e.prefix_len() / 8).max(1),
+                IpNet::V6(e) => (e.prefix_len() / 4).max(1),
+            } as usize;
+            let name = reverse_name(&network.network());
+            let skip = name.split('.').count() - 2 - labels;
+
+            name.split('.').skip(skip).collect::<Vec<&str>>().join(".")
+        }
+
+        pub fn is_reverse_zone(zone: &str) -> bool {
+            zone.ends_with("in-addr.arpa") || zone.ends_with("ip6.arpa")
+        }
+
+        // YYYYMMDDnn, it always moves forward even if the clock goes back
+        pub fn next_serial(previous: Option<u32>, today: Date) -> u32 {
+            let base = (today.year() as u32 * 10000
+                + u8::from(today.month()) as u32 * 100
+                + today.day() as u32)
+                * 100;
+
+            match previous {
+                Some(e) if e >= base => e + 1,
+                _ => base,
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Record {
+            Ns(String),
+            A(Ipv4Addr),
+            Aaaa(Ipv6Addr),
+            Ptr(String),
+        }
+
+        impl std::fmt::Display for Record {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Ns(e) => write!(f, "NS\t{}", e),
+                    Self::A(e) => write!(f, "A\t{}", e),
+                    Self::Aaaa(e) => write!(f, "AAAA\t{}", e),
+                    Self::Ptr(e) => write!(f, "PTR\t{}", e),
+                }
+            }
+        }
+
+        impl From<IpAddr> for Record {
+            fn from(value: IpAddr) -> Self {
+                match value {
+                    IpAddr::V4(e) => Self::A(e),
+                    IpAddr::V6(e) => Self::Aaaa(e),
+                }
+            }
+        }
+
+        #[derive(Debug)]
+        pub struct Soa {
+            pub primary: String,
+            pub hostmaster: String,
+            pub serial: u32,
+            pub refresh: u32,
+            pub retry: u32,
+            pub expire: u32,
+            pub minimum: u32,
+        }
+
+        #[derive(Debug)]
+        pub struct Zone {
+            pub origin: String,
+            pub ttl: u32,
+            pub soa: Soa,
+            pub records: Vec<(String, Record)>,
+        }
+
+        // Names are written absolute (with the trailing dot) when they don't
+        // belong to the origin
+        pub fn absolute(name: &str) -> String {
+            format!("{}.", name.trim_end_matches('.'))
+        }
+
+        pub fn relative(name: &str, origin: &str) -> String {
+            let name = name.trim_end_matches('.');
+            let origin = origin.trim_end_matches('.');
+
+            if name == origin {
+                "@".to_string()
+            } else {
+                match name.strip_suffix(origin).and_then(|x| x.strip_suffix('.')) {
+                    Some(e) => e.to_string(),
+                    None => absolute(name),
+                }
+            }
+        }
+
+        impl Zone {
+            pub fn records(&self) -> String {
+                self.records
+                    .iter()
+                    .map(|(owner, record)| format!("{}\tIN\t{}\n", owner, record))
+                    .collect()
+            }
+
+            pub fn render(&self) -> String {
+                format!(
+                    "$ORIGIN {}\n$TTL {}\n@\tIN\tSOA\t{} {} (\n\t\t{}\t; serial\n\t\t{}\t; refresh\n\t\t{}\t; retry\n\t\t{}\t; expire\n\t\t{} )\t; minimum\n{}",
+                    absolute(&self.origin),
+                    self.ttl,
+                    absolute(&self.soa.primary),
+                    absolute(&self.soa.hostmaster),
+                    self.soa.serial,
+                    self.soa.refresh,
+                    self.soa.retry,
+                    self.soa.expire,
+                    self.soa.minimum,
+                    self.records(),
+                )
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+            use time::{Date, Month};
+
+            #[test]
+            fn dns_reverse_name() {
+                let ip: IpAddr = "192.168.1.10".parse().unwrap();
+                assert_eq!("10.1.168.192.in-addr.arpa", reverse_name(&ip));
+
+                let ip: IpAddr = "2001:db8::1".parse().unwrap();
+                assert_eq!(
+                    "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+                    reverse_name(&ip)
+                );
+            }
+
+            #[test]
+            fn dns_reverse_zone() {
+                let zone = |x: &str| reverse_zone(&x.parse().unwrap());
+                assert_eq!("1.168.192.in-addr.arpa", zone("192.168.1.0/24"));
+                assert_eq!("168.192.in-addr.arpa", zone("192.168.0.0/16"));
+                assert_eq!("1.168.192.in-addr.arpa", zone("192.168.1.64/26"));
+                assert_eq!("10.in-addr.arpa", zone("10.0.0.0/8"));
+                assert_eq!("8.b.d.0.1.0.0.2.ip6.arpa", zone("2001:db8::/32"));
+                assert_eq!("8.b.d.0.1.0.0.2.ip6.arpa", zone("2001:db8::/34"));
+                assert_eq!("0.8.b.d.0.1.0.0.2.ip6.arpa", zone("2001:db8::/36"));
+                assert!(is_reverse_zone(&zone("2001:db8::/64")));
+            }
+
+            #[test]
+            fn dns_next_serial() {
+                let today = Date::from_calendar_date(2024, Month::March, 5).unwrap();
+                assert_eq!(2024030500, next_serial(None, today));
+                assert_eq!(2024030500, next_serial(Some(2024030417), today));
+                assert_eq!(2024030508, next_serial(Some(2024030507), today));
+                assert_eq!(2024031001, next_serial(Some(2024031000), today));
+            }
+
+            #[test]
+            fn dns_relative_names() {
+                assert_eq!("@", relative("example.com.", "example.com"));
+                assert_eq!("printer", relative("printer.example.com", "example.com"));
+                assert_eq!("a.b", relative("a.b.example.com", "example.com."));
+                assert_eq!("ns1.other.org.", relative("ns1.other.org", "example.com"));
+                assert_eq!(
+                    "ns1.myexample.com.",
+                    relative("ns1.myexample.com", "example.com")
+                );
+            }
+
+            #[test]
+            fn dns_zone_render() {
+                let zone = Zone {
+                    origin: "example.com".to_string(),
+                    ttl: 3600,
+                    soa: Soa {
+                        primary: "ns1.example.com".to_string(),
+                        hostmaster: "hostmaster.example.com".to_string(),
+                        serial: 2024030500,
+                        refresh: 3600,
+                        retry: 900,
+                        expire: 604800,
+                        minimum: 3600,
+                    },
+                    records: vec![
+                        ("@".to_string(), Record::Ns("ns1.example.com.".to_string())),
+                        (
+                            "printer".to_string(),
+                            Record::A("10.0.0.5".parse().unwrap()),
+                        ),
+                        (
+                            "nas".to_string(),
+                            Record::Aaaa("2001:db8::5".parse().unwrap()),
+                        ),
+                    ],
+                };
+
+                assert_eq!(
+                    "$ORIGIN example.com.\n\
+                     $TTL 3600\n\
+                     @\tIN\tSOA\tns1.example.com. hostmaster.example.com. (\n\
+                     \t\t2024030500\t; serial\n\
+                     \t\t3600\t; refresh\n\
+                     \t\t900\t; retry\n\
+                     \t\t604800\t; expire\n\
+                     \t\t3600 )\t; minimum\n\
+                     @\tIN\tNS\tns1.example.com.\n\
+                     printer\tIN\tA\t10.0.0.5\n\
+                     nas\tIN\tAAAA\t2001:db8::5\n",
+                    zone.render()
+                );
+            }
+        }
+    }
+
+    pub mod oui {
+        use crate::type_net::mac::{MacAddr, Oui};
+        use std::collections::HashMap;
+
+        #[derive(Debug, Default)]
+        pub struct OuiDatabase(HashMap<Oui, String>);
+
+        impl OuiDatabase {
+            // Reads the IEEE listing (oui.txt) and its csv export (oui.csv),
+            // MA-M and MA-S blocks are ignored
+            pub fn parse(text: &str) -> Self {
+                let mut resp = HashMap::new();
+
+                for line in text.lines() {
+                    let entry = match line.split_once("(hex)") {
+                        Some((oui, vendor)) => Some((oui.trim(), vendor.trim().to_string())),
+                        None => csv_entry(line),
+                    };
+
+                    if let Some((oui, vendor)) = entry {
+                        if let (Ok(oui), false) = (oui.parse::<Oui>(), vendor.is_empty()) {
+                            resp.insert(oui, vendor);
+                        }
+                    }
+                }
+
+                Self(resp)
+            }
+
+            pub fn lookup(&self, mac: &MacAddr) -> Option<&str> {
+                self.0.get(&mac.oui()).map(String::as_str)
+            }
+
+            pub fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+        }
+
+        fn csv_entry(line: &str) -> Option<(&str, String)> {
+            let mut fields = line.splitn(3, ',');
+            let registry = fields.next()?;
+            let oui = fields.next()?;
+            let rest = fields.next()?;
+
+            if registry != "MA-L" {
+                return None;
+            }
+
+            let vendor = match rest.strip_prefix('"') {
+                Some(quoted) => {
+                    let mut vendor = String::new();
+                    let mut chars = quoted.chars().peekable();
+                    while let Some(c) = chars.next() {
+                        match (c, chars.peek()) {
+                            ('"', Some('"')) => {
+                                vendor.push('"');
+                                chars.next();
+                            }
+                            ('"', _) => break,
+                            (c, _) => vendor.push(c),
+                        }
+                    }
+                    vendor
+                }
+                None => rest.split(',').next().unwrap_or_default().to_string(),
+            };
+
+            Some((oui, vendor.trim().to_string()))
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::OuiDatabase;
+            use crate::type_net::mac::MacAddr;
+
+            #[test]
+            fn oui_parse_ieee_listing() {
+                let db = OuiDatabase::parse(
+                    "OUI/MA-L                                                    Organization\n\
+                     company_id                                                  Organization\n\
+                     \n\
+                     00-1B-44   (hex)\t\tSanDisk Corporation\n\
+                     001B44     (base 16)\t\tSanDisk Corporation\n\
+                     \t\t\t\tMilpitas  CA  95035\n",
+                );
+                let mac: MacAddr = "00:1b:44:11:3a:b7".parse().unwrap();
+
+                assert_eq!(1, db.len());
+                assert_eq!(Some("SanDisk Corporation"), db.lookup(&mac));
+            }
+
+            #[test]
+            fn oui_parse_csv_export() {
+                let db = OuiDatabase::parse(
+                    "Registry,Assignment,Organization Name,Organization Address\n\
+                     MA-L,3C5AB4,\"Google, Inc.\",1600 Amphitheatre Parkway Mountain View CA US 94043\n\
+                     MA-L,001B44,SanDisk Corporation,Milpitas CA US 95035\n\
+                     MA-M,70B3D5F2F,Vendor,Somewhere\n",
+                );
+
+                assert_eq!(2, db.len());
+                assert_eq!(
+                    Some("Google, Inc."),
+                    db.lookup(&"3c:5a:b4:00:00:01".parse().unwrap())
+                );
+                assert_eq!(
+                    Some("SanDisk Corporation"),
+                    db.lookup(&"00:1b:44:00:00:01".parse().unwrap())
+                );
+            }
+
+            #[test]
+            fn oui_lookup_unknown() {
+                let db = OuiDatabase::parse("00-1B-44   (hex)\t\tSanDisk Corporation\n");
+                assert!(db.lookup(&"70:b3:d5:00:00:01".parse().unwrap()).is_none());
+                assert!(OuiDatabase::default().is_empty());
+            }
+        }
+    }
+
+    // Dynamic updates (RFC 2136) signed with TSIG (RFC 8945)
+    pub mod nsupdate {
+        use super::dns::Record;
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+        use std::{net::SocketAddr, time::Duration};
+        use time::OffsetDateTime;
+        use tokio::net::UdpSocket;
+        use uuid::Uuid;
+
+        const TYPE_SOA: u16 = 6;
+        const TYPE_TSIG: u16 = 250;
+        const CLASS_IN: u16 = 1;
+        const CLASS_NONE: u16 = 254;
+        const CLASS_ANY: u16 = 255;
+        const OPCODE_UPDATE: u16 = 5 << 11;
+        const SERVFAIL: u8 = 2;
+        const FUDGE: u16 = 300;
+        const ALGORITHM: &str = "hmac-sha256";
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Change {
+            Add(String, u32, Record),
+            Delete(String, Record),
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct Tsig {
+            name: String,
+            secret: Vec<u8>,
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct Client {
+            pub server: SocketAddr,
+            pub tsig: Option<Tsig>,
+            pub timeout: Duration,
+            pub retries: u32,
+        }
+
+        #[derive(Debug, PartialEq)]
+        pub enum UpdateError {
+            InvalidKey,
+            Io(String),
+            Timeout,
+            Malformed,
+            Rcode(u8),
+        }
+
+        impl std::fmt::Display for UpdateError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::InvalidKey => write!(f, "The TSIG secret isn't valid base64"),
+                    Self::Io(e) => write!(f, "{}", e),
+                    Self::Timeout => write!(f, "The server didn't answer"),
+                    Self::Malformed => write!(f, "The answer of the server is malformed"),
+                    Self::Rcode(e) => write!(f, "The server answered with rcode {}", e),
+                }
+            }
+        }
+
+        impl std::error::Error for UpdateError {}
+
+        impl From<std::io::Error> for UpdateError {
+            fn from(value: std::io::Error) -> Self {
+                Self::Io(value.to_string())
+            }
+        }
+
+        impl Record {
+            fn rtype(&self) -> u16 {
+                match self {
+                    Self::A(_) => 1,
+                    Self::Ns(_) => 2,
+                    Self::Ptr(_) => 12,
+                    Self::Aaaa(_) => 28,
+                }
+            }
+
+            fn rdata(&self) -> Vec<u8> {
+                match self {
+                    Self::A(e) => e.octets().to_vec(),
+                    Self::Aaaa(e) => e.octets().to_vec(),
+                    Self::Ns(e) | Self::Ptr(e) => {
+                        let mut buf = Vec::new();
+                        name(&mut buf, e);
+                        buf
+                    }
+                }
+            }
+        }
+
+        fn name(buf: &mut Vec<u8>, name: &str) {
+            for label in name.trim_end_matches('.').split('.') {
+                buf.push(label.len() as u8);
+                buf.extend(label.to_ascii_lowercase().as_bytes());
+            }
+            buf.push(0);
+        }
+
+        fn record(buf: &mut Vec<u8>, owner: &str, class: u16, ttl: u32, rtype: u16, rdata: &[u8]) {
+            name(buf, owner);
+            buf.extend(rtype.to_be_bytes());
+            buf.extend(class.to_be_bytes());
+            buf.extend(ttl.to_be_bytes());
+            buf.extend((rdata.len() as u16).to_be_bytes());
+            buf.extend(rdata);
+        }
+
+        impl Tsig {
+            pub fn new(name: &str, secret: &str) -> Result<Self, UpdateError> {
+                Ok(Self {
+                    name: name.to_string(),
+                    secret: STANDARD
+                        .decode(secret)
+                        .map_err(|_| UpdateError::InvalidKey)?,
+                })
+            }
+
+            fn sign(&self, buf: &mut Vec<u8>, id: u16, now: u64) {
+                let mut key = Vec::new();
+                name(&mut key, &self.name);
+                let mut algorithm = Vec::new();
+                name(&mut algorithm, ALGORITHM);
+                let time = &now.to_be_bytes()[2..];
+
+                let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret)
+                    .expect("HMAC takes keys of any size");
+                mac.update(buf);
+                mac.update(&key);
+                mac.update(&CLASS_ANY.to_be_bytes());
+                mac.update(&0u32.to_be_bytes());
+                mac.update(&algorithm);
+                mac.update(time);
+                mac.update(&FUDGE.to_be_bytes());
+                mac.update(&[0, 0, 0, 0]);
+                let mac = mac.finalize().into_bytes();
+
+                let mut rdata = algorithm;
+                rdata.extend(time);
+                rdata.extend(FUDGE.to_be_bytes());
+                rdata.extend((mac.len() as u16).to_be_bytes());
+                rdata.extend(mac);
+                rdata.extend(id.to_be_bytes());
+                rdata.extend([0, 0, 0, 0]);
+                record(buf, &self.name, CLASS_ANY, 0, TYPE_TSIG, &rdata);
+
+                let count = u16::from_be_bytes([buf[10], buf[11]]) + 1;
+                buf[10..12].copy_from_slice(&count.to_be_bytes());
+            }
+        }
+
+        pub fn message(
+            id: u16,
+            zone: &str,
+            changes: &[Change],
+            tsig: Option<&Tsig>,
+            now: u64,
+        ) -> Vec<u8> {
+            let mut buf = Vec::new();
+            buf.extend(id.to_be_bytes());
+            buf.extend(OPCODE_UPDATE.to_be_bytes());
+            for count in [1, 0, changes.len() as u16, 0] {
+                buf.extend(count.to_be_bytes());
+            }
+
+            name(&mut buf, zone);
+            buf.extend(TYPE_SOA.to_be_bytes());
+            buf.extend(CLASS_IN.to_be_bytes());
+
+            for change in changes {
+                match change {
+                    Change::Add(owner, ttl, e) => {
+                        record(&mut buf, owner, CLASS_IN, *ttl, e.rtype(), &e.rdata())
+                    }
+                    Change::Delete(owner, e) => {
+                        record(&mut buf, owner, CLASS_NONE, 0, e.rtype(), &e.rdata())
+                    }
+                }
+            }
+
+            if let Some(tsig) = tsig {
+                tsig.sign(&mut buf, id, now);
+            }
+
+            buf
+        }
+
+        pub fn rcode(id: u16, response: &[u8]) -> Result<u8, UpdateError> {
+            if response.len() < 12 || response[..2] != id.to_be_bytes() || response[2] & 0x80 == 0 {
+                return Err(UpdateError::Malformed);
+            }
+
+            Ok(response[3] & 0x0f)
+        }
+
+        impl Client {
+            // Timeouts and SERVFAIL are retried, any other answer is final
+            pub async fn update(&self, zone: &str, changes: &[Change]) -> Result<(), UpdateError> {
+                let mut attempt = 0;
+
+                loop {
+                    match self.send(zone, changes).await {
+                        Err(
+                            UpdateError::Timeout
+                            | UpdateError::Io(_)
+                            | UpdateError::Rcode(SERVFAIL),
+                        ) if attempt < self.retries => {
+                            attempt += 1;
+                            tokio::time::sleep(self.timeout * attempt).await;
+                        }
+                        resp => return resp,
+                    }
+                }
+            }
+
+            async fn send(&self, zone: &str, changes: &[Change]) -> Result<(), UpdateError> {
+                let random = Uuid::new_v4().into_bytes();
+                let id = u16::from_be_bytes([random[0], random[1]]);
+                let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+                let message = message(id, zone, changes, self.tsig.as_ref(), now);
+
+                let local = if self.server.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(local).await?;
+                socket.connect(self.server).await?;
+                socket.send(&message).await?;
+
+                let mut buf = [0; 512];
+                let len = tokio::time::timeout(self.timeout, socket.recv(&mut buf))
+                    .await
+                    .map_err(|_| UpdateError::Timeout)??;
+
+                match rcode(id, &buf[..len])? {
+                    0 => Ok(()),
+                    e => Err(UpdateError::Rcode(e)),
+                }
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+            use std::net::UdpSocket;
+            use std::thread::JoinHandle;
+            use tokio::runtime::Runtime;
+
+            // Answers each request with the next rcode, None drops the request
+            fn mock_server(answers: Vec<Option<u8>>) -> (SocketAddr, JoinHandle<Vec<Vec<u8>>>) {
+                let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+                socket
+                    .set_read_timeout(Some(Duration::from_millis(500)))
+                    .unwrap();
+                let addr = socket.local_addr().unwrap();
+
+                let handle = std::thread::spawn(move || {
+                    let mut received = Vec::new();
+                    for answer in answers {
+                        let mut buf = [0; 512];
+                        let Ok((len, peer)) = socket.recv_from(&mut buf) else {
+                            break;
+                        };
+                        received.push(buf[..len].to_vec());
+
+                        if let Some(rcode) = answer {
+                            let mut resp = buf[..12].to_vec();
+                            resp[2] |= 0x80;
+                            resp[3] = rcode;
+                            socket.send_to(&resp, peer).unwrap();
+                        }
+                    }
+                    received
+                });
+
+                (addr, handle)
+            }
+
+            fn client(server: SocketAddr) -> Client {
+                Client {
+                    server,
+                    tsig: None,
+                    timeout: Duration::from_millis(100),
+                    retries: 2,
+                }
+            }
+
+            #[test]
+            fn nsupdate_message() {
+                let changes = [
+                    Change::Delete(
+                        "pc1.example.com".into(),
+                        Record::A("10.0.0.1".parse().unwrap()),
+                    ),
+                    Change::Add(
+                        "pc1.example.com".into(),
+                        3600,
+                        Record::A("10.0.0.2".parse().unwrap()),
+                    ),
+                ];
+                let msg = message(0x1234, "example.com", &changes, None, 0);
+
+                assert_eq!(
+                    &[0x12, 0x34, 0x28, 0x00, 0, 1, 0, 0, 0, 2, 0, 0],
+                    &msg[..12]
+                );
+                assert_eq!(b"\x07example\x03com\x00\x00\x06\x00\x01", &msg[12..29]);
+
+                let delete = b"\x03pc1\x07example\x03com\x00\x00\x01\x00\xfe\x00\x00\x00\x00\x00\x04\x0a\x00\x00\x01";
+                assert_eq!(delete, &msg[29..29 + delete.len()]);
+                assert_eq!(
+                    &[0x00, 0x00, 0x0e, 0x10, 0x00, 0x04, 10, 0, 0, 2],
+                    &msg[msg.len() - 10..]
+                );
+            }
+
+            #[test]
+            fn nsupdate_tsig() {
+                let tsig = Tsig::new("ipam-key", "c2VjcmV0").unwrap();
+                let changes = [Change::Add(
+                    "1.0.0.10.in-addr.arpa".into(),
+                    300,
+                    Record::Ptr("pc1.example.com".into()),
+                )];
+                let unsigned = message(7, "0.0.10.in-addr.arpa", &changes, None, 1700000000);
+                let signed = message(7, "0.0.10.in-addr.arpa", &changes, Some(&tsig), 1700000000);
+
+                assert_eq!(&[0, 1], &signed[10..12]);
+                assert_eq!(&unsigned[12..], &signed[12..unsigned.len()]);
+                assert_eq!(&[0, 7, 0, 0, 0, 0], &signed[signed.len() - 6..]);
+
+                let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+                mac.update(&unsigned);
+                mac.update(b"\x08ipam-key\x00\x00\xff\x00\x00\x00\x00");
+                mac.update(b"\x0bhmac-sha256\x00");
+                mac.update(&1700000000u64.to_be_bytes()[2..]);
+                mac.update(&[0x01, 0x2c, 0, 0, 0, 0]);
+                let mac = mac.finalize().into_bytes();
+                assert_eq!(&mac[..], &signed[signed.len() - 38..signed.len() - 6]);
+
+                assert_eq!(
+                    Err(UpdateError::InvalidKey),
+                    Tsig::new("ipam-key", "%%").map(|_| ())
+                );
+            }
+
+            #[test]
+            fn nsupdate_retry() {
+                let (server, handle) = mock_server(vec![None, Some(SERVFAIL), Some(0)]);
+                let changes = [Change::Add(
+                    "pc1.example.com".into(),
+                    60,
+                    Record::A("10.0.0.1".parse().unwrap()),
+                )];
+
+                let resp = Runtime::new()
+                    .unwrap()
+                    .block_on(client(server).update("example.com", &changes));
+
+                assert_eq!(Ok(()), resp);
+                assert_eq!(3, handle.join().unwrap().len());
+            }
+
+            #[test]
+            fn nsupdate_refused() {
+                let (server, handle) = mock_server(vec![Some(5), Some(0)]);
+                let changes = [Change::Delete(
+                    "pc1.example.com".into(),
+                    Record::A("10.0.0.1".parse().unwrap()),
+                )];
+
+                let resp = Runtime::new()
+                    .unwrap()
+                    .block_on(client(server).update("example.com", &changes));
+
+                assert_eq!(Err(UpdateError::Rcode(5)), resp);
+                assert_eq!(1, handle.join().unwrap().len());
+            }
+        }
+    }
+
+    pub mod dhcp {
+        use ipnet::IpNet;
+        use serde::{Deserialize, Serialize};
+        use std::net::IpAddr;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+        pub struct Range {
+            pub start: IpAddr,
+            pub end: IpAddr,
+        }
+
+        #[derive(Debug, PartialEq)]
+        pub struct InvalidRange(pub String);
+
+        impl std::fmt::Display for InvalidRange {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl std::error::Error for InvalidRange {}
+
+        impl Range {
+            pub fn new(start: IpAddr, end: IpAddr, network: &IpNet) -> Result<Self, InvalidRange> {
+                if start > end {
+                    return Err(InvalidRange(format!("{} is after {}", start, end)));
+                }
+
+                if !network.contains(&start) || !network.contains(&end) {
+                    return Err(InvalidRange(format!(
+                        "{}-{} is outside of {}",
+                        start, end, network
+                    )));
+                }
+
+                // The network and broadcast addresses can't be leased
+                match network {
+                    IpNet::V4(e)
+                        if e.prefix_len() < 31
+                            && (start == IpAddr::V4(e.network())
+                                || end == IpAddr::V4(e.broadcast())) =>
+                    {
+                        Err(InvalidRange(format!(
+                            "{}-{} includes the network or broadcast address",
+                            start, end
+                        )))
+                    }
+                    _ => Ok(Self { start, end }),
+                }
+            }
+
+            pub fn contains(&self, ip: &IpAddr) -> bool {
+                self.start <= *ip && *ip <= self.end
+            }
+
+            pub fn overlaps(&self, other: &Self) -> bool {
+                self.start <= other.end && other.start <= self.end
+            }
+        }
+
+        impl std::fmt::Display for Range {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}-{}", self.start, self.end)
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+
+            #[test]
+            fn dhcp_range_new() {
+                let network: IpNet = "192.168.0.0/24".parse().unwrap();
+                let ip = |x: &str| x.parse::<IpAddr>().unwrap();
+
+                assert!(Range::new(ip("192.168.0.100"), ip("192.168.0.200"), &network).is_ok());
+                assert!(Range::new(ip("192.168.0.200"), ip("192.168.0.100"), &network).is_err());
+                assert!(Range::new(ip("192.168.0.100"), ip("192.168.1.10"), &network).is_err());
+                assert!(Range::new(ip("192.168.0.0"), ip("192.168.0.10"), &network).is_err());
+                assert!(Range::new(ip("192.168.0.10"), ip("192.168.0.255"), &network).is_err());
+                assert!(Range::new(ip("2001:db8::100"), ip("2001:db8::200"), &network).is_err());
+
+                let network: IpNet = "2001:db8::/64".parse().unwrap();
+                assert!(Range::new(ip("2001:db8::"), ip("2001:db8::ffff"), &network).is_ok());
+            }
+
+            #[test]
+            fn dhcp_range_overlaps() {
+                let network: IpNet = "10.0.0.0/24".parse().unwrap();
+                let range = |x: &str, y: &str| {
+                    Range::new(x.parse().unwrap(), y.parse().unwrap(), &network).unwrap()
+                };
+                let pool = range("10.0.0.100", "10.0.0.200");
+
+                assert!(pool.contains(&"10.0.0.100".parse().unwrap()));
+                assert!(pool.contains(&"10.0.0.200".parse().unwrap()));
+                assert!(!pool.contains(&"10.0.0.201".parse().unwrap()));
+                assert!(!pool.contains(&"::1".parse().unwrap()));
+
+                assert!(pool.overlaps(&range("10.0.0.200", "10.0.0.210")));
+                assert!(pool.overlaps(&range("10.0.0.120", "10.0.0.130")));
+                assert!(!pool.overlaps(&range("10.0.0.1", "10.0.0.99")));
+            }
+        }
+    }
+
+    pub mod kea {
+        use super::dhcp::Range;
+        use crate::type_net::mac::MacAddr;
+        use ipnet::IpNet;
+        use serde_json::{json, Value};
+        use std::net::IpAddr;
+
+        #[derive(Debug, Clone)]
+        pub struct Pool {
+            pub range: Range,
+            pub gateway: Option<IpAddr>,
+            pub dns_servers: Vec<IpAddr>,
+            pub lease_time: u32,
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct Host {
+            pub mac: MacAddr,
+            pub ip: IpAddr,
+            pub hostname: Option<String>,
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct Subnet {
+            pub id: u32,
+            pub network: IpNet,
+            pub pools: Vec<Pool>,
+            pub hosts: Vec<Host>,
+        }
+
+        fn options(v4: bool, pool: &Pool) -> Vec<Value> {
+            let mut resp = Vec::new();
+            let servers: Vec<String> = pool.dns_servers.iter().map(IpAddr::to_string).collect();
+
+            if let (true, Some(gateway)) = (v4, pool.gateway) {
+                resp.push(json!({ "name": "routers", "data": gateway }));
+            }
+            if !servers.is_empty() {
+                resp.push(json!({
+                    "name": if v4 { "domain-name-servers" } else { "dns-servers" },
+                    "data": servers.join(", "),
+                }));
+            }
+
+            resp
+        }
+
+        impl Subnet {
+            // The first pool gives the options of the subnet, the others only
+            // carry theirs when they differ
+            fn render(&self) -> Value {
+                let v4 = matches!(self.network, IpNet::V4(_));
+                let default = self
+                    .pools
+                    .first()
+                    .map(|x| options(v4, x))
+                    .unwrap_or_default();
+
+                let pools: Vec<Value> = self
+                    .pools
+                    .iter()
+                    .map(|x| {
+                        let mut pool =
+                            json!({ "pool": format!("{} - {}", x.range.start, x.range.end) });
+                        let options = options(v4, x);
+                        if options != default {
+                            pool["option-data"] = json!(options);
+                        }
+                        pool
+                    })
+                    .collect();
+
+                let reservations: Vec<Value> = self
+                    .hosts
+                    .iter()
+                    .map(|x| {
+                        let mut host = if v4 {
+                            json!({ "hw-address": x.mac, "ip-address": x.ip })
+                        } else {
+                            json!({ "hw-address": x.mac, "ip-addresses": [x.ip] })
+                        };
+                        if let Some(hostname) = &x.hostname {
+                            host["hostname"] = json!(hostname);
+                        }
+                        host
+                    })
+                    .collect();
+
+                let mut resp = json!({
+                    "id": self.id,
+                    "subnet": self.network.to_string(),
+                    "pools": pools,
+                    "option-data": default,
+                    "reservations": reservations,
+                    "reservations-out-of-pool": true,
+                });
+                if let Some(pool) = self.pools.first() {
+                    resp["valid-lifetime"] = json!(pool.lease_time);
+                }
+
+                resp
+            }
+        }
+
+        pub fn dhcp4(subnets: &[Subnet]) -> Value {
+            json!({
+                "subnet4": subnets
+                    .iter()
+                    .filter(|x| matches!(x.network, IpNet::V4(_)))
+                    .map(Subnet::render)
+                    .collect::<Vec<Value>>()
+            })
+        }
+
+        pub fn dhcp6(subnets: &[Subnet]) -> Value {
+            json!({
+                "subnet6": subnets
+                    .iter()
+                    .filter(|x| matches!(x.network, IpNet::V6(_)))
+                    .map(Subnet::render)
+                    .collect::<Vec<Value>>()
+            })
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+
+            fn pool(start: &str, end: &str, gateway: Option<&str>) -> Pool {
+                Pool {
+                    range: Range {
+                        start: start.parse().unwrap(),
+                        end: end.parse().unwrap(),
+                    },
+                    gateway: gateway.map(|x| x.parse().unwrap()),
+                    dns_servers: vec!["10.0.0.2".parse().unwrap(), "10.0.0.3".parse().unwrap()],
+                    lease_time: 3600,
+                }
+            }
+
+            #[test]
+            fn kea_dhcp4() {
+                let subnets = [Subnet {
+                    id: 7,
+                    network: "10.0.0.0/24".parse().unwrap(),
+                    pools: vec![
+                        pool("10.0.0.100", "10.0.0.150", Some("10.0.0.1")),
+                        pool("10.0.0.200", "10.0.0.250", Some("10.0.0.254")),
+                    ],
+                    hosts: vec![Host {
+                        mac: "00:1b:44:11:3a:b7".parse().unwrap(),
+                        ip: "10.0.0.10".parse().unwrap(),
+                        hostname: Some("printer.example.com".to_string()),
+                    }],
+                }];
+
+                assert_eq!(
+                    json!({
+                        "subnet4": [{
+                            "id": 7,
+                            "subnet": "10.0.0.0/24",
+                            "pools": [
+                                { "pool": "10.0.0.100 - 10.0.0.150" },
+                                {
+                                    "pool": "10.0.0.200 - 10.0.0.250",
+                                    "option-data": [
+                                        { "name": "routers", "data": "10.0.0.254" },
+                                        { "name": "domain-name-servers", "data": "10.0.0.2, 10.0.0.3" },
+                                    ]
+                                },
+                            ],
+                            "option-data": [
+                                { "name": "routers", "data": "10.0.0.1" },
+                                { "name": "domain-name-servers", "data": "10.0.0.2, 10.0.0.3" },
+                            ],
+                            "reservations": [{
+                                "hw-address": "00:1b:44:11:3a:b7",
+                                "ip-address": "10.0.0.10",
+                                "hostname": "printer.example.com",
+                            }],
+                            "reservations-out-of-pool": true,
+                            "valid-lifetime": 3600,
+                        }]
+                    }),
+                    dhcp4(&subnets)
+                );
+                assert_eq!(json!({ "subnet6": [] }), dhcp6(&subnets));
+            }
+
+            #[test]
+            fn kea_dhcp6() {
+                let subnets = [Subnet {
+                    id: 8,
+                    network: "2001:db8::/64".parse().unwrap(),
+                    pools: vec![pool("2001:db8::100", "2001:db8::1ff", Some("2001:db8::1"))],
+                    hosts: vec![Host {
+                        mac: "00:1b:44:11:3a:b7".parse().unwrap(),
+                        ip: "2001:db8::10".parse().unwrap(),
+                        hostname: None,
+                    }],
+                }];
+                let resp = dhcp6(&subnets);
+                let subnet = &resp["subnet6"][0];
+
+                assert_eq!(json!("2001:db8::/64"), subnet["subnet"]);
+                assert_eq!(
+                    json!([{ "name": "dns-servers", "data": "10.0.0.2, 10.0.0.3" }]),
+                    subnet["option-data"]
+                );
+                assert_eq!(
+                    json!([{ "hw-address": "00:1b:44:11:3a:b7", "ip-addresses": ["2001:db8::10"] }]),
+                    subnet["reservations"]
+                );
+            }
+        }
+    }
+
+    pub mod leases {
+        use crate::type_net::mac::MacAddr;
+        use std::{collections::HashSet, net::IpAddr};
+        use time::{Date, Month, OffsetDateTime, Time};
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Lease {
+            pub ip: IpAddr,
+            pub mac: Option<MacAddr>,
+            pub hostname: Option<String>,
+            pub seen: Option<OffsetDateTime>,
+            pub ends: Option<OffsetDateTime>,
+            pub active: bool,
+        }
+
+        impl Lease {
+            fn new(ip: IpAddr) -> Self {
+                Self {
+                    ip,
+                    mac: None,
+                    hostname: None,
+                    seen: None,
+                    ends: None,
+                    active: false,
+                }
+            }
+
+            pub fn is_current(&self, now: OffsetDateTime) -> bool {
+                self.active && self.ends.is_none_or(|x| x > now)
+            }
+        }
+
+        // Kea's memfile starts with its header, anything else is read as dhcpd.leases
+        pub fn parse(text: &str) -> Vec<Lease> {
+            if text.trim_start().starts_with("address,") {
+                kea(text)
+            } else {
+                dhcpd(text)
+            }
+        }
+
+        // Both files are append only, the last entry of an address wins
+        fn latest(leases: Vec<Lease>) -> Vec<Lease> {
+            let mut seen = HashSet::new();
+            let mut resp: Vec<Lease> = leases
+                .into_iter()
+                .rev()
+                .filter(|x| seen.insert(x.ip))
+                .collect();
+            resp.reverse();
+            resp
+        }
+
+        // "4 2024/01/11 22:00:00" (UTC) or "epoch 1705010400"
+        fn dhcpd_time(value: &str) -> Option<OffsetDateTime> {
+            let mut fields = value.split_whitespace();
+            match fields.next()? {
+                "epoch" => OffsetDateTime::from_unix_timestamp(fields.next()?.parse().ok()?).ok(),
+                _ => {
+                    let date: Vec<u32> = fields
+                        .next()?
+                        .split('/')
+                        .filter_map(|x| x.parse().ok())
+                        .collect();
+                    let time: Vec<u8> = fields
+                        .next()?
+                        .split(':')
+                        .filter_map(|x| x.parse().ok())
+                        .collect();
+                    let (&[year, month, day], &[hour, minute, second]) =
+                        (date.as_slice(), time.as_slice())
+                    else {
+                        return None;
+                    };
+
+                    let date = Date::from_calendar_date(
+                        year as i32,
+                        Month::try_from(month as u8).ok()?,
+                        day as u8,
+                    )
+                    .ok()?;
+                    Some(
+                        date.with_time(Time::from_hms(hour, minute, second).ok()?)
+                            .assume_utc(),
+                    )
+                }
+            }
+        }
+
+        pub fn dhcpd(text: &str) -> Vec<Lease> {
+            let mut resp = Vec::new();
+            let mut current: Option<Lease> = None;
+
+            for line in text.lines() {
+                let line = line.split('#').next().unwrap_or_default().trim();
+
+                if let Some(ip) = line
+                    .strip_prefix("lease ")
+                    .and_then(|x| x.strip_suffix('{'))
+                {
+                    current = ip.trim().parse().ok().map(Lease::new);
+                    continue;
+                }
+
+                let Some(lease) = current.as_mut() else {
+                    continue;
+                };
+                if line == "}" {
+                    resp.extend(current.take());
+                    continue;
+                }
+
+                let line = line.trim_end_matches(';');
+                if let Some(e) = line.strip_prefix("starts ") {
+                    lease.seen = lease.seen.or(dhcpd_time(e));
+                } else if let Some(e) = line.strip_prefix("cltt ") {
+                    lease.seen = dhcpd_time(e).or(lease.seen);
+                } else if let Some(e) = line.strip_prefix("ends ") {
+                    lease.ends = dhcpd_time(e);
+                } else if let Some(e) = line.strip_prefix("binding state ") {
+                    lease.active = e == "active";
+                } else if let Some(e) = line.strip_prefix("hardware ethernet ") {
+                    lease.mac = e.parse().ok();
+                } else if let Some(e) = line.strip_prefix("client-hostname ") {
+                    lease.hostname =
+                        Some(e.trim_matches('"').to_string()).filter(|x| !x.is_empty());
+                }
+            }
+
+            latest(resp)
+        }
+
+        pub fn kea(text: &str) -> Vec<Lease> {
+            let mut lines = text.lines();
+            let Some(header) = lines.next() else {
+                return Vec::new();
+            };
+            let header: Vec<&str> = header.trim().split(',').collect();
+            let column = |name: &str| header.iter().position(|x| *x == name);
+            let (
+                Some(address),
+                Some(hwaddr),
+                Some(expire),
+                Some(lifetime),
+                Some(hostname),
+                Some(state),
+            ) = (
+                column("address"),
+                column("hwaddr"),
+                column("expire"),
+                column("valid_lifetime"),
+                column("hostname"),
+                column("state"),
+            )
+            else {
+                return Vec::new();
+            };
+            let prefix_len = column("prefix_len");
+
+            let mut resp = Vec::new();
+            for line in lines {
+                let fields: Vec<&str> = line.trim().split(',').collect();
+                if fields.len() < header.len() {
+                    continue;
+                }
+                // Delegated prefixes aren't addresses
+                if prefix_len.is_some_and(|x| !matches!(fields[x], "" | "0" | "128")) {
+                    continue;
+                }
+                let Ok(ip) = fields[address].parse() else {
+                    continue;
+                };
+
+                let expire: Option<i64> = fields[expire].parse().ok();
+                let lifetime: i64 = fields[lifetime].parse().unwrap_or_default();
+                let mut lease = Lease::new(ip);
+                lease.mac = fields[hwaddr].parse().ok();
+                lease.hostname = Some(fields[hostname].trim_end_matches('.').to_string())
+                    .filter(|x| !x.is_empty());
+                lease.ends = expire.and_then(|x| OffsetDateTime::from_unix_timestamp(x).ok());
+                lease.seen =
+                    expire.and_then(|x| OffsetDateTime::from_unix_timestamp(x - lifetime).ok());
+                lease.active = fields[state] == "0";
+                resp.push(lease);
+            }
+
+            latest(resp)
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+
+            #[test]
+            fn leases_dhcpd() {
+                let leases = parse(
+                    "# The format of this file is documented in the dhcpd.leases(5) manual page.\n\
+                     lease 192.168.1.10 {\n\
+                     \x20 starts 4 2024/01/11 10:00:00;\n\
+                     \x20 ends 4 2024/01/11 22:00:00;\n\
+                     \x20 binding state free;\n\
+                     \x20 hardware ethernet 00:1b:44:11:3a:b7;\n\
+                     }\n\
+                     lease 192.168.1.11 {\n\
+                     \x20 starts epoch 1704967200; # Thu Jan 11 10:00:00 2024\n\
+                     \x20 ends never;\n\
+                     \x20 binding state active;\n\
+                     \x20 next binding state free;\n\
+                     \x20 hardware ethernet 3c:5a:b4:00:00:01;\n\
+                     \x20 client-hostname \"printer\";\n\
+                     }\n\
+                     lease 192.168.1.10 {\n\
+                     \x20 starts 4 2024/01/11 12:00:00;\n\
+                     \x20 cltt 4 2024/01/11 12:30:00;\n\
+                     \x20 ends 4 2024/01/12 00:00:00;\n\
+                     \x20 binding state active;\n\
+                     \x20 hardware ethernet 00:1b:44:11:3a:b7;\n\
+                     }\n",
+                );
+                let at = |x: i64| OffsetDateTime::from_unix_timestamp(x).unwrap();
+
+                assert_eq!(2, leases.len());
+                assert_eq!("192.168.1.11".parse::<IpAddr>().unwrap(), leases[0].ip);
+                assert_eq!(Some("printer".to_string()), leases[0].hostname);
+                assert_eq!(Some(at(1704967200)), leases[0].seen);
+                assert_eq!(None, leases[0].ends);
+                assert!(leases[0].is_current(at(1800000000)));
+
+                assert_eq!("192.168.1.10".parse::<IpAddr>().unwrap(), leases[1].ip);
+                assert_eq!("00:1b:44:11:3a:b7".parse().ok(), leases[1].mac);
+                assert_eq!(Some(at(1704976200)), leases[1].seen);
+                assert!(leases[1].is_current(at(1704976200)));
+                assert!(!leases[1].is_current(at(1705017600)));
+            }
+
+            #[test]
+            fn leases_kea() {
+                let leases = parse(
+                    "address,hwaddr,client_id,valid_lifetime,expire,subnet_id,fqdn_fwd,fqdn_rev,hostname,state,user_context,pool_id\n\
+                     10.0.0.100,00:1b:44:11:3a:b7,,3600,1704970800,1,0,0,printer.example.com.,0,,0\n\
+                     10.0.0.101,3c:5a:b4:00:00:01,,3600,1704970800,1,0,0,,2,,0\n",
+                );
+
+                assert_eq!(2, leases.len());
+                assert_eq!(Some("printer.example.com".to_string()), leases[0].hostname);
+                assert_eq!(
+                    Some(OffsetDateTime::from_unix_timestamp(1704967200).unwrap()),
+                    leases[0].seen
+                );
+                assert!(leases[0].active);
+                assert!(!leases[1].active);
+                assert_eq!(None, leases[1].hostname);
+            }
+        }
+    }
+
+    pub mod neighbors {
+        use crate::type_net::mac::MacAddr;
+        use std::{collections::HashSet, net::IpAddr};
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct Neighbor {
+            pub ip: IpAddr,
+            pub mac: MacAddr,
+        }
+
+        // A CSV starts with its header, anything else is read as `ip neigh` output
+        pub fn parse(text: &str) -> Vec<Neighbor> {
+            let header = text.lines().next().unwrap_or_default().to_lowercase();
+            if header.contains(',') && header.contains("mac") {
+                csv(text)
+            } else {
+                ip_neigh(text)
+            }
+        }
+
+        fn unique(neighbors: Vec<Neighbor>) -> Vec<Neighbor> {
+            let mut seen = HashSet::new();
+            neighbors.into_iter().filter(|x| seen.insert(*x)).collect()
+        }
+
+        // "10.0.0.1 dev eth0 lladdr 00:1b:44:11:3a:b7 REACHABLE", entries
+        // without a link layer address never resolved
+        pub fn ip_neigh(text: &str) -> Vec<Neighbor> {
+            let mut resp = Vec::new();
+
+            for line in text.lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let Some(Ok(ip)) = fields.first().map(|x| x.parse()) else {
+                    continue;
+                };
+                if fields.iter().any(|x| matches!(*x, "FAILED" | "INCOMPLETE")) {
+                    continue;
+                }
+                let mac = fields
+                    .iter()
+                    .position(|x| *x == "lladdr")
+                    .and_then(|x| fields.get(x + 1))
+                    .and_then(|x| x.parse().ok());
+
+                if let Some(mac) = mac {
+                    resp.push(Neighbor { ip, mac });
+                }
+            }
+
+            unique(resp)
+        }
+
+        pub fn csv(text: &str) -> Vec<Neighbor> {
+            let mut lines = text.lines();
+            let Some(header) = lines.next() else {
+                return Vec::new();
+            };
+            let header: Vec<String> = header.split(',').map(|x| x.trim().to_lowercase()).collect();
+            let column = |names: &[&str]| header.iter().position(|x| names.contains(&x.as_str()));
+            let (Some(ip), Some(mac)) = (
+                column(&["ip", "address"]),
+                column(&["mac", "hwaddr", "lladdr"]),
+            ) else {
+                return Vec::new();
+            };
+
+            let mut resp = Vec::new();
+            for line in lines {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let (Some(Ok(ip)), Some(Ok(mac))) = (
+                    fields.get(ip).map(|x| x.parse()),
+                    fields.get(mac).map(|x| x.parse()),
+                ) else {
+                    continue;
+                };
+                resp.push(Neighbor { ip, mac });
+            }
+
+            unique(resp)
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+
+            #[test]
+            fn neighbors_ip_neigh() {
+                let neighbors = parse(
+                    "10.0.0.1 dev eth0 lladdr 00:1b:44:11:3a:b7 REACHABLE\n\
+                     10.0.0.7 dev eth0 FAILED\n\
+                     10.0.0.8 dev eth0 lladdr 3c:5a:b4:00:00:01 STALE\n\
+                     10.0.0.8 dev eth1 lladdr 3c:5a:b4:00:00:02 STALE\n\
+                     fe80::1 dev eth0 lladdr 00:1b:44:11:3a:b7 router REACHABLE\n\
+                     10.0.0.1 dev eth0 lladdr 00:1b:44:11:3a:b7 DELAY\n",
+                );
+
+                assert_eq!(4, neighbors.len());
+                assert_eq!("10.0.0.1".parse::<IpAddr>().unwrap(), neighbors[0].ip);
+                assert_eq!(
+                    "00:1b:44:11:3a:b7".parse::<MacAddr>().unwrap(),
+                    neighbors[0].mac
+                );
+                assert_eq!(neighbors[1].ip, neighbors[2].ip);
+                assert_eq!("fe80::1".parse::<IpAddr>().unwrap(), neighbors[3].ip);
+            }
+
+            #[test]
+            fn neighbors_csv() {
+                let neighbors = parse(
+                    "interface,IP,MAC\n\
+                     eth0,10.0.0.1,00:1b:44:11:3a:b7\n\
+                     eth0,10.0.0.2,\n\
+                     eth0,2001:db8::2,3c:5a:b4:00:00:01\n",
+                );
+
+                assert_eq!(
+                    vec![
+                        Neighbor {
+                            ip: "10.0.0.1".parse().unwrap(),
+                            mac: "00:1b:44:11:3a:b7".parse().unwrap()
+                        },
+                        Neighbor {
+                            ip: "2001:db8::2".parse().unwrap(),
+                            mac: "3c:5a:b4:00:00:01".parse().unwrap()
+                        },
+                    ],
+                    neighbors
+                );
+            }
+        }
+    }
+
+    pub mod csv {
+        use std::collections::HashMap;
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct Row {
+            pub line: usize,
+            pub fields: HashMap<String, String>,
+        }
+
+        impl Row {
+            pub fn get(&self, name: &str) -> Option<&str> {
+                self.fields.get(name).map(String::as_str)
+            }
+        }
+
+        // "IP Address" and "ip-address" both become "ip_address"
+        pub fn column(name: &str) -> String {
+            name.to_lowercase()
+                .split(|x: char| x.is_whitespace() || x == '-')
+                .filter(|x| !x.is_empty())
+                .collect::<Vec<_>>()
+                .join("_")
+        }
+
+        // "IP Address=ip,Host=hostname" renames spreadsheet columns to fields
+        pub fn mapping(spec: &str) -> HashMap<String, String> {
+            spec.split(',')
+                .filter_map(|x| x.split_once('='))
+                .map(|(from, to)| (column(from), column(to)))
+                .collect()
+        }
+
+        // Fields may be quoted to hold commas, a doubled quote is a literal
+        // one. Quoted line breaks aren't supported
+        pub fn record(line: &str) -> Vec<String> {
+            let mut fields = Vec::new();
+            let mut field = String::new();
+            let mut quoted = false;
+            let mut chars = line.chars().peekable();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if quoted && chars.peek() == Some(&'"') => {
+                        field.push('"');
+                        chars.next();
+                    }
+                    '"' => quoted = !quoted,
+                    ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                    c => field.push(c),
+                }
+            }
+            fields.push(field);
+
+            fields.into_iter().map(|x| x.trim().to_string()).collect()
+        }
+
+        // Lines are numbered like the spreadsheet, header included. Blank
+        // lines and empty cells are skipped
+        pub fn parse(text: &str, mapping: &HashMap<String, String>) -> Vec<Row> {
+            let mut lines = text
+                .lines()
+                .enumerate()
+                .filter(|(_, x)| !x.trim().is_empty());
+            let Some((_, header)) = lines.next() else {
+                return Vec::new();
+            };
+            let header: Vec<String> = record(header.trim_start_matches('\u{feff}'))
+                .iter()
+                .map(|x| {
+                    let x = column(x);
+                    mapping.get(&x).cloned().unwrap_or(x)
+                })
+                .collect();
+
+            lines
+                .map(|(line, text)| Row {
+                    line: line + 1,
+                    fields: header
+                        .iter()
+                        .zip(record(text))
+                        .filter(|(_, value)| !value.is_empty())
+                        .map(|(name, value)| (name.clone(), value))
+                        .collect(),
+                })
+                .collect()
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+
+            #[test]
+            fn csv_record() {
+                assert_eq!(
+                    vec!["10.0.0.1", "core, rack 2", "say \"hi\"", ""],
+                    record("10.0.0.1, \"core, rack 2\",\"say \"\"hi\"\"\",")
+                );
+            }
+
+            #[test]
+            fn csv_parse() {
+                let rows = parse(
+                    "\u{feff}IP Address,Host,Serial Number\n\
+                     10.0.0.1,sw-01,\n\
+                     \n\
+                     10.0.0.2,sw-02,ABC123\n",
+                    &mapping("ip address=ip, HOST=hostname"),
+                );
+
+                assert_eq!(2, rows.len());
+                assert_eq!(2, rows[0].line);
+                assert_eq!(Some("10.0.0.1"), rows[0].get("ip"));
+                assert_eq!(Some("sw-01"), rows[0].get("hostname"));
+                assert_eq!(None, rows[0].get("serial_number"));
+                assert_eq!(4, rows[1].line);
+                assert_eq!(Some("ABC123"), rows[1].get("serial_number"));
+            }
+        }
+    }
+
+    pub mod webhook {
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        pub const SIGNATURE: &str = "X-IPAM-Signature";
+        pub const DELIVERY: &str = "X-IPAM-Delivery";
+        pub const EVENT: &str = "X-IPAM-Event";
+
+        fn mac(secret: &[u8], body: &[u8]) -> Hmac<Sha256> {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+            mac.update(body);
+            mac
+        }
+
+        // "sha256=<hex>" over the raw body
+        pub fn sign(secret: &[u8], body: &[u8]) -> String {
+            let digest = mac(secret, body).finalize().into_bytes();
+            let hex: String = digest.iter().map(|x| format!("{:02x}", x)).collect();
+            format!("sha256={}", hex)
+        }
+
+        pub fn verify(secret: &[u8], body: &[u8], signature: &str) -> bool {
+            let Some(hex) = signature.strip_prefix("sha256=") else {
+                return false;
+            };
+            let digest: Option<Vec<u8>> = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                .collect();
+
+            digest.is_some_and(|x| mac(secret, body).verify_slice(&x).is_ok())
+        }
+
+        // The status of the receiver, whatever it is; errors are transport failures
+        pub async fn send(
+            client: &reqwest::Client,
+            url: &str,
+            secret: &str,
+            headers: &[(&str, String)],
+            body: Vec<u8>,
+        ) -> Result<u16, String> {
+            let mut request = client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE, sign(secret.as_bytes(), &body));
+            for (name, value) in headers {
+                request = request.header(*name, value);
+            }
+
+            match request.body(body).send().await {
+                Ok(e) => Ok(e.status().as_u16()),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+            use tokio::{
+                io::{AsyncReadExt, AsyncWriteExt},
+                net::TcpListener,
+                runtime::Runtime,
+            };
+
+            // Answers one request with `status` and hands back its headers and body
+            async fn receiver(listener: TcpListener, status: &str) -> (String, Vec<u8>) {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+
+                let (head, body) = loop {
+                    let len = stream.read(&mut chunk).await.unwrap();
+                    buf.extend(&chunk[..len]);
+                    let Some(end) = buf.windows(4).position(|x| x == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&buf[..end]).to_lowercase();
+                    let length: usize = head
+                        .lines()
+                        .find_map(|x| x.strip_prefix("content-length:"))
+                        .and_then(|x| x.trim().parse().ok())
+                        .unwrap_or_default();
+                    if buf.len() >= end + 4 + length || len == 0 {
+                        break (head, buf[end + 4..].to_vec());
+                    }
+                };
+
+                let resp = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(resp.as_bytes()).await.unwrap();
+                (head, body)
+            }
+
+            #[test]
+            fn webhook_signature() {
+                let signature = sign(b"key", b"The quick brown fox jumps over the lazy dog");
+                assert_eq!(
+                    "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+                    signature
+                );
+                assert!(verify(
+                    b"key",
+                    b"The quick brown fox jumps over the lazy dog",
+                    &signature
+                ));
+                assert!(!verify(
+                    b"other",
+                    b"The quick brown fox jumps over the lazy dog",
+                    &signature
+                ));
+                assert!(!verify(b"key", b"", "sha256=zz"));
+            }
+
+            #[test]
+            fn webhook_send() {
+                let runtime = Runtime::new().unwrap();
+                let client = reqwest::Client::new();
+                let body = br#"{"entity":"Address","action":"Insert"}"#.to_vec();
+
+                let (status, (head, received)) = runtime.block_on(async {
+                    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+                    let receiver = tokio::spawn(receiver(listener, "204 No Content"));
+                    let status = send(
+                        &client,
+                        &url,
+                        "s3cret",
+                        &[(EVENT, "Address.Insert".to_string())],
+                        body.clone(),
+                    )
+                    .await;
+                    (status, receiver.await.unwrap())
+                });
+
+                assert_eq!(Ok(204), status);
+                assert_eq!(body, received);
+                assert!(head.starts_with("post /hook http/1.1"));
+                assert!(head.contains("x-ipam-event: address.insert"));
+                let signature = head
+                    .lines()
+                    .find_map(|x| x.strip_prefix("x-ipam-signature: "))
+                    .unwrap();
+                assert!(verify(b"s3cret", &body, signature));
+
+                let status = runtime.block_on(async {
+                    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                    let url = format!("http://{}/", listener.local_addr().unwrap());
+                    drop(listener);
+                    send(&client, &url, "s3cret", &[], body.clone()).await
+                });
+                assert!(status.is_err());
+            }
+        }
+    }
+
+    pub mod designation {
+        use ipnet::IpNet;
+        use serde::{Deserialize, Serialize};
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+        #[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+        pub enum GatewayPolicy {
+            #[default]
+            First,
+            Last,
+            None,
+        }
+
+        // ipv4 networks bigger than a /31 lose their network and broadcast addresses
+        fn has_broadcast(network: &IpNet) -> bool {
+            matches!(network, IpNet::V4(e) if e.prefix_len() < 31)
+        }
+
+        pub fn broadcast(network: &IpNet) -> Option<IpAddr> {
+            has_broadcast(network).then(|| network.broadcast())
+        }
+
+        pub fn is_unusable(network: &IpNet, ip: &IpAddr) -> bool {
+            has_broadcast(network) && (*ip == network.network() || *ip == network.broadcast())
+        }
+
+        // The subnet-router anycast address (the first one) is skipped in ipv6
+        pub fn gateway(network: &IpNet, policy: GatewayPolicy) -> Option<IpAddr> {
+            let single = match network {
+                IpNet::V4(e) => e.prefix_len() == 32,
+                IpNet::V6(e) => e.prefix_len() == 128,
+            };
+
+            match (policy, network) {
+                (GatewayPolicy::None, _) => None,
+                (_, _) if single => Some(network.network()),
+                (GatewayPolicy::First, IpNet::V4(e)) if e.prefix_len() == 31 => {
+                    Some(IpAddr::V4(e.network()))
+                }
+                (GatewayPolicy::First, IpNet::V4(e)) => {
+                    Some(IpAddr::V4(Ipv4Addr::from(u32::from(e.network()) + 1)))
+                }
+                (GatewayPolicy::First, IpNet::V6(e)) => {
+                    Some(IpAddr::V6(Ipv6Addr::from(u128::from(e.network()) + 1)))
+                }
+                (GatewayPolicy::Last, IpNet::V4(e)) if e.prefix_len() == 31 => {
+                    Some(IpAddr::V4(e.broadcast()))
+                }
+                (GatewayPolicy::Last, IpNet::V4(e)) => {
+                    Some(IpAddr::V4(Ipv4Addr::from(u32::from(e.broadcast()) - 1)))
+                }
+                (GatewayPolicy::Last, IpNet::V6(e)) => Some(IpAddr::V6(e.broadcast())),
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use super::*;
+
+            fn ip(x: &str) -> Option<IpAddr> {
+                Some(x.parse().unwrap())
+            }
+
+            #[test]
+            fn designation_gateway() {
+                let network: IpNet = "192.168.0.0/24".parse().unwrap();
+                assert_eq!(ip("192.168.0.1"), gateway(&network, GatewayPolicy::First));
+                assert_eq!(ip("192.168.0.254"), gateway(&network, GatewayPolicy::Last));
+                assert_eq!(None, gateway(&network, GatewayPolicy::None));
+
+                let network: IpNet = "10.0.0.0/31".parse().unwrap();
+                assert_eq!(ip("10.0.0.0"), gateway(&network, GatewayPolicy::First));
+                assert_eq!(ip("10.0.0.1"), gateway(&network, GatewayPolicy::Last));
+
+                let network: IpNet = "10.0.0.7/32".parse().unwrap();
+                assert_eq!(ip("10.0.0.7"), gateway(&network, GatewayPolicy::Last));
+
+                let network: IpNet = "2001:db8::/64".parse().unwrap();
+                assert_eq!(ip("2001:db8::1"), gateway(&network, GatewayPolicy::First));
+                assert_eq!(
+                    ip("2001:db8::ffff:ffff:ffff:ffff"),
+                    gateway(&network, GatewayPolicy::Last)
+                );
+            }
+
+            #[test]
+            fn designation_broadcast() {
+                let network: IpNet = "192.168.0.0/24".parse().unwrap();
+                assert_eq!(ip("192.168.0.255"), broadcast(&network));
+                assert!(is_unusable(&network, &"192.168.0.0".parse().unwrap()));
+                assert!(is_unusable(&network, &"192.168.0.255".parse().unwrap()));
+                assert!(!is_unusable(&network, &"192.168.0.1".parse().unwrap()));
+
+                assert_eq!(None, broadcast(&"10.0.0.0/31".parse().unwrap()));
+                assert_eq!(None, broadcast(&"2001:db8::/64".parse().unwrap()));
+            }
+        }
+    }
+
     #[cfg(test)]
     mod test {
         use super::*;
@@ -867,6 +4869,7 @@ pub mod ipam_services {
         static RUNTIME: LazyLock<Runtime> = std::sync::LazyLock::new(|| Runtime::new().unwrap());
 
         #[test]
+        #[allow(clippy::vec_init_then_push)]
         fn sub_net_first_prefix_fifty_six() {
             let ip = "192.168.0.1/24".parse::<IpNet>().unwrap();
             let subnet = subnetting(ip, 26).unwrap();
@@ -883,6 +4886,7 @@ pub mod ipam_services {
         }
 
         #[test]
+        #[allow(clippy::vec_init_then_push)]
         fn sub_net_first_prefix_fifty_eight() {
             let ip = "192.168.0.1/24".parse::<IpNet>().unwrap();
             let subnet = subnetting(ip, 28).unwrap();
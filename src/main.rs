@@ -6,7 +6,7 @@ mod services;
 use axum::{
     http::Response,
     routing::{delete, get, post, put},
-    serve, Router,
+    serve, Extension, Router,
 };
 use database::RepositoryInjection;
 use dotenv::dotenv;
@@ -42,12 +42,84 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let db = Arc::new(Mutex::new(db));
 
-    let network = Router::new().route("/create", put(network::create)).route(
-        "/",
-        get(network::get)
-            .delete(network::delete)
-            .patch(network::update),
-    );
+    let oui_database =
+        env::var("OUI_DATABASE").unwrap_or("/usr/share/ieee-data/oui.txt".to_string());
+    let oui = Arc::new(services::oui::Oui::load(oui_database.into()).await);
+    let ddns = services::ddns::provider()?;
+    let transitions = services::status::transitions()?;
+    let probe = services::probe::settings()?;
+    let snmp = services::snmp::settings()?;
+    let dispatcher = services::webhooks::dispatcher()?;
+
+    let retention = env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(30);
+    let purge_interval = services::interval("TRASH_PURGE_INTERVAL", 3600)?;
+    tokio::spawn(services::trash::purge(
+        db.clone(),
+        time::Duration::days(retention),
+        purge_interval,
+    ));
+
+    let expiry_interval = services::interval("RESERVATION_EXPIRY_INTERVAL", 60)?;
+    tokio::spawn(services::status::expire(
+        db.clone(),
+        ddns.clone(),
+        expiry_interval,
+    ));
+
+    let scanner = services::scanner::Settings {
+        concurrency: env::var("SCAN_CONCURRENCY")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(64),
+        timeout_ms: env::var("SCAN_TIMEOUT_MS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(1000),
+        pinger: match libipam::ipam_services::icmp::Pinger::new() {
+            Ok(e) => Some(Arc::new(e)),
+            Err(e) => {
+                tracing::warn!("Scanner: {}, using the system ping", e);
+                None
+            }
+        },
+    };
+    let scan_interval = services::interval("SCAN_INTERVAL", 30)?;
+    tokio::spawn(services::scanner::run(
+        db.clone(),
+        scan_interval,
+        scanner.clone(),
+        transitions.clone(),
+    ));
+    let jobs = services::scanner::Jobs::default();
+
+    tokio::spawn(services::webhooks::run(db.clone(), dispatcher.clone()));
+
+    let snmp_interval = env::var("SNMP_INTERVAL")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(0);
+    if snmp_interval > 0 {
+        tokio::spawn(services::snmp::run(
+            db.clone(),
+            std::time::Duration::from_secs(snmp_interval),
+            snmp.clone(),
+        ));
+    }
+
+    let network = Router::new()
+        .route("/create", put(network::create))
+        .route(
+            "/",
+            get(network::get)
+                .delete(network::delete)
+                .patch(network::update),
+        )
+        .route("/:id/history", get(network::history))
+        .route("/:id/scan", post(scan::start))
+        .route("/:id/history/:revision", post(network::restore));
 
     let device = Router::new()
         .route("/create", put(device::create))
@@ -56,18 +128,117 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             get(device::get_all).put(device::create_all_devices),
         ) // create, update and get all devices
         .route("/delete", delete(device::delete))
-        .route("/one", get(device::get_one).patch(device::update)); //get one device
+        .route("/one", get(device::get_one).patch(device::update)) //get one device
+        .route("/search", get(device::search))
+        .route("/transitions", get(device::transitions))
+        .route("/services", get(device::services))
+        .route("/services/probe", post(device::probe_services))
+        .route("/snmp", post(device::poll_snmp))
+        .route("/snmp/neighbors", post(device::snmp_neighbors))
+        .route("/history", get(device::history))
+        .route("/ip_history", get(device::ip_history))
+        .route("/history/:revision", post(device::restore))
+        .route(
+            "/:id",
+            get(device::get_device)
+                .patch(device::update_device)
+                .delete(device::delete_device),
+        )
+        .route(
+            "/:id/address",
+            post(device::assign).delete(device::unassign),
+        )
+        .route("/:id/history", get(device::device_history));
 
     let user = Router::new().route("/", post(auth::create));
 
+    let trash = Router::new()
+        .route("/", get(trash::get))
+        .route("/network/:id", post(trash::restore_network))
+        .route("/device/:id", post(trash::restore_device))
+        .route("/address", post(trash::restore_address))
+        .route("/office/:id", post(trash::restore_office));
+
+    let office = Router::new()
+        .route("/", get(office::get).put(office::create))
+        .route("/:id", delete(office::delete).patch(office::update));
+
+    let dhcp = Router::new()
+        .route("/scope", get(dhcp::get_scopes).put(dhcp::create_scope))
+        .route(
+            "/scope/:id",
+            delete(dhcp::delete_scope).patch(dhcp::update_scope),
+        )
+        .route(
+            "/reservation",
+            get(dhcp::get_reservations)
+                .put(dhcp::create_reservation)
+                .delete(dhcp::delete_reservation),
+        );
+
+    let scan = Router::new()
+        .route(
+            "/schedule",
+            get(scan::get_schedules).put(scan::create_schedule),
+        )
+        .route(
+            "/schedule/:network_id",
+            delete(scan::delete_schedule).patch(scan::update_schedule),
+        )
+        .route("/result", get(scan::get_results))
+        .route("/job/:id", get(scan::get_job))
+        .route("/job/:id/events", get(scan::job_events));
+
+    let tools = Router::new()
+        .route("/oui/reload", post(tools::reload_oui))
+        .route("/oui/:mac", get(tools::oui));
+
+    let import = Router::new()
+        .route("/leases", post(import::leases))
+        .route("/neighbors", post(import::neighbors))
+        .route("/devices", post(import::devices))
+        .route("/networks", post(import::networks));
+
+    let export = Router::new()
+        .route("/dns", get(export::dns_zones))
+        .route("/dns/:zone", get(export::dns_zone))
+        .route("/kea", get(export::kea));
+
+    let webhook = Router::new()
+        .route("/", get(webhook::get).put(webhook::create))
+        .route("/:id", delete(webhook::delete).patch(webhook::update))
+        .route("/:id/deliveries", get(webhook::deliveries))
+        .route("/delivery/:id/redeliver", post(webhook::redeliver));
+
+    let reports = Router::new().route("/conflicts", get(report::conflicts));
+
     let app = Router::new()
         .route("/", get(hello_world))
         .nest("/network", network)
         .nest("/device", device)
         .nest("/user", user)
-        // .layer(axum::middleware::from_fn(auth::verify_token))
+        .nest("/office", office)
+        .nest("/dhcp", dhcp)
+        .nest("/scan", scan)
+        .nest("/trash", trash)
+        .route("/audit", get(audit::get))
+        .route("/events", get(events::events))
+        .nest("/tools", tools)
+        .nest("/export", export)
+        .nest("/import", import)
+        .nest("/reports", reports)
+        .nest("/webhook", webhook)
+        .layer(axum::middleware::from_fn(auth::verify_token))
         .route("/login", post(auth::login))
         .with_state(db.clone())
+        .layer(Extension(oui))
+        .layer(Extension(ddns))
+        .layer(Extension(transitions))
+        .layer(Extension(jobs))
+        .layer(Extension(scanner))
+        .layer(Extension(probe))
+        .layer(Extension(snmp))
+        .layer(Extension(dispatcher))
         .layer(ServiceBuilder::new().layer(CorsLayer::permissive()));
 
     serve(lst, app).await?;
@@ -0,0 +1,288 @@
+use super::*;
+use serde_json::{json, Value};
+use time::OffsetDateTime;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Audit {
+    pub id: Uuid,
+    pub actor: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub entity: Entity,
+    pub action: Action,
+    pub key: Value,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, PartialEq, Clone, Copy)]
+#[sqlx(type_name = "ENTITY")]
+pub enum Entity {
+    Network,
+    Device,
+    Address,
+    User,
+    Office,
+    Scope,
+    Reservation,
+    Schedule,
+    Webhook,
+}
+
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, PartialEq, Clone, Copy)]
+#[sqlx(type_name = "ACTION")]
+pub enum Action {
+    Insert,
+    Update,
+    Delete,
+    Restore,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct AuditFilter {
+    pub entity: Option<Entity>,
+    pub actor: Option<Uuid>,
+    #[serde(skip)]
+    pub key: Option<Value>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub since: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+}
+
+// What subscribers of /events receive for every audited change
+#[derive(Serialize, Debug, Clone)]
+pub struct Event {
+    pub id: Uuid,
+    pub actor: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub entity: Entity,
+    pub action: Action,
+    pub key: Value,
+    pub network_id: Option<Uuid>,
+    pub office_id: Option<Uuid>,
+    pub data: Option<Value>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct EventFilter {
+    pub entity: Option<Entity>,
+    pub network_id: Option<Uuid>,
+    pub office_id: Option<Uuid>,
+}
+
+pub trait Auditable: Serialize {
+    const ENTITY: Entity;
+
+    fn key(&self) -> Value;
+
+    fn snapshot(&self) -> Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+impl Audit {
+    fn new<T: Auditable>(
+        actor: Option<Uuid>,
+        action: Action,
+        key: Value,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            actor,
+            timestamp: OffsetDateTime::now_utc(),
+            entity: T::ENTITY,
+            action,
+            key,
+            before: before.map(Auditable::snapshot),
+            after: after.map(Auditable::snapshot),
+        }
+    }
+
+    pub fn insert<T: Auditable>(actor: Option<Uuid>, after: &T) -> Self {
+        Self::new(actor, Action::Insert, after.key(), None, Some(after))
+    }
+
+    pub fn update<T: Auditable>(actor: Option<Uuid>, before: &T, after: &T) -> Self {
+        Self::new(
+            actor,
+            Action::Update,
+            before.key(),
+            Some(before),
+            Some(after),
+        )
+    }
+
+    pub fn delete<T: Auditable>(actor: Option<Uuid>, before: &T) -> Self {
+        Self::new(actor, Action::Delete, before.key(), Some(before), None)
+    }
+
+    pub fn restore<T: Auditable>(actor: Option<Uuid>, after: &T) -> Self {
+        Self::new(actor, Action::Restore, after.key(), None, Some(after))
+    }
+}
+
+impl Auditable for super::network::Network {
+    const ENTITY: Entity = Entity::Network;
+
+    fn key(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+// Device passwords are kept out of the trail
+fn without_password(mut value: Value) -> Value {
+    if let Some(e) = value.get_mut("credential").and_then(Value::as_object_mut) {
+        e.remove("password");
+    }
+    value
+}
+
+// Puts back the password taken out of a snapshot, without one the
+// credential is left out of the restored state
+pub fn with_password(mut snapshot: Value, password: Option<String>) -> Value {
+    if let Some(credential) = snapshot
+        .get_mut("credential")
+        .filter(|x| x.is_object() && x.get("password").is_none())
+    {
+        *credential = match password {
+            Some(e) => {
+                let mut credential = credential.take();
+                credential["password"] = e.into();
+                credential
+            }
+            None => Value::Null,
+        };
+    }
+    snapshot
+}
+
+impl Auditable for super::device::Device {
+    const ENTITY: Entity = Entity::Device;
+
+    fn key(&self) -> Value {
+        json!({ "id": self.id })
+    }
+
+    fn snapshot(&self) -> Value {
+        without_password(serde_json::to_value(self).unwrap_or_default())
+    }
+}
+
+impl Auditable for super::device::DeviceView {
+    const ENTITY: Entity = Entity::Address;
+
+    fn key(&self) -> Value {
+        json!({ "ip": self.ip, "network_id": self.network_id })
+    }
+
+    fn snapshot(&self) -> Value {
+        without_password(serde_json::to_value(self).unwrap_or_default())
+    }
+}
+
+impl Auditable for super::dhcp::Scope {
+    const ENTITY: Entity = Entity::Scope;
+
+    fn key(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+impl Auditable for super::dhcp::Reservation {
+    const ENTITY: Entity = Entity::Reservation;
+
+    fn key(&self) -> Value {
+        json!({ "ip": self.ip, "network_id": self.network_id })
+    }
+}
+
+impl Auditable for super::scan::Schedule {
+    const ENTITY: Entity = Entity::Schedule;
+
+    fn key(&self) -> Value {
+        json!({ "network_id": self.network_id })
+    }
+}
+
+impl Auditable for super::user::User {
+    const ENTITY: Entity = Entity::User;
+
+    fn key(&self) -> Value {
+        json!({ "id": self.id })
+    }
+
+    fn snapshot(&self) -> Value {
+        json!({
+            "id": self.id,
+            "username": self.username,
+            "role": self.role,
+        })
+    }
+}
+
+impl Auditable for super::office::Office {
+    const ENTITY: Entity = Entity::Office;
+
+    fn key(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+impl Auditable for super::webhook::Webhook {
+    const ENTITY: Entity = Entity::Webhook;
+
+    fn key(&self) -> Value {
+        json!({ "id": self.id })
+    }
+
+    // The signing secret stays out of the trail
+    fn snapshot(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(e) = value.as_object_mut() {
+            e.remove("secret");
+        }
+        value
+    }
+}
+
+impl From<&Audit> for Event {
+    fn from(value: &Audit) -> Self {
+        let data = value.after.clone().or(value.before.clone());
+        let field = |name: &str| {
+            [Some(&value.key), data.as_ref()]
+                .into_iter()
+                .flatten()
+                .find_map(|x| serde_json::from_value(x.get(name)?.clone()).ok())
+        };
+
+        Self {
+            id: value.id,
+            actor: value.actor,
+            timestamp: value.timestamp,
+            entity: value.entity,
+            action: value.action,
+            key: value.key.clone(),
+            network_id: match value.entity {
+                Entity::Network => field("id"),
+                _ => field("network_id"),
+            },
+            office_id: match value.entity {
+                Entity::Office => field("id"),
+                _ => field("office_id"),
+            },
+            data,
+        }
+    }
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        self.entity.is_none_or(|x| x == event.entity)
+            && self.network_id.is_none_or(|x| Some(x) == event.network_id)
+            && self.office_id.is_none_or(|x| Some(x) == event.office_id)
+    }
+}
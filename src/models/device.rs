@@ -1,30 +1,317 @@
 use super::*;
+use libipam::ipam_services::snmp::Interface;
+use libipam::type_net::{
+    dns::{DomainName, Hostname},
+    mac::{MacAddr, Oui},
+    port::Port,
+};
+use serde_json::{json, Value};
 use std::net::IpAddr;
+use time::OffsetDateTime;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct UpdateDevice {
-    pub ip: Option<IpAddr>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub hostname: Option<Option<Hostname>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub domain: Option<Option<DomainName>>,
+    pub kind: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
     pub description: Option<String>,
     pub office_id: Option<Uuid>,
     pub rack: Option<String>,
     pub room: Option<String>,
-    pub status: Option<Status>,
-    pub network_id: Option<Uuid>,
     pub credential: Option<Credential>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Device {
-    pub ip: IpAddr,
+    pub id: Uuid,
+    pub hostname: Option<Hostname>,
+    pub domain: Option<DomainName>,
+    pub kind: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
     pub description: Option<String>,
     pub office_id: Option<Uuid>,
     pub rack: Option<String>,
     pub room: Option<String>,
+    pub credential: Option<Credential>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Address {
+    pub ip: IpAddr,
+    pub network_id: Uuid,
+    pub device_id: Option<Uuid>,
+    pub status: Status,
+    pub mac: Option<MacAddr>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_seen: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub reserved_until: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateDeviceView {
+    pub ip: Option<IpAddr>,
+    pub network_id: Option<Uuid>,
+    pub status: Option<Status>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub reserved_until: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub mac: Option<Option<MacAddr>>,
+    #[serde(flatten)]
+    pub device: UpdateDevice,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DeviceView {
+    pub ip: IpAddr,
+    pub network_id: Uuid,
     pub status: Status,
-    pub network_id: uuid::Uuid,
+    pub device_id: Option<Uuid>,
+    pub mac: Option<MacAddr>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_seen: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub reserved_until: Option<OffsetDateTime>,
+    pub hostname: Option<Hostname>,
+    pub domain: Option<DomainName>,
+    pub kind: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub description: Option<String>,
+    pub office_id: Option<Uuid>,
+    pub rack: Option<String>,
+    pub room: Option<String>,
     pub credential: Option<Credential>,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Service {
+    pub device_id: Uuid,
+    pub ip: IpAddr,
+    pub network_id: Uuid,
+    pub port: Port,
+    pub name: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub seen: OffsetDateTime,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ServiceFilter {
+    pub device_id: Option<Uuid>,
+    pub network_id: Option<Uuid>,
+    pub port: Option<Port>,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SnmpConflict {
+    pub ip: IpAddr,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SnmpReport {
+    pub device_id: Uuid,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub hostname: Option<Hostname>,
+    pub interfaces: Vec<Interface>,
+    pub created: usize,
+    pub updated: usize,
+    pub unmatched: Vec<IpAddr>,
+    pub conflicts: Vec<SnmpConflict>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct NeighborConflict {
+    pub ip: IpAddr,
+    pub macs: Vec<MacAddr>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct NeighborReport {
+    pub online: usize,
+    pub updated: usize,
+    pub unmatched: Vec<IpAddr>,
+    pub unregistered: Vec<IpAddr>,
+    pub conflicts: Vec<NeighborConflict>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct WithVendor<T> {
+    #[serde(flatten)]
+    pub data: T,
+    pub vendor: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct DeviceFilter {
+    pub network_id: Option<Uuid>,
+    pub hostname: Option<String>,
+    pub domain: Option<DomainName>,
+    pub mac: Option<MacAddr>,
+    pub oui: Option<Oui>,
+}
+
+impl UpdateDevice {
+    pub fn is_empty(&self) -> bool {
+        self.hostname.is_none()
+            && self.domain.is_none()
+            && self.kind.is_none()
+            && self.model.is_none()
+            && self.serial.is_none()
+            && self.description.is_none()
+            && self.office_id.is_none()
+            && self.rack.is_none()
+            && self.room.is_none()
+            && self.credential.is_none()
+    }
+}
+
+impl From<UpdateDevice> for Device {
+    fn from(value: UpdateDevice) -> Self {
+        let not_empty = |x: Option<String>| x.filter(|x| !x.is_empty());
+        Self {
+            id: Uuid::new_v4(),
+            hostname: value.hostname.flatten(),
+            domain: value.domain.flatten(),
+            kind: not_empty(value.kind),
+            model: not_empty(value.model),
+            serial: not_empty(value.serial),
+            description: not_empty(value.description),
+            office_id: value.office_id.filter(|x| !x.is_nil()),
+            rack: not_empty(value.rack),
+            room: not_empty(value.room),
+            credential: value
+                .credential
+                .filter(|x| !x.username.is_empty() || !x.password.is_empty()),
+        }
+    }
+}
+
+impl Device {
+    // The row as it reads back once the updater is written
+    pub fn updated(&self, updater: &UpdateDevice) -> Self {
+        let text = |x: &Option<String>, current: &Option<String>| match x {
+            Some(e) if e.is_empty() => None,
+            Some(e) => Some(e.clone()),
+            None => current.clone(),
+        };
+        Self {
+            hostname: updater.hostname.clone().unwrap_or(self.hostname.clone()),
+            domain: updater.domain.clone().unwrap_or(self.domain.clone()),
+            kind: text(&updater.kind, &self.kind),
+            model: text(&updater.model, &self.model),
+            serial: text(&updater.serial, &self.serial),
+            description: text(&updater.description, &self.description),
+            office_id: match updater.office_id {
+                Some(e) => Some(e).filter(|x| !x.is_nil()),
+                None => self.office_id,
+            },
+            rack: text(&updater.rack, &self.rack),
+            room: text(&updater.room, &self.room),
+            credential: match &updater.credential {
+                Some(e) => {
+                    Some(e.clone()).filter(|x| !x.username.is_empty() || !x.password.is_empty())
+                }
+                None => self.credential.clone(),
+            },
+            ..self.clone()
+        }
+    }
+}
+
+impl Address {
+    pub fn free(ip: IpAddr, network_id: Uuid) -> Self {
+        Self {
+            ip,
+            network_id,
+            device_id: None,
+            status: Status::default(),
+            mac: None,
+            last_seen: None,
+            reserved_until: None,
+        }
+    }
+}
+
+impl DeviceView {
+    pub fn new(address: Address, device: Option<Device>) -> Self {
+        let device = device.filter(|x| Some(x.id) == address.device_id);
+        Self {
+            ip: address.ip,
+            network_id: address.network_id,
+            status: address.status,
+            device_id: device.as_ref().map(|x| x.id),
+            mac: address.mac,
+            last_seen: address.last_seen,
+            reserved_until: address.reserved_until,
+            hostname: device.as_ref().and_then(|x| x.hostname.clone()),
+            domain: device.as_ref().and_then(|x| x.domain.clone()),
+            kind: device.as_ref().and_then(|x| x.kind.clone()),
+            model: device.as_ref().and_then(|x| x.model.clone()),
+            serial: device.as_ref().and_then(|x| x.serial.clone()),
+            description: device.as_ref().and_then(|x| x.description.clone()),
+            office_id: device.as_ref().and_then(|x| x.office_id),
+            rack: device.as_ref().and_then(|x| x.rack.clone()),
+            room: device.as_ref().and_then(|x| x.room.clone()),
+            credential: device.and_then(|x| x.credential),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        Address {
+            ip: self.ip,
+            network_id: self.network_id,
+            device_id: self.device_id,
+            status: self.status.clone(),
+            mac: self.mac,
+            last_seen: self.last_seen,
+            reserved_until: self.reserved_until,
+        }
+    }
+
+    pub fn device(&self) -> Option<Device> {
+        self.device_id.map(|id| Device {
+            id,
+            hostname: self.hostname.clone(),
+            domain: self.domain.clone(),
+            kind: self.kind.clone(),
+            model: self.model.clone(),
+            serial: self.serial.clone(),
+            description: self.description.clone(),
+            office_id: self.office_id,
+            rack: self.rack.clone(),
+            room: self.room.clone(),
+            credential: self.credential.clone(),
+        })
+    }
+
+    pub fn identity(&self) -> Option<(Uuid, Value)> {
+        self.device_id.map(|id| {
+            (
+                id,
+                json!({
+                    "id": id,
+                    "mac": self.mac,
+                    "hostname": self.hostname,
+                    "domain": self.domain,
+                    "description": self.description,
+                    "office_id": self.office_id,
+                    "rack": self.rack,
+                    "room": self.room,
+                }),
+            )
+        })
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, sqlx::Type, PartialEq, Clone)]
 #[sqlx(type_name = "CREDENTIAL")]
 pub struct Credential {
@@ -32,16 +319,17 @@ pub struct Credential {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, sqlx::Type, PartialEq, Clone)]
+#[derive(Debug, Deserialize, Serialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Default)]
 pub enum Status {
     Reserved,
+    #[default]
     Unknown,
     Online,
     Offline,
-}
-
-impl Default for Status {
-    fn default() -> Self {
-        Self::Unknown
-    }
+    Allocated,
+    Deprecated,
+    Quarantined,
+    #[serde(rename = "DHCP")]
+    #[sqlx(rename = "DHCP")]
+    Dhcp,
 }
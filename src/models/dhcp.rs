@@ -0,0 +1,73 @@
+use super::*;
+use libipam::{
+    ipam_services::dhcp::Range,
+    type_net::{
+        dns::{DomainName, Hostname},
+        mac::MacAddr,
+    },
+};
+use std::net::IpAddr;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Scope {
+    pub id: Uuid,
+    pub network_id: Uuid,
+    pub range: Range,
+    pub gateway: Option<IpAddr>,
+    pub dns_servers: Vec<IpAddr>,
+    pub lease_time: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateScope {
+    pub range: Option<Range>,
+    pub gateway: Option<IpAddr>,
+    pub dns_servers: Option<Vec<IpAddr>>,
+    pub lease_time: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Reservation {
+    pub ip: IpAddr,
+    pub network_id: Uuid,
+    pub device_id: Uuid,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LeaseConflict {
+    pub ip: IpAddr,
+    pub mac: Option<MacAddr>,
+    pub hostname: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct LeaseReport {
+    pub created: usize,
+    pub updated: usize,
+    pub expired: usize,
+    pub unmatched: Vec<IpAddr>,
+    pub conflicts: Vec<LeaseConflict>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReservationView {
+    pub ip: IpAddr,
+    pub network_id: Uuid,
+    pub device_id: Uuid,
+    pub mac: MacAddr,
+    pub hostname: Option<Hostname>,
+    pub domain: Option<DomainName>,
+}
+
+impl Scope {
+    pub fn updated(&self, updater: UpdateScope) -> Self {
+        Self {
+            range: updater.range.unwrap_or(self.range),
+            gateway: updater.gateway.or(self.gateway),
+            dns_servers: updater.dns_servers.unwrap_or(self.dns_servers.clone()),
+            lease_time: updater.lease_time.unwrap_or(self.lease_time),
+            ..self.clone()
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use super::*;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ZoneSerial {
+    pub zone: String,
+    pub serial: i64,
+    pub content: String,
+}
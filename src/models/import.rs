@@ -0,0 +1,32 @@
+use super::*;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RowError {
+    pub line: usize,
+    pub field: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub rows: usize,
+    pub valid: usize,
+    pub created: usize,
+    pub errors: Vec<RowError>,
+}
+
+impl ImportReport {
+    pub fn new(rows: usize, dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            rows,
+            ..Default::default()
+        }
+    }
+
+    // Rows are only written when every one of them is valid
+    pub fn committed(&self) -> bool {
+        !self.dry_run && self.errors.is_empty()
+    }
+}
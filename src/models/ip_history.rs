@@ -0,0 +1,42 @@
+use super::*;
+use serde_json::Value;
+use std::net::IpAddr;
+use time::OffsetDateTime;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct IpHistory {
+    pub id: Uuid,
+    pub ip: IpAddr,
+    pub network_id: Uuid,
+    pub device_id: Uuid,
+    pub device: Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Debug)]
+pub struct CloseIpHistory {
+    pub to: OffsetDateTime,
+}
+
+impl IpHistory {
+    pub fn open(
+        ip: IpAddr,
+        network_id: Uuid,
+        device_id: Uuid,
+        device: Value,
+        from: OffsetDateTime,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            ip,
+            network_id,
+            device_id,
+            device,
+            from,
+            to: None,
+        }
+    }
+}
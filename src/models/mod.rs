@@ -1,14 +1,32 @@
+pub mod audit;
 pub mod device;
+pub mod dhcp;
+pub mod dns;
+pub mod import;
+pub mod ip_history;
 pub mod network;
+pub mod report;
+pub mod scan;
+pub mod trash;
 pub mod user;
+pub mod webhook;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
+// Tells apart a missing field from an explicit null
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 pub mod office {
     use super::*;
 
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Debug, Deserialize, Serialize, Clone)]
     pub struct Office {
         pub id: Uuid,
         pub name: String,
@@ -16,8 +34,21 @@ pub mod office {
         pub description: Option<String>,
     }
 
+    #[derive(Debug, Deserialize, Serialize)]
     pub struct UpdateOffice {
+        pub name: Option<String>,
         pub description: Option<String>,
         pub address: Option<String>,
     }
+
+    impl Office {
+        pub fn updated(&self, updater: &UpdateOffice) -> Self {
+            Self {
+                name: updater.name.clone().unwrap_or(self.name.clone()),
+                address: updater.address.clone().or(self.address.clone()),
+                description: updater.description.clone().or(self.description.clone()),
+                ..self.clone()
+            }
+        }
+    }
 }
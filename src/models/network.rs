@@ -1,12 +1,19 @@
 use super::*;
 use ipnet::IpNet;
-use libipam::type_net::{host_count::HostCount, vlan::Vlan};
+use libipam::{
+    ipam_services::designation::{broadcast, is_unusable},
+    type_net::{host_count::HostCount, vlan::Vlan},
+};
+use std::net::IpAddr;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UpdateNetwork {
     pub network: Option<IpNet>,
     pub description: Option<String>,
     pub vlan: Option<Vlan>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub gateway: Option<Option<IpAddr>>,
+    pub infrastructure: Option<Vec<IpAddr>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -18,4 +25,86 @@ pub struct Network {
     pub available: HostCount,
     pub used: HostCount,
     pub free: HostCount,
+    #[serde(default)]
+    pub gateway: Option<IpAddr>,
+    #[serde(default, skip_deserializing)]
+    pub broadcast: Option<IpAddr>,
+    #[serde(default)]
+    pub infrastructure: Vec<IpAddr>,
+}
+
+impl Network {
+    pub fn new(
+        network: IpNet,
+        description: Option<String>,
+        vlan: Option<Vlan>,
+        gateway: Option<IpAddr>,
+        infrastructure: Vec<IpAddr>,
+    ) -> Self {
+        let avl = 2_u32.pow(32 - network.prefix_len() as u32) - 2;
+        Self {
+            id: Uuid::new_v4(),
+            network,
+            description,
+            available: avl.into(),
+            used: 0.into(),
+            free: avl.into(),
+            vlan,
+            gateway,
+            broadcast: None,
+            infrastructure,
+        }
+        .with_broadcast()
+    }
+
+    pub fn designation(&self, ip: &IpAddr) -> Option<&'static str> {
+        if Some(*ip) == self.gateway {
+            Some("the gateway")
+        } else if self.infrastructure.contains(ip) {
+            Some("an infrastructure address")
+        } else if is_unusable(&self.network, ip) {
+            Some("the network or broadcast address")
+        } else {
+            None
+        }
+    }
+
+    pub fn designated(&self) -> Vec<IpAddr> {
+        self.gateway
+            .iter()
+            .chain(self.infrastructure.iter())
+            .copied()
+            .collect()
+    }
+
+    // The row as it reads back once the updater is written
+    pub fn updated(&self, updater: &UpdateNetwork) -> Self {
+        Self {
+            network: updater.network.unwrap_or(self.network),
+            description: match &updater.description {
+                Some(e) if e.is_empty() => None,
+                Some(e) => Some(e.clone()),
+                None => self.description.clone(),
+            },
+            vlan: match &updater.vlan {
+                Some(e) if **e == 0 => None,
+                Some(e) => Some(e.clone()),
+                None => self.vlan.clone(),
+            },
+            gateway: updater.gateway.unwrap_or(self.gateway),
+            infrastructure: updater
+                .infrastructure
+                .clone()
+                .unwrap_or(self.infrastructure.clone()),
+            ..self.clone()
+        }
+        .with_broadcast()
+    }
+
+    pub fn with_broadcast(self) -> Self {
+        Self {
+            broadcast: broadcast(&self.network),
+            ..self
+        }
+    }
 }
@@ -0,0 +1,55 @@
+use super::device::{DeviceView, Status};
+use super::*;
+use ipnet::IpNet;
+use libipam::type_net::{dns::Hostname, mac::MacAddr};
+use std::net::IpAddr;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConflictAddress {
+    pub ip: IpAddr,
+    pub network_id: Uuid,
+    pub network: Option<IpNet>,
+    pub device_id: Option<Uuid>,
+    pub hostname: Option<Hostname>,
+    pub mac: Option<MacAddr>,
+    pub status: Status,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DuplicateIp {
+    pub ip: IpAddr,
+    pub addresses: Vec<ConflictAddress>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MacCollision {
+    pub mac: MacAddr,
+    pub addresses: Vec<ConflictAddress>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ConflictReport {
+    pub duplicate_ips: Vec<DuplicateIp>,
+    pub outside_prefix: Vec<ConflictAddress>,
+    pub mac_collisions: Vec<MacCollision>,
+}
+
+impl ConflictAddress {
+    pub fn new(view: &DeviceView, network: Option<IpNet>) -> Self {
+        Self {
+            ip: view.ip,
+            network_id: view.network_id,
+            network,
+            device_id: view.device_id,
+            hostname: view.hostname.clone(),
+            mac: view.mac,
+            status: view.status.clone(),
+        }
+    }
+}
+
+impl ConflictReport {
+    pub fn len(&self) -> usize {
+        self.duplicate_ips.len() + self.outside_prefix.len() + self.mac_collisions.len()
+    }
+}
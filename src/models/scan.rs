@@ -0,0 +1,97 @@
+use super::device::DeviceView;
+use super::*;
+use std::net::IpAddr;
+use time::OffsetDateTime;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Schedule {
+    pub network_id: Uuid,
+    pub interval: i64,
+    pub enabled: bool,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_run: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateSchedule {
+    pub interval: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ScanResult {
+    pub id: Uuid,
+    pub network_id: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub finished_at: OffsetDateTime,
+    pub scanned: i64,
+    pub online: i64,
+    pub offline: i64,
+    pub alive: Vec<IpAddr>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum JobState {
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScanReport {
+    pub result: ScanResult,
+    pub rogue: Vec<IpAddr>,
+    pub silent: Vec<DeviceView>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScanJob {
+    pub id: Uuid,
+    pub network_id: Uuid,
+    pub state: JobState,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+    pub total: usize,
+    pub done: usize,
+    pub alive: usize,
+    pub report: Option<ScanReport>,
+    pub error: Option<String>,
+}
+
+impl ScanJob {
+    pub fn new(network_id: Uuid, total: usize) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            network_id,
+            state: JobState::Running,
+            started_at: OffsetDateTime::now_utc(),
+            finished_at: None,
+            total,
+            done: 0,
+            alive: 0,
+            report: None,
+            error: None,
+        }
+    }
+}
+
+impl Schedule {
+    pub fn updated(&self, updater: UpdateSchedule) -> Self {
+        Self {
+            interval: updater.interval.unwrap_or(self.interval),
+            enabled: updater.enabled.unwrap_or(self.enabled),
+            ..self.clone()
+        }
+    }
+
+    pub fn is_due(&self, now: OffsetDateTime) -> bool {
+        self.enabled
+            && self
+                .last_run
+                .is_none_or(|x| x + time::Duration::seconds(self.interval) <= now)
+    }
+}
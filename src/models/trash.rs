@@ -0,0 +1,33 @@
+use super::*;
+use time::OffsetDateTime;
+
+#[derive(Serialize, Debug)]
+pub struct Trash<T> {
+    #[serde(flatten)]
+    pub data: T,
+    #[serde(with = "time::serde::rfc3339")]
+    pub deleted_at: OffsetDateTime,
+    pub deleted_by: Option<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct SoftDelete {
+    pub deleted_at: Option<OffsetDateTime>,
+    pub deleted_by: Option<Uuid>,
+}
+
+impl SoftDelete {
+    pub fn delete(actor: Uuid, deleted_at: OffsetDateTime) -> Self {
+        Self {
+            deleted_at: Some(deleted_at),
+            deleted_by: Some(actor),
+        }
+    }
+
+    pub fn restore() -> Self {
+        Self {
+            deleted_at: None,
+            deleted_by: None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::audit::{Action, Entity, Event};
+use super::*;
+use serde_json::Value;
+use time::OffsetDateTime;
+
+// Empty entities or actions match all of them
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub entities: Vec<Entity>,
+    pub actions: Vec<Action>,
+    pub network_id: Option<Uuid>,
+    pub office_id: Option<Uuid>,
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateWebhook {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub entities: Option<Vec<Entity>>,
+    pub actions: Option<Vec<Action>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub network_id: Option<Option<Uuid>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub office_id: Option<Option<Uuid>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Delivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: Value,
+    pub attempts: i64,
+    pub status: Option<i64>,
+    pub error: Option<String>,
+    pub delivered: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_attempt: Option<OffsetDateTime>,
+}
+
+impl Webhook {
+    pub fn updated(&self, updater: UpdateWebhook) -> Self {
+        Self {
+            url: updater.url.unwrap_or(self.url.clone()),
+            secret: updater.secret.unwrap_or(self.secret.clone()),
+            entities: updater.entities.unwrap_or(self.entities.clone()),
+            actions: updater.actions.unwrap_or(self.actions.clone()),
+            network_id: updater.network_id.unwrap_or(self.network_id),
+            office_id: updater.office_id.unwrap_or(self.office_id),
+            enabled: updater.enabled.unwrap_or(self.enabled),
+            ..self.clone()
+        }
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        self.enabled
+            && (self.entities.is_empty() || self.entities.contains(&event.entity))
+            && (self.actions.is_empty() || self.actions.contains(&event.action))
+            && self.network_id.is_none_or(|x| Some(x) == event.network_id)
+            && self.office_id.is_none_or(|x| Some(x) == event.office_id)
+    }
+}
+
+impl Delivery {
+    pub fn new(webhook_id: Uuid, event: Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            webhook_id,
+            event,
+            attempts: 0,
+            status: None,
+            error: None,
+            delivered: false,
+            created_at: OffsetDateTime::now_utc(),
+            last_attempt: None,
+        }
+    }
+
+    // "Address.Insert", sent along so receivers can route without parsing
+    pub fn event_name(&self) -> String {
+        let field = |x: &str| {
+            self.event
+                .get(x)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+        };
+        format!("{}.{}", field("entity"), field("action"))
+    }
+}
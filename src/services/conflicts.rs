@@ -0,0 +1,262 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository},
+        RepositoryInjection,
+    },
+    models::{
+        device::{DeviceView, Status},
+        network::Network,
+        report::{ConflictAddress, ConflictReport, DuplicateIp, MacCollision},
+    },
+};
+use ipnet::IpNet;
+use libipam::type_net::mac::MacAddr;
+use sqlx::Postgres;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::IpAddr,
+};
+use uuid::Uuid;
+
+// Free addresses left behind by a release don't count as a use of the ip
+fn in_use(view: &DeviceView) -> bool {
+    view.device_id.is_some() || view.status != Status::Unknown
+}
+
+// An unassigned address owns its mac on its own
+fn owner(view: &DeviceView) -> (Option<Uuid>, Option<(IpAddr, Uuid)>) {
+    match view.device_id {
+        Some(e) => (Some(e), None),
+        None => (None, Some((view.ip, view.network_id))),
+    }
+}
+
+pub async fn report(db: &RepositoryInjection<Postgres>) -> Result<ConflictReport, RepositoryError> {
+    let networks: HashMap<Uuid, IpNet> = match db.get::<Network>(None).await {
+        Err(RepositoryError::RowNotFound) => HashMap::new(),
+        e => e?.into_iter().map(|x| (x.id, x.network)).collect(),
+    };
+    let addresses = match db.get::<DeviceView>(None).await {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    Ok(find(&networks, &addresses))
+}
+
+// The same ip in overlapping networks, addresses outside the prefix of their
+// network and macs shared by more than one device
+fn find(networks: &HashMap<Uuid, IpNet>, addresses: &[DeviceView]) -> ConflictReport {
+    let entry =
+        |view: &DeviceView| ConflictAddress::new(view, networks.get(&view.network_id).copied());
+    let mut report = ConflictReport::default();
+
+    let mut by_ip: BTreeMap<IpAddr, Vec<&DeviceView>> = BTreeMap::new();
+    let mut by_mac: HashMap<MacAddr, Vec<&DeviceView>> = HashMap::new();
+    for view in addresses.iter().filter(|x| in_use(x)) {
+        by_ip.entry(view.ip).or_default().push(view);
+        if let Some(mac) = view.mac {
+            by_mac.entry(mac).or_default().push(view);
+        }
+        if networks
+            .get(&view.network_id)
+            .is_some_and(|x| !x.contains(&view.ip))
+        {
+            report.outside_prefix.push(entry(view));
+        }
+    }
+
+    report.duplicate_ips = by_ip
+        .into_iter()
+        .filter(|x| x.1.len() > 1)
+        .map(|(ip, views)| DuplicateIp {
+            ip,
+            addresses: views.into_iter().map(entry).collect(),
+        })
+        .collect();
+
+    report.mac_collisions = by_mac
+        .into_iter()
+        .filter(|x| x.1.iter().map(|x| owner(x)).collect::<HashSet<_>>().len() > 1)
+        .map(|(mac, mut views)| {
+            views.sort_by_key(|x| x.ip);
+            MacCollision {
+                mac,
+                addresses: views.into_iter().map(entry).collect(),
+            }
+        })
+        .collect();
+    report
+        .mac_collisions
+        .sort_by_key(|x| x.addresses.first().map(|x| x.ip));
+    report.outside_prefix.sort_by_key(|x| x.ip);
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find, DeviceView, IpNet, Status};
+    use crate::models::device::Address;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    const MAC: &str = "00:1b:44:11:3a:b7";
+
+    fn view(
+        ip: &str,
+        network_id: Uuid,
+        device_id: Option<Uuid>,
+        status: Status,
+        mac: Option<&str>,
+    ) -> DeviceView {
+        let address = Address {
+            status,
+            mac: mac.map(|x| x.parse().unwrap()),
+            ..Address::free(ip.parse().unwrap(), network_id)
+        };
+
+        DeviceView {
+            device_id,
+            ..DeviceView::new(address, None)
+        }
+    }
+
+    // 10.0.0.0/16 overlaps 10.0.1.0/24
+    fn networks() -> (Uuid, Uuid, HashMap<Uuid, IpNet>) {
+        let (wide, narrow) = (Uuid::new_v4(), Uuid::new_v4());
+        let networks = HashMap::from([
+            (wide, "10.0.0.0/16".parse().unwrap()),
+            (narrow, "10.0.1.0/24".parse().unwrap()),
+        ]);
+
+        (wide, narrow, networks)
+    }
+
+    #[test]
+    fn duplicate_ip_in_overlapping_networks() {
+        let (wide, narrow, networks) = networks();
+        let addresses = [
+            view("10.0.1.5", wide, Some(Uuid::new_v4()), Status::Online, None),
+            view("10.0.1.5", narrow, None, Status::Reserved, None),
+            view("10.0.1.6", wide, Some(Uuid::new_v4()), Status::Online, None),
+            view("10.0.1.6", narrow, None, Status::Unknown, None),
+        ];
+
+        let report = find(&networks, &addresses);
+        assert_eq!(1, report.duplicate_ips.len());
+        assert_eq!("10.0.1.5".parse(), Ok(report.duplicate_ips[0].ip));
+
+        let mut found: Vec<Uuid> = report.duplicate_ips[0]
+            .addresses
+            .iter()
+            .map(|x| x.network_id)
+            .collect();
+        found.sort();
+        let mut expected = vec![wide, narrow];
+        expected.sort();
+        assert_eq!(expected, found);
+        assert_eq!(1, report.len());
+    }
+
+    #[test]
+    fn outside_prefix_of_its_own_network() {
+        let (wide, narrow, networks) = networks();
+        let addresses = [
+            view(
+                "10.0.2.5",
+                narrow,
+                Some(Uuid::new_v4()),
+                Status::Online,
+                None,
+            ),
+            view(
+                "10.0.1.7",
+                narrow,
+                Some(Uuid::new_v4()),
+                Status::Online,
+                None,
+            ),
+            view("10.0.2.6", wide, Some(Uuid::new_v4()), Status::Online, None),
+            view("10.1.0.1", wide, None, Status::Reserved, None),
+            view("10.2.0.1", wide, None, Status::Unknown, None),
+            view(
+                "10.3.0.1",
+                Uuid::new_v4(),
+                Some(Uuid::new_v4()),
+                Status::Online,
+                None,
+            ),
+        ];
+
+        let report = find(&networks, &addresses);
+        let outside: Vec<_> = report
+            .outside_prefix
+            .iter()
+            .map(|x| (x.ip.to_string(), x.network))
+            .collect();
+        assert_eq!(
+            vec![
+                ("10.0.2.5".to_string(), networks.get(&narrow).copied()),
+                ("10.1.0.1".to_string(), networks.get(&wide).copied()),
+            ],
+            outside
+        );
+        assert!(report.duplicate_ips.is_empty());
+    }
+
+    #[test]
+    fn mac_shared_by_devices() {
+        let (wide, narrow, networks) = networks();
+        let device = Uuid::new_v4();
+        let addresses = [
+            view("10.0.0.10", wide, Some(device), Status::Online, Some(MAC)),
+            view("10.0.1.10", narrow, Some(device), Status::Online, Some(MAC)),
+            view(
+                "10.0.0.20",
+                wide,
+                Some(Uuid::new_v4()),
+                Status::Online,
+                Some(MAC),
+            ),
+        ];
+
+        let report = find(&networks, &addresses);
+        assert_eq!(1, report.mac_collisions.len());
+        assert_eq!(MAC.parse(), Ok(report.mac_collisions[0].mac));
+        assert_eq!(
+            vec!["10.0.0.10", "10.0.0.20", "10.0.1.10"],
+            report.mac_collisions[0]
+                .addresses
+                .iter()
+                .map(|x| x.ip.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mac_of_one_device_is_no_collision() {
+        let (wide, narrow, networks) = networks();
+        let device = Uuid::new_v4();
+        let addresses = [
+            view("10.0.0.10", wide, Some(device), Status::Online, Some(MAC)),
+            view("10.0.1.10", narrow, Some(device), Status::Online, Some(MAC)),
+            view("10.0.0.11", wide, None, Status::Unknown, Some(MAC)),
+        ];
+
+        assert_eq!(0, find(&networks, &addresses).len());
+    }
+
+    #[test]
+    fn mac_of_unassigned_addresses_collides() {
+        let (wide, _, networks) = networks();
+        let addresses = [
+            view("10.0.0.10", wide, None, Status::Reserved, Some(MAC)),
+            view("10.0.0.11", wide, None, Status::Dhcp, Some(MAC)),
+        ];
+
+        let report = find(&networks, &addresses);
+        assert_eq!(1, report.mac_collisions.len());
+        assert_eq!(2, report.mac_collisions[0].addresses.len());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    database::{repository::Repository, RepositoryInjection},
+    models::{device::DeviceView, network::Network},
+};
+use futures::future::BoxFuture;
+use libipam::ipam_services::{
+    dns::{reverse_name, reverse_zone, Record},
+    nsupdate::{Change, Client, Tsig, UpdateError},
+};
+use sqlx::Postgres;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
+use uuid::Uuid;
+
+pub trait DnsProvider: Send + Sync {
+    fn apply<'a>(
+        &'a self,
+        zone: &'a str,
+        changes: &'a [Change],
+    ) -> BoxFuture<'a, Result<(), UpdateError>>;
+}
+
+pub type Ddns = Arc<dyn DnsProvider>;
+
+pub struct Disabled;
+
+impl DnsProvider for Disabled {
+    fn apply<'a>(&'a self, _: &'a str, _: &'a [Change]) -> BoxFuture<'a, Result<(), UpdateError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl DnsProvider for Client {
+    fn apply<'a>(
+        &'a self,
+        zone: &'a str,
+        changes: &'a [Change],
+    ) -> BoxFuture<'a, Result<(), UpdateError>> {
+        Box::pin(self.update(zone, changes))
+    }
+}
+
+fn setting(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|x| !x.is_empty())
+}
+
+pub fn provider() -> Result<Ddns, Box<dyn std::error::Error>> {
+    let Some(server) = setting("DNS_UPDATE_SERVER") else {
+        return Ok(Arc::new(Disabled));
+    };
+
+    let tsig = match (
+        setting("DNS_UPDATE_KEY_NAME"),
+        setting("DNS_UPDATE_KEY_SECRET"),
+    ) {
+        (Some(name), Some(secret)) => Some(Tsig::new(&name, &secret)?),
+        _ => None,
+    };
+
+    Ok(Arc::new(Client {
+        server: server.parse()?,
+        tsig,
+        timeout: Duration::from_secs(
+            setting("DNS_UPDATE_TIMEOUT")
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(2),
+        ),
+        retries: setting("DNS_UPDATE_RETRIES")
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(3),
+    }))
+}
+
+struct Name {
+    ip: IpAddr,
+    network_id: Uuid,
+    domain: String,
+    fqdn: String,
+}
+
+impl Name {
+    fn new(view: &DeviceView) -> Option<Self> {
+        let domain = view.domain.as_ref()?;
+        Some(Self {
+            ip: view.ip,
+            network_id: view.network_id,
+            domain: domain.to_string(),
+            fqdn: domain.fqdn(view.hostname.as_ref()?),
+        })
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        (self.ip, &self.fqdn) == (other.ip, &other.fqdn)
+    }
+}
+
+async fn reverse(db: &RepositoryInjection<Postgres>, network_id: Uuid) -> Option<String> {
+    db.get::<Network>(Some(HashMap::from([("id", network_id.into())])))
+        .await
+        .ok()?
+        .pop()
+        .map(|x| reverse_zone(&x.network))
+}
+
+// The A/AAAA and PTR records follow the address, the update is sent in the
+// background so a DNS outage doesn't block the ipam
+pub async fn publish(
+    db: &RepositoryInjection<Postgres>,
+    ddns: &Ddns,
+    before: Option<&DeviceView>,
+    after: Option<&DeviceView>,
+) {
+    let before = before.and_then(Name::new);
+    let after = after.and_then(Name::new);
+
+    if before == after {
+        return;
+    }
+
+    let ttl = setting("DNS_TTL")
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(3600);
+    let mut zones: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+
+    if let Some(e) = before {
+        zones
+            .entry(e.domain)
+            .or_default()
+            .push(Change::Delete(e.fqdn.clone(), Record::from(e.ip)));
+        if let Some(zone) = reverse(db, e.network_id).await {
+            zones
+                .entry(zone)
+                .or_default()
+                .push(Change::Delete(reverse_name(&e.ip), Record::Ptr(e.fqdn)));
+        }
+    }
+
+    if let Some(e) = after {
+        zones.entry(e.domain).or_default().push(Change::Add(
+            e.fqdn.clone(),
+            ttl,
+            Record::from(e.ip),
+        ));
+        if let Some(zone) = reverse(db, e.network_id).await {
+            zones.entry(zone).or_default().push(Change::Add(
+                reverse_name(&e.ip),
+                ttl,
+                Record::Ptr(e.fqdn),
+            ));
+        }
+    }
+
+    let ddns = ddns.clone();
+    tokio::spawn(async move {
+        for (zone, changes) in zones {
+            if let Err(e) = ddns.apply(&zone, &changes).await {
+                tracing::warn!("The dynamic update of {} failed: {}", zone, e);
+            }
+        }
+    });
+}
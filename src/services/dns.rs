@@ -0,0 +1,170 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository},
+        RepositoryInjection,
+    },
+    models::{device::DeviceView, dns::ZoneSerial, network::Network},
+};
+use libipam::ipam_services::dns::{
+    absolute, is_reverse_zone, next_serial, relative, reverse_name, reverse_zone, Record, Soa, Zone,
+};
+use sqlx::Postgres;
+use std::collections::{BTreeSet, HashMap};
+use time::OffsetDateTime;
+
+const REFRESH: u32 = 3600;
+const RETRY: u32 = 900;
+const EXPIRE: u32 = 604800;
+
+fn setting(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|x| !x.is_empty())
+}
+
+// There's no address to glue a made up ns1 of the zone to, the name server
+// has to be configured
+pub fn primary_ns() -> Option<String> {
+    setting("DNS_PRIMARY_NS")
+}
+
+fn fqdn(device: &DeviceView) -> Option<String> {
+    Some(device.domain.as_ref()?.fqdn(device.hostname.as_ref()?))
+}
+
+fn in_zone(domain: &str, zone: &str) -> bool {
+    domain == zone || domain.strip_suffix(zone).is_some_and(|x| x.ends_with('.'))
+}
+
+async fn networks(db: &RepositoryInjection<Postgres>) -> Result<Vec<Network>, RepositoryError> {
+    match db.get::<Network>(None).await {
+        Err(RepositoryError::RowNotFound) => Ok(Vec::new()),
+        e => e,
+    }
+}
+
+pub async fn zones(
+    db: &RepositoryInjection<Postgres>,
+) -> Result<(Vec<String>, Vec<String>), RepositoryError> {
+    let forward: BTreeSet<String> = db
+        .named_devices()
+        .await?
+        .into_iter()
+        .filter_map(|x| x.domain.map(String::from))
+        .collect();
+    let reverse: BTreeSet<String> = networks(db)
+        .await?
+        .iter()
+        .map(|x| reverse_zone(&x.network))
+        .collect();
+
+    Ok((forward.into_iter().collect(), reverse.into_iter().collect()))
+}
+
+// None when nothing in the ipam belongs to the zone
+pub async fn zone(
+    db: &RepositoryInjection<Postgres>,
+    origin: &str,
+    primary: &str,
+) -> Result<Option<Zone>, RepositoryError> {
+    let devices = db.named_devices().await?;
+
+    let mut records: Vec<(String, Record)> = if is_reverse_zone(origin) {
+        let networks: Vec<_> = networks(db)
+            .await?
+            .into_iter()
+            .filter(|x| reverse_zone(&x.network) == origin)
+            .map(|x| x.id)
+            .collect();
+        if networks.is_empty() {
+            return Ok(None);
+        }
+
+        devices
+            .iter()
+            .filter(|x| networks.contains(&x.network_id))
+            .filter_map(|x| {
+                Some((
+                    relative(&reverse_name(&x.ip), origin),
+                    Record::Ptr(absolute(&fqdn(x)?)),
+                ))
+            })
+            .collect()
+    } else {
+        devices
+            .iter()
+            .filter(|x| x.domain.as_ref().is_some_and(|x| in_zone(x, origin)))
+            .filter_map(|x| Some((relative(&fqdn(x)?, origin), Record::from(x.ip))))
+            .collect()
+    };
+
+    if records.is_empty() && !is_reverse_zone(origin) {
+        return Ok(None);
+    }
+    records.sort_by(|a, b| (&a.0, a.1.to_string()).cmp(&(&b.0, b.1.to_string())));
+    records.dedup();
+
+    records.insert(0, ("@".to_string(), Record::Ns(absolute(primary))));
+    let ttl = setting("DNS_TTL")
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(3600);
+
+    let mut zone = Zone {
+        origin: origin.to_string(),
+        ttl,
+        soa: Soa {
+            primary: primary.to_string(),
+            hostmaster: setting("DNS_HOSTMASTER").unwrap_or(format!("hostmaster.{}", origin)),
+            serial: 0,
+            refresh: REFRESH,
+            retry: RETRY,
+            expire: EXPIRE,
+            minimum: ttl,
+        },
+        records,
+    };
+    zone.soa.serial = serial(db, origin, zone.records()).await?;
+
+    Ok(Some(zone))
+}
+
+// The serial only moves when the records of the zone change
+async fn serial(
+    db: &RepositoryInjection<Postgres>,
+    origin: &str,
+    content: String,
+) -> Result<u32, RepositoryError> {
+    let today = OffsetDateTime::now_utc().date();
+    let condition = || Some(HashMap::from([("zone", origin.to_string().into())]));
+
+    let stored = match db.get::<ZoneSerial>(condition()).await {
+        Ok(mut e) => e.pop(),
+        Err(RepositoryError::RowNotFound) => None,
+        Err(e) => return Err(e),
+    };
+
+    match stored {
+        Some(e) if e.content == content => Ok(e.serial as u32),
+        Some(e) => {
+            let serial = next_serial(Some(e.serial as u32), today);
+            db.update::<ZoneSerial, _>(
+                ZoneSerial {
+                    zone: origin.to_string(),
+                    serial: serial as i64,
+                    content,
+                },
+                condition(),
+            )
+            .await?;
+            Ok(serial)
+        }
+        None => {
+            let serial = next_serial(None, today);
+            db.insert(vec![ZoneSerial {
+                zone: origin.to_string(),
+                serial: serial as i64,
+                content,
+            }])
+            .await?;
+            Ok(serial)
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::models::{
+    audit::{Audit, Entity, Event},
+    user::Role,
+};
+use serde_json::Value;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+
+// Subscribers falling further behind than this miss events
+const CAPACITY: usize = 1024;
+
+fn channel() -> &'static broadcast::Sender<Event> {
+    static CHANNEL: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+    CHANNEL.get_or_init(|| broadcast::channel(CAPACITY).0)
+}
+
+pub fn publish(audits: &[Audit]) {
+    let tx = channel();
+    if tx.receiver_count() == 0 {
+        return;
+    }
+
+    for audit in audits {
+        let _ = tx.send(Event::from(audit));
+    }
+}
+
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    channel().subscribe()
+}
+
+// Users are only visible to admins, password hashes and webhook secrets to
+// nobody and device credentials to admins only
+pub fn redact(mut event: Event, role: &Role) -> Option<Event> {
+    if event.entity == Entity::User && role != &Role::Admin {
+        return None;
+    }
+
+    if let Some(Value::Object(data)) = event.data.as_mut() {
+        data.remove("password");
+        data.remove("secret");
+        if role != &Role::Admin {
+            data.remove("credential");
+        }
+    }
+    Some(event)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{redact, Entity, Event, Role};
+    use crate::models::audit::Action;
+    use serde_json::{json, Value};
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    fn event(entity: Entity, data: Value) -> Event {
+        Event {
+            id: Uuid::new_v4(),
+            actor: None,
+            timestamp: OffsetDateTime::now_utc(),
+            entity,
+            action: Action::Update,
+            key: json!({ "id": Uuid::new_v4() }),
+            network_id: None,
+            office_id: None,
+            data: Some(data),
+        }
+    }
+
+    #[test]
+    fn redact_removes_secrets_for_everyone() {
+        let webhook = json!({ "url": "https://example.test/hook", "secret": "s3cret" });
+        let user = json!({ "username": "admin", "password": "$argon2id$hash", "role": "Admin" });
+
+        for role in [Role::Admin, Role::Operator, Role::Guest] {
+            let data = redact(event(Entity::Webhook, webhook.clone()), &role)
+                .and_then(|x| x.data)
+                .unwrap();
+            assert_eq!(json!({ "url": "https://example.test/hook" }), data);
+        }
+
+        let data = redact(event(Entity::User, user), &Role::Admin)
+            .and_then(|x| x.data)
+            .unwrap();
+        assert_eq!(json!({ "username": "admin", "role": "Admin" }), data);
+    }
+
+    #[test]
+    fn redact_keeps_credentials_for_admins_only() {
+        let device = json!({ "hostname": "printer", "credential": { "username": "snmp" } });
+
+        let data = redact(event(Entity::Device, device.clone()), &Role::Admin)
+            .and_then(|x| x.data)
+            .unwrap();
+        assert_eq!(device, data);
+
+        for role in [Role::Operator, Role::Guest] {
+            let data = redact(event(Entity::Device, device.clone()), &role)
+                .and_then(|x| x.data)
+                .unwrap();
+            assert_eq!(json!({ "hostname": "printer" }), data);
+        }
+    }
+
+    #[test]
+    fn redact_hides_users_from_non_admins() {
+        let user = json!({ "username": "admin", "role": "Admin" });
+
+        assert!(redact(event(Entity::User, user.clone()), &Role::Operator).is_none());
+        assert!(redact(event(Entity::User, user.clone()), &Role::Guest).is_none());
+        assert!(redact(event(Entity::User, user), &Role::Admin).is_some());
+    }
+}
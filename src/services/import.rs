@@ -0,0 +1,497 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository, Table, TypeTable},
+        RepositoryInjection,
+    },
+    models::{
+        audit::Audit,
+        device::{Address, Device, DeviceView, Status},
+        dhcp::Scope,
+        import::{ImportReport, RowError},
+        ip_history::IpHistory,
+        network::Network,
+        office::Office,
+    },
+    services::{
+        ddns::{self, Ddns},
+        status::Transitions,
+    },
+};
+use ipnet::IpNet;
+use libipam::{
+    ipam_services::{
+        csv::Row,
+        designation::{gateway, is_unusable, GatewayPolicy},
+    },
+    type_net::{
+        dns::{DomainName, Hostname},
+        mac::MacAddr,
+        vlan::Vlan,
+    },
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::{postgres::PgRow, Postgres};
+use std::{collections::HashMap, fmt::Debug, hash::Hash, net::IpAddr};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// Collects every problem of a row instead of stopping at the first one
+struct Check<'a> {
+    row: &'a Row,
+    errors: Vec<RowError>,
+}
+
+impl<'a> Check<'a> {
+    fn new(row: &'a Row) -> Self {
+        Self {
+            row,
+            errors: Vec::new(),
+        }
+    }
+
+    fn fail(&mut self, field: &str, reason: String) {
+        self.errors.push(RowError {
+            line: self.row.line,
+            field: Some(field.to_string()),
+            reason,
+        });
+    }
+
+    fn text(&self, field: &str) -> Option<String> {
+        self.row.get(field).map(str::to_string)
+    }
+
+    // A cell is read as a json string first, then as a json value so
+    // numbers like vlans parse too
+    fn optional<T: DeserializeOwned>(&mut self, field: &str) -> Option<T> {
+        let value = self.row.get(field)?;
+        match serde_json::from_value(Value::String(value.to_string()))
+            .or_else(|_| serde_json::from_str(value))
+        {
+            Ok(e) => Some(e),
+            Err(_) => {
+                self.fail(field, format!("{} isn't a valid {}", value, field));
+                None
+            }
+        }
+    }
+
+    fn required<T: DeserializeOwned>(&mut self, field: &str) -> Option<T> {
+        if self.row.get(field).is_none() {
+            self.fail(field, format!("{} is missing", field));
+            return None;
+        }
+        self.optional(field)
+    }
+
+    // "10.0.0.2 10.0.0.3" or "10.0.0.2;10.0.0.3"
+    fn addresses(&mut self, field: &str) -> Vec<IpAddr> {
+        let Some(value) = self.row.get(field) else {
+            return Vec::new();
+        };
+        let mut resp = Vec::new();
+        for i in value.split([' ', ';']).filter(|x| !x.is_empty()) {
+            match i.parse() {
+                Ok(ip) => resp.push(ip),
+                Err(_) => self.fail(field, format!("{} isn't a valid address", i)),
+            }
+        }
+        resp
+    }
+}
+
+// The line a key was first seen on, when it isn't this one
+fn first<K: Eq + Hash>(seen: &mut HashMap<K, usize>, key: K, line: usize) -> Option<usize> {
+    Some(*seen.entry(key).or_insert(line)).filter(|x| *x != line)
+}
+
+async fn all<T>(db: &RepositoryInjection<Postgres>) -> Result<Vec<T>, RepositoryError>
+where
+    T: Table + From<PgRow> + Send + Debug + Clone,
+{
+    match db.get::<T>(None).await {
+        Err(RepositoryError::RowNotFound) => Ok(Vec::new()),
+        e => e,
+    }
+}
+
+fn address_key(ip: IpAddr, network_id: Uuid) -> HashMap<&'static str, TypeTable> {
+    HashMap::from([("ip", ip.into()), ("network_id", network_id.into())])
+}
+
+// First line of every ip, name and mac of the file
+#[derive(Default)]
+struct Seen {
+    ips: HashMap<(IpAddr, Uuid), usize>,
+    names: HashMap<String, usize>,
+    macs: HashMap<(Uuid, MacAddr), usize>,
+}
+
+struct DeviceRow {
+    view: DeviceView,
+    current: Option<DeviceView>,
+    trashed: bool,
+}
+
+// Rows go through the same checks as a device created by hand, against the
+// database and against each other. Without a network the most specific one
+// holding the ip is used
+async fn device_row(
+    db: &RepositoryInjection<Postgres>,
+    transitions: &Transitions,
+    check: &mut Check<'_>,
+    networks: &[Network],
+    offices: &[Office],
+    scopes: &[Scope],
+    seen: &mut Seen,
+) -> Result<Option<DeviceRow>, RepositoryError> {
+    let line = check.row.line;
+    let ip: Option<IpAddr> = check.required("ip");
+    let network = match (
+        check.optional::<Uuid>("network_id"),
+        check.optional::<IpNet>("network"),
+    ) {
+        (Some(id), _) => {
+            let network = networks.iter().find(|x| x.id == id);
+            if network.is_none() {
+                check.fail("network_id", format!("{} isn't a registered network", id));
+            }
+            network
+        }
+        (None, Some(prefix)) => {
+            let network = networks.iter().find(|x| x.network == prefix);
+            if network.is_none() {
+                check.fail("network", format!("{} isn't a registered network", prefix));
+            }
+            network
+        }
+        (None, None) => ip.and_then(|ip| {
+            let network = networks
+                .iter()
+                .filter(|x| x.network.contains(&ip))
+                .max_by_key(|x| x.network.prefix_len());
+            if network.is_none() {
+                check.fail("ip", format!("No registered network holds {}", ip));
+            }
+            network
+        }),
+    };
+    let office_id = match check.text("office") {
+        Some(name) => {
+            let office = offices.iter().find(|x| x.name == name).map(|x| x.id);
+            if office.is_none() {
+                check.fail("office", format!("{} isn't a registered office", name));
+            }
+            office
+        }
+        None => check.optional("office_id"),
+    };
+    let status: Status = check.optional("status").unwrap_or_default();
+    let hostname: Option<Hostname> = check.optional("hostname");
+    let domain: Option<DomainName> = check.optional("domain");
+    let mac: Option<MacAddr> = check.optional("mac");
+
+    let (Some(ip), Some(network)) = (ip, network) else {
+        return Ok(None);
+    };
+
+    if !network.network.contains(&ip) {
+        check.fail("ip", format!("{} is outside {}", ip, network.network));
+    } else if let Some(e) = network.designation(&ip) {
+        check.fail("ip", format!("{} is {} of {}", ip, e, network.network));
+    } else if let Some(e) = scopes
+        .iter()
+        .find(|x| x.network_id == network.id && x.range.contains(&ip))
+    {
+        check.fail(
+            "ip",
+            format!("{} belongs to {} of the scope {}", ip, e.range, e.id),
+        );
+    }
+    if let Some(e) = first(&mut seen.ips, (ip, network.id), line) {
+        check.fail("ip", format!("{} is a duplicate of line {}", ip, e));
+    }
+
+    let current = db
+        .get::<DeviceView>(Some(address_key(ip, network.id)))
+        .await
+        .ok()
+        .and_then(|mut x| x.pop());
+    if let Some(e) = current.as_ref().and_then(|x| x.device_id) {
+        check.fail("ip", format!("{} is used by {}", ip, e));
+    }
+    let from = current
+        .as_ref()
+        .map(|x| x.status.clone())
+        .unwrap_or_default();
+    if !transitions.allows(&from, &status) {
+        check.fail("status", format!("{:?} can't change to {:?}", from, status));
+    }
+
+    if let Some(hostname) = &hostname {
+        let fqdn = domain
+            .as_ref()
+            .map_or(hostname.to_string(), |x| x.fqdn(hostname));
+        if let Some(e) = first(&mut seen.names, fqdn.clone(), line) {
+            check.fail("hostname", format!("{} is a duplicate of line {}", fqdn, e));
+        }
+        let used = db
+            .get::<Device>(Some(HashMap::from([
+                ("hostname", hostname.clone().into()),
+                ("domain", domain.clone().map_or(TypeTable::Null, Into::into)),
+            ])))
+            .await
+            .unwrap_or_default();
+        if let Some(e) = used.first() {
+            check.fail("hostname", format!("{} is used by {}", fqdn, e.id));
+        }
+    }
+
+    if let Some(mac) = mac {
+        if let Some(e) = first(&mut seen.macs, (network.id, mac), line) {
+            check.fail("mac", format!("{} is a duplicate of line {}", mac, e));
+        }
+        let used = db
+            .get::<Address>(Some(HashMap::from([
+                ("network_id", network.id.into()),
+                ("mac", mac.into()),
+            ])))
+            .await
+            .unwrap_or_default();
+        if let Some(e) = used.iter().find(|x| x.ip != ip) {
+            check.fail("mac", format!("{} is used by {}", mac, e.ip));
+        }
+    }
+
+    let trashed = !db
+        .get_trash::<Address>(Some(address_key(ip, network.id)))
+        .await?
+        .is_empty();
+
+    Ok(Some(DeviceRow {
+        view: DeviceView {
+            ip,
+            network_id: network.id,
+            status,
+            device_id: Some(Uuid::new_v4()),
+            mac,
+            last_seen: None,
+            reserved_until: None,
+            hostname,
+            domain,
+            kind: check.text("kind"),
+            model: check.text("model"),
+            serial: check.text("serial"),
+            description: check.text("description"),
+            office_id,
+            rack: check.text("rack"),
+            room: check.text("room"),
+            credential: None,
+        },
+        current,
+        trashed,
+    }))
+}
+
+// Nothing is written on a dry run or when any row fails, otherwise every row
+// is written in a single transaction
+pub async fn devices(
+    db: &RepositoryInjection<Postgres>,
+    ddns: &Ddns,
+    transitions: &Transitions,
+    actor: Uuid,
+    rows: Vec<Row>,
+    dry_run: bool,
+) -> Result<ImportReport, RepositoryError> {
+    let networks = all::<Network>(db).await?;
+    let offices = all::<Office>(db).await?;
+    let scopes = all::<Scope>(db).await?;
+    let mut report = ImportReport::new(rows.len(), dry_run);
+    let mut seen = Seen::default();
+    let mut valid = Vec::new();
+
+    for row in &rows {
+        let mut check = Check::new(row);
+        let device = device_row(
+            db,
+            transitions,
+            &mut check,
+            &networks,
+            &offices,
+            &scopes,
+            &mut seen,
+        )
+        .await?;
+
+        match device {
+            Some(e) if check.errors.is_empty() => valid.push(e),
+            _ => report.errors.extend(check.errors),
+        }
+    }
+
+    report.valid = valid.len();
+    if !report.committed() {
+        return Ok(report);
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let mut tx = db.transaction().await?;
+    let (mut devices, mut addresses, mut history, mut audit) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut trashed = Vec::new();
+    let mut updated = Vec::new();
+
+    for i in &valid {
+        let key = address_key(i.view.ip, i.view.network_id);
+        if let Some(device) = i.view.device() {
+            audit.push(Audit::insert(Some(actor), &device));
+            devices.push(device);
+        }
+        if let Some((id, device)) = i.view.identity() {
+            history.push(IpHistory::open(
+                i.view.ip,
+                i.view.network_id,
+                id,
+                device,
+                now,
+            ));
+        }
+        match &i.current {
+            Some(before) => {
+                audit.push(Audit::update(Some(actor), before, &i.view));
+                updated.push((i.view.address(), key));
+            }
+            None => {
+                audit.push(Audit::insert(Some(actor), &i.view));
+                if i.trashed {
+                    trashed.push(key);
+                }
+                addresses.push(i.view.address());
+            }
+        }
+    }
+
+    // Devices go first since the addresses point to them
+    tx.insert(devices);
+    for key in trashed {
+        tx.delete::<Address>(Some(key));
+    }
+    for (address, key) in updated {
+        tx.update::<Address, _>(address, Some(key));
+    }
+    tx.insert(addresses);
+    tx.insert(history);
+    tx.insert(audit);
+    tx.execute().await?;
+
+    report.created = valid.len();
+    for i in &valid {
+        ddns::publish(db, ddns, i.current.as_ref(), Some(&i.view)).await;
+    }
+
+    Ok(report)
+}
+
+fn network_row(
+    check: &mut Check<'_>,
+    networks: &[Network],
+    seen: &mut HashMap<IpNet, usize>,
+) -> Option<Network> {
+    let line = check.row.line;
+    let prefix: Option<IpNet> = check.required("network");
+    let vlan: Option<Vlan> = check.optional("vlan");
+    let gateway_ip: Option<IpAddr> = check.optional("gateway");
+    let policy: GatewayPolicy = check.optional("gateway_policy").unwrap_or_default();
+    let infrastructure = check.addresses("infrastructure");
+
+    let prefix = prefix?;
+    // Host counts are kept for IPv4 only
+    if !matches!(prefix, IpNet::V4(_)) || !(1..=31).contains(&prefix.prefix_len()) {
+        check.fail(
+            "network",
+            format!("{} isn't an IPv4 network from /1 to /31", prefix),
+        );
+        return None;
+    }
+    if prefix != prefix.trunc() {
+        check.fail(
+            "network",
+            format!("{} has host bits set, use {}", prefix, prefix.trunc()),
+        );
+        return None;
+    }
+
+    if let Some(e) = first(seen, prefix, line) {
+        check.fail(
+            "network",
+            format!("{} is a duplicate of line {}", prefix, e),
+        );
+    }
+    if let Some(e) = networks.iter().find(|x| x.network == prefix) {
+        check.fail(
+            "network",
+            format!("{} is already registered as {}", prefix, e.id),
+        );
+    }
+
+    let network = Network::new(
+        prefix,
+        check.text("description"),
+        vlan,
+        gateway_ip.or(gateway(&prefix, policy)),
+        infrastructure,
+    );
+    for ip in network.designated() {
+        if !network.network.contains(&ip) || is_unusable(&network.network, &ip) {
+            let field = match Some(ip) == network.gateway {
+                true => "gateway",
+                false => "infrastructure",
+            };
+            check.fail(
+                field,
+                format!("{} isn't a usable address of {}", ip, network.network),
+            );
+        }
+    }
+
+    Some(network)
+}
+
+pub async fn networks(
+    db: &RepositoryInjection<Postgres>,
+    actor: Uuid,
+    rows: Vec<Row>,
+    dry_run: bool,
+) -> Result<ImportReport, RepositoryError> {
+    let networks = all::<Network>(db).await?;
+    let mut report = ImportReport::new(rows.len(), dry_run);
+    let mut seen = HashMap::new();
+    let mut valid = Vec::new();
+
+    for row in &rows {
+        let mut check = Check::new(row);
+        match network_row(&mut check, &networks, &mut seen) {
+            Some(e) if check.errors.is_empty() => valid.push(e),
+            _ => report.errors.extend(check.errors),
+        }
+    }
+
+    report.valid = valid.len();
+    if !report.committed() {
+        return Ok(report);
+    }
+
+    let audit = valid
+        .iter()
+        .map(|x| Audit::insert(Some(actor), x))
+        .collect();
+    report.created = valid.len();
+
+    let mut tx = db.transaction().await?;
+    tx.insert(valid);
+    tx.insert::<Audit>(audit);
+    tx.execute().await?;
+
+    Ok(report)
+}
@@ -0,0 +1,42 @@
+use crate::{
+    database::{repository::TypeTable, transaction::BuilderPgTransaction},
+    models::{
+        device::DeviceView,
+        ip_history::{CloseIpHistory, IpHistory},
+    },
+};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+// Closes the span of the previous holder and opens one for the new holder,
+// written as part of the caller's transaction
+pub fn record(
+    tx: &mut BuilderPgTransaction<'_>,
+    before: Option<&DeviceView>,
+    after: Option<&DeviceView>,
+) {
+    let now = OffsetDateTime::now_utc();
+    let before = before.and_then(|x| x.identity().map(|(id, e)| (x.ip, x.network_id, id, e)));
+    let after = after.and_then(|x| x.identity().map(|(id, e)| (x.ip, x.network_id, id, e)));
+
+    if before.as_ref().map(|x| (x.0, x.1, x.2)) == after.as_ref().map(|x| (x.0, x.1, x.2)) {
+        return;
+    }
+
+    if let Some((ip, network_id, _, _)) = before {
+        tx.update::<IpHistory, _>(
+            CloseIpHistory { to: now },
+            Some(HashMap::from([
+                ("ip", ip.into()),
+                ("network_id", network_id.into()),
+                ("assigned_to", TypeTable::Null),
+            ])),
+        );
+    }
+
+    if let Some((ip, network_id, device_id, device)) = after {
+        tx.insert(vec![IpHistory::open(
+            ip, network_id, device_id, device, now,
+        )]);
+    }
+}
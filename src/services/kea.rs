@@ -0,0 +1,78 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository, Table},
+        RepositoryInjection,
+    },
+    models::{dhcp::Scope, network::Network},
+};
+use libipam::ipam_services::kea::{Host, Pool, Subnet};
+use sqlx::Postgres;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+async fn all<T>(db: &RepositoryInjection<Postgres>) -> Result<Vec<T>, RepositoryError>
+where
+    T: Table + From<sqlx::postgres::PgRow> + std::fmt::Debug + Clone + Send,
+{
+    match db.get::<T>(None).await {
+        Err(RepositoryError::RowNotFound) => Ok(Vec::new()),
+        e => e,
+    }
+}
+
+// Kea wants a stable numeric id per subnet
+fn subnet_id(network_id: Uuid) -> u32 {
+    let bytes = network_id.as_bytes();
+    (u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) & 0x7fffffff).max(1)
+}
+
+fn subnet<'a>(
+    subnets: &'a mut BTreeMap<Uuid, Subnet>,
+    networks: &BTreeMap<Uuid, Network>,
+    network_id: Uuid,
+) -> Option<&'a mut Subnet> {
+    let network = networks.get(&network_id)?;
+    Some(subnets.entry(network_id).or_insert_with(|| Subnet {
+        id: subnet_id(network_id),
+        network: network.network,
+        pools: Vec::new(),
+        hosts: Vec::new(),
+    }))
+}
+
+pub async fn subnets(db: &RepositoryInjection<Postgres>) -> Result<Vec<Subnet>, RepositoryError> {
+    let networks: BTreeMap<Uuid, Network> = all::<Network>(db)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x))
+        .collect();
+    let mut subnets: BTreeMap<Uuid, Subnet> = BTreeMap::new();
+
+    let mut scopes = all::<Scope>(db).await?;
+    scopes.sort_by_key(|x| x.range.start);
+    for scope in scopes {
+        if let Some(e) = subnet(&mut subnets, &networks, scope.network_id) {
+            e.pools.push(Pool {
+                range: scope.range,
+                gateway: scope.gateway,
+                dns_servers: scope.dns_servers,
+                lease_time: scope.lease_time as u32,
+            });
+        }
+    }
+
+    for reservation in db.get_reservations(None).await? {
+        if let Some(e) = subnet(&mut subnets, &networks, reservation.network_id) {
+            e.hosts.push(Host {
+                mac: reservation.mac,
+                ip: reservation.ip,
+                hostname: reservation.hostname.map(|x| match &reservation.domain {
+                    Some(domain) => domain.fqdn(&x),
+                    None => x.to_string(),
+                }),
+            });
+        }
+    }
+
+    Ok(subnets.into_values().collect())
+}
@@ -0,0 +1,227 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository, TypeTable},
+        RepositoryInjection,
+    },
+    models::{
+        audit::Audit,
+        device::{Address, Device, DeviceView, Status},
+        dhcp::{LeaseConflict, LeaseReport},
+        network::Network,
+    },
+    services::ip_history,
+};
+use libipam::{
+    ipam_services::leases::Lease,
+    type_net::dns::{DomainName, Hostname},
+};
+use sqlx::Postgres;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub(super) async fn name_is_free(
+    db: &RepositoryInjection<Postgres>,
+    device: &Device,
+) -> Result<bool, RepositoryError> {
+    let Some(hostname) = &device.hostname else {
+        return Ok(true);
+    };
+
+    let used = match db
+        .get::<Device>(Some(HashMap::from([
+            ("hostname", hostname.clone().into()),
+            (
+                "domain",
+                device.domain.clone().map_or(TypeTable::Null, Into::into),
+            ),
+        ])))
+        .await
+    {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    Ok(used.iter().all(|x| x.id == device.id))
+}
+
+// Reads don't see the queued writes, a name taken earlier in the same
+// import is only known here
+fn claim(names: &mut HashMap<(Hostname, Option<DomainName>), Uuid>, device: &Device) -> bool {
+    let Some(hostname) = &device.hostname else {
+        return true;
+    };
+
+    let owner = names
+        .entry((hostname.clone(), device.domain.clone()))
+        .or_insert(device.id);
+    *owner == device.id
+}
+
+// Leases only carry a name, the domain of the device is kept
+fn hostname(lease: &Lease) -> Option<Hostname> {
+    lease.hostname.as_ref()?.split('.').next()?.parse().ok()
+}
+
+pub async fn import(
+    db: &RepositoryInjection<Postgres>,
+    actor: Uuid,
+    leases: Vec<Lease>,
+) -> Result<LeaseReport, RepositoryError> {
+    let now = OffsetDateTime::now_utc();
+    let mut report = LeaseReport::default();
+    let networks = match db.get::<Network>(None).await {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    let mut tx = db.transaction().await?;
+    let mut audit = Vec::new();
+    let (mut names, mut macs, mut renamed) = (HashMap::new(), HashMap::new(), HashMap::new());
+
+    for lease in leases {
+        if !lease.is_current(now) {
+            report.expired += 1;
+            continue;
+        }
+
+        let Some(network) = networks
+            .iter()
+            .filter(|x| x.network.contains(&lease.ip))
+            .max_by_key(|x| x.network.prefix_len())
+        else {
+            report.unmatched.push(lease.ip);
+            continue;
+        };
+        let key = || HashMap::from([("ip", lease.ip.into()), ("network_id", network.id.into())]);
+        let mut conflict = |reason: &str| {
+            report.conflicts.push(LeaseConflict {
+                ip: lease.ip,
+                mac: lease.mac,
+                hostname: lease.hostname.clone(),
+                reason: reason.to_string(),
+            })
+        };
+
+        if let Some(e) = network.designation(&lease.ip) {
+            conflict(&format!("The address is {} of the network", e));
+            continue;
+        }
+
+        if !db.get_trash::<Address>(Some(key())).await?.is_empty() {
+            tx.delete::<Address>(Some(key()));
+        }
+        let current = db
+            .get::<DeviceView>(Some(key()))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop());
+
+        match &current {
+            Some(e) if e.status == Status::Reserved => {
+                conflict("The address is reserved");
+                continue;
+            }
+            Some(e) if e.device_id.is_some() && e.mac.is_some() && e.mac != lease.mac => {
+                conflict("The address belongs to another device");
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(mac) = lease.mac {
+            let used = db
+                .get::<Address>(Some(HashMap::from([
+                    ("network_id", network.id.into()),
+                    ("mac", mac.into()),
+                ])))
+                .await
+                .unwrap_or_default();
+            if used.iter().any(|x| x.ip != lease.ip)
+                || macs.get(&(network.id, mac)).is_some_and(|x| *x != lease.ip)
+            {
+                conflict("The mac address is used by another address");
+                continue;
+            }
+            macs.insert((network.id, mac), lease.ip);
+        }
+
+        let device = match current
+            .as_ref()
+            .and_then(DeviceView::device)
+            .map(|x| renamed.get(&x.id).cloned().unwrap_or(x))
+        {
+            Some(before) if before.hostname.is_none() && hostname(&lease).is_some() => {
+                let after = Device {
+                    hostname: hostname(&lease),
+                    ..before.clone()
+                };
+                if name_is_free(db, &after).await? && claim(&mut names, &after) {
+                    tx.update::<Device, _>(
+                        after.clone(),
+                        Some(HashMap::from([("id", after.id.into())])),
+                    );
+                    audit.push(Audit::update(Some(actor), &before, &after));
+                    renamed.insert(after.id, after.clone());
+                    after
+                } else {
+                    before
+                }
+            }
+            Some(e) => e,
+            None => {
+                let mut device = Device {
+                    id: Uuid::new_v4(),
+                    hostname: hostname(&lease),
+                    domain: None,
+                    kind: None,
+                    model: None,
+                    serial: None,
+                    description: None,
+                    office_id: None,
+                    rack: None,
+                    room: None,
+                    credential: None,
+                };
+                if !(name_is_free(db, &device).await? && claim(&mut names, &device)) {
+                    device.hostname = None;
+                }
+                tx.insert(vec![device.clone()]);
+                audit.push(Audit::insert(Some(actor), &device));
+                device
+            }
+        };
+
+        let address = Address {
+            ip: lease.ip,
+            network_id: network.id,
+            device_id: Some(device.id),
+            status: Status::Online,
+            mac: lease.mac.or(current.as_ref().and_then(|x| x.mac)),
+            last_seen: lease
+                .seen
+                .max(current.as_ref().and_then(|x| x.last_seen))
+                .or(Some(now)),
+            reserved_until: None,
+        };
+        let after = DeviceView::new(address.clone(), Some(device));
+
+        match &current {
+            Some(before) => {
+                tx.update::<Address, _>(address, Some(key()));
+                audit.push(Audit::update(Some(actor), before, &after));
+                report.updated += 1;
+            }
+            None => {
+                tx.insert(vec![address]);
+                audit.push(Audit::insert(Some(actor), &after));
+                report.created += 1;
+            }
+        }
+        ip_history::record(&mut tx, current.as_ref(), Some(&after));
+    }
+    tx.insert(audit);
+    tx.execute().await?;
+
+    Ok(report)
+}
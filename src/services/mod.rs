@@ -1,12 +1,29 @@
+pub mod conflicts;
+pub mod ddns;
+pub mod dns;
+pub mod events;
+pub mod import;
+pub mod ip_history;
+pub mod kea;
+pub mod leases;
+pub mod neighbors;
+pub mod oui;
+pub mod probe;
+pub mod scanner;
+pub mod snmp;
+pub mod status;
+pub mod trash;
+pub mod webhooks;
+
 use crate::{
     database::repository::{error::RepositoryError, Repository},
-    models::user::*,
+    models::{audit::Audit, user::*},
 };
 use libipam::authentication::{encrypt, Claim};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, env, time::Duration};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub exp: usize,
     pub id: uuid::Uuid,
@@ -15,6 +32,18 @@ pub struct Claims {
 
 impl Claim for Claims {}
 
+// Seconds between runs of a background task, tokio's interval panics on zero
+pub fn interval(name: &str, default: u64) -> Result<Duration, Box<dyn std::error::Error>> {
+    match env::var(name)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(default)
+    {
+        0 => Err(format!("{} must be at least 1 second", name).into()),
+        e => Ok(Duration::from_secs(e)),
+    }
+}
+
 pub async fn create_default_user(db: &impl Repository) -> Result<(), RepositoryError> {
     if db
         .get::<User>(Some(HashMap::from([("role", Role::Admin.into())])))
@@ -31,7 +60,9 @@ pub async fn create_default_user(db: &impl Repository) -> Result<(), RepositoryE
         role: Role::Admin,
     };
 
+    let audit = Audit::insert(None, &user);
     db.insert::<User>(vec![user]).await?;
+    db.insert(vec![audit]).await?;
     Ok(())
 }
 
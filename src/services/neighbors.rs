@@ -0,0 +1,137 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository},
+        RepositoryInjection,
+    },
+    models::{
+        audit::Audit,
+        device::{Address, DeviceView, NeighborConflict, NeighborReport, Status},
+        network::Network,
+    },
+    services::{ip_history, scanner::observed, status::Transitions},
+};
+use libipam::{ipam_services::neighbors::Neighbor, type_net::mac::MacAddr};
+use sqlx::Postgres;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// An entry in a neighbor table proves the host answered recently even if it
+// drops pings. Only registered addresses are updated, an address seen with
+// several macs is reported instead
+pub async fn import(
+    db: &RepositoryInjection<Postgres>,
+    actor: Option<Uuid>,
+    neighbors: Vec<Neighbor>,
+    transitions: &Transitions,
+) -> Result<NeighborReport, RepositoryError> {
+    let now = OffsetDateTime::now_utc();
+    let mut report = NeighborReport::default();
+    let networks = match db.get::<Network>(None).await {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+
+    let mut seen: BTreeMap<IpAddr, Vec<MacAddr>> = BTreeMap::new();
+    for neighbor in neighbors {
+        let macs = seen.entry(neighbor.ip).or_default();
+        if !macs.contains(&neighbor.mac) {
+            macs.push(neighbor.mac);
+        }
+    }
+
+    let mut tx = db.transaction().await?;
+    let mut audit = Vec::new();
+    // Reads don't see the queued writes, a mac given to an earlier address is
+    // only known here
+    let mut taken = HashMap::new();
+
+    for (ip, macs) in seen {
+        let mut conflict = |macs: Vec<MacAddr>, reason: &str| {
+            report.conflicts.push(NeighborConflict {
+                ip,
+                macs,
+                reason: reason.to_string(),
+            })
+        };
+        let &[mac] = macs.as_slice() else {
+            conflict(macs, "The address is answered by several macs");
+            continue;
+        };
+
+        let Some(network) = networks
+            .iter()
+            .filter(|x| x.network.contains(&ip))
+            .max_by_key(|x| x.network.prefix_len())
+        else {
+            report.unmatched.push(ip);
+            continue;
+        };
+        let key = || HashMap::from([("ip", ip.into()), ("network_id", network.id.into())]);
+
+        let Some(before) = db
+            .get::<DeviceView>(Some(key()))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop())
+        else {
+            report.unregistered.push(ip);
+            continue;
+        };
+        if before.device_id.is_none() {
+            report.unregistered.push(ip);
+        }
+
+        match before.mac {
+            Some(e) if e != mac => {
+                conflict(vec![e, mac], "The address is registered with another mac");
+                continue;
+            }
+            Some(_) => {}
+            None => {
+                let used = db
+                    .get::<Address>(Some(HashMap::from([
+                        ("network_id", network.id.into()),
+                        ("mac", mac.into()),
+                    ])))
+                    .await
+                    .unwrap_or_default();
+                let owner = used
+                    .iter()
+                    .map(|x| x.ip)
+                    .chain(taken.get(&(network.id, mac)).copied())
+                    .find(|x| *x != ip);
+                if let Some(e) = owner {
+                    conflict(vec![mac], &format!("The mac address is used by {}", e));
+                    continue;
+                }
+                taken.insert((network.id, mac), ip);
+            }
+        }
+
+        let address = Address {
+            status: observed(&before, true, transitions),
+            mac: Some(mac),
+            last_seen: Some(now),
+            ..before.address()
+        };
+        if address.status == Status::Online {
+            report.online += 1;
+        }
+        tx.update::<Address, _>(address.clone(), Some(key()));
+        report.updated += 1;
+
+        if address.status != before.status || address.mac != before.mac {
+            let after = DeviceView::new(address, before.device());
+            audit.push(Audit::update(actor, &before, &after));
+            ip_history::record(&mut tx, Some(&before), Some(&after));
+        }
+    }
+    tx.insert(audit);
+    tx.execute().await?;
+
+    Ok(report)
+}
@@ -0,0 +1,75 @@
+use crate::models::device::{Address, DeviceView, WithVendor};
+use libipam::{ipam_services::oui::OuiDatabase, type_net::mac::MacAddr};
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+
+pub trait Mac {
+    fn mac(&self) -> Option<MacAddr>;
+}
+
+impl Mac for DeviceView {
+    fn mac(&self) -> Option<MacAddr> {
+        self.mac
+    }
+}
+
+impl Mac for Address {
+    fn mac(&self) -> Option<MacAddr> {
+        self.mac
+    }
+}
+
+pub struct Oui {
+    path: PathBuf,
+    database: RwLock<OuiDatabase>,
+}
+
+async fn read(path: &Path) -> std::io::Result<OuiDatabase> {
+    Ok(OuiDatabase::parse(&tokio::fs::read_to_string(path).await?))
+}
+
+impl Oui {
+    pub async fn load(path: PathBuf) -> Self {
+        let database = match read(&path).await {
+            Ok(e) => {
+                tracing::info!("OUI database: {} vendors loaded", e.len());
+                e
+            }
+            Err(e) => {
+                tracing::warn!("OUI database {}: {}", path.display(), e);
+                OuiDatabase::default()
+            }
+        };
+
+        Self {
+            path,
+            database: RwLock::new(database),
+        }
+    }
+
+    pub async fn reload(&self) -> std::io::Result<usize> {
+        let database = read(&self.path).await?;
+        let len = database.len();
+        *self.database.write().await = database;
+
+        Ok(len)
+    }
+
+    pub async fn lookup(&self, mac: &MacAddr) -> Option<String> {
+        self.database.read().await.lookup(mac).map(str::to_string)
+    }
+
+    pub async fn enrich<T: Mac>(&self, data: Vec<T>) -> Vec<WithVendor<T>> {
+        let database = self.database.read().await;
+
+        data.into_iter()
+            .map(|x| WithVendor {
+                vendor: x
+                    .mac()
+                    .and_then(|mac| database.lookup(&mac))
+                    .map(str::to_string),
+                data: x,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository, TypeTable},
+        RepositoryInjection,
+    },
+    models::device::{DeviceView, Service},
+};
+use libipam::{
+    ipam_services::probe::{open_ports, service, DEFAULT_PORTS},
+    type_net::port::Port,
+};
+use sqlx::Postgres;
+use std::{collections::HashMap, env, sync::Arc, time::Duration};
+use time::OffsetDateTime;
+
+#[derive(Debug)]
+pub struct Settings {
+    pub ports: Vec<Port>,
+    pub timeout: Duration,
+    pub concurrency: usize,
+}
+
+// PROBE_PORTS is a comma separated list like 22,80,443
+pub fn settings() -> Result<Arc<Settings>, Box<dyn std::error::Error>> {
+    let ports = match env::var("PROBE_PORTS").ok().filter(|x| !x.is_empty()) {
+        Some(e) => e
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Port>, _>>()?,
+        None => DEFAULT_PORTS.into_iter().map(Port::new).collect(),
+    };
+
+    Ok(Arc::new(Settings {
+        ports,
+        timeout: Duration::from_millis(
+            env::var("PROBE_TIMEOUT_MS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(1000),
+        ),
+        concurrency: env::var("PROBE_CONCURRENCY")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(32),
+    }))
+}
+
+pub async fn probe(view: &DeviceView, ports: &[Port], settings: &Settings) -> Vec<Port> {
+    open_ports(view.ip, ports, settings.timeout, settings.concurrency).await
+}
+
+// The probed ports of the address are replaced by the ones found open
+pub async fn store(
+    db: &RepositoryInjection<Postgres>,
+    view: &DeviceView,
+    ports: &[Port],
+    open: Vec<Port>,
+) -> Result<Vec<Service>, RepositoryError> {
+    let Some(device_id) = view.device_id else {
+        return Ok(Vec::new());
+    };
+    let now = OffsetDateTime::now_utc();
+
+    for port in ports {
+        let condition: HashMap<&str, TypeTable> = HashMap::from([
+            ("device_id", device_id.into()),
+            ("ip", view.ip.into()),
+            ("network_id", view.network_id.into()),
+            ("port", (*port).into()),
+        ]);
+        db.delete::<Service>(Some(condition)).await?;
+    }
+
+    let services: Vec<Service> = open
+        .into_iter()
+        .map(|port| Service {
+            device_id,
+            ip: view.ip,
+            network_id: view.network_id,
+            port,
+            name: service(&port).map(str::to_string),
+            seen: now,
+        })
+        .collect();
+    if !services.is_empty() {
+        db.insert(services.clone()).await?;
+    }
+
+    Ok(services)
+}
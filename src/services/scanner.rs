@@ -0,0 +1,406 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository, TypeTable},
+        RepositoryInjection,
+    },
+    models::{
+        audit::Audit,
+        device::{Address, DeviceView, Status},
+        network::Network,
+        scan::{JobState, ScanJob, ScanReport, ScanResult, Schedule},
+    },
+    services::status::Transitions,
+};
+use futures::{stream, StreamExt};
+use libipam::ipam_services::{icmp::Pinger, ping, Ping};
+use sqlx::Postgres;
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
+use time::OffsetDateTime;
+use tokio::sync::{watch, Mutex};
+use uuid::Uuid;
+
+type Db = Arc<Mutex<RepositoryInjection<Postgres>>>;
+
+// The pinger's receivers run on the runtime that created it, so it's made
+// once at startup; without ICMP sockets the system ping is used
+#[derive(Clone)]
+pub struct Settings {
+    pub concurrency: usize,
+    pub timeout_ms: u64,
+    pub pinger: Option<Arc<Pinger>>,
+}
+
+pub async fn run(
+    db: Db,
+    every: std::time::Duration,
+    settings: Settings,
+    transitions: Arc<Transitions>,
+) {
+    let mut interval = tokio::time::interval(every);
+
+    loop {
+        interval.tick().await;
+        let now = OffsetDateTime::now_utc();
+        let due = match db.lock().await.get::<Schedule>(None).await {
+            Ok(e) => e,
+            Err(RepositoryError::RowNotFound) => continue,
+            Err(e) => {
+                tracing::error!("Scanner: {}", e);
+                continue;
+            }
+        };
+
+        for schedule in due.into_iter().filter(|x| x.is_due(now)) {
+            match sweep(&db, schedule.network_id, &settings, &transitions).await {
+                Ok(e) => tracing::info!(
+                    "Scanner: {} answered {}/{}",
+                    schedule.network_id,
+                    e.alive.len(),
+                    e.scanned
+                ),
+                Err(e) => tracing::error!("Scanner: {}: {}", schedule.network_id, e),
+            }
+        }
+    }
+}
+
+// Only assigned addresses in a liveness state change their status, and only
+// when the transitions allow it. The rest just record when they were seen
+pub(super) fn observed(view: &DeviceView, alive: bool, transitions: &Transitions) -> Status {
+    let status = match (&view.status, alive) {
+        _ if view.device_id.is_none() => return view.status.clone(),
+        (Status::Unknown | Status::Allocated | Status::Online | Status::Offline, true) => {
+            Status::Online
+        }
+        (Status::Online, false) => Status::Offline,
+        (status, _) => status.clone(),
+    };
+
+    if transitions.allows(&view.status, &status) {
+        status
+    } else {
+        view.status.clone()
+    }
+}
+
+async fn addresses(
+    db: &RepositoryInjection<Postgres>,
+    network_id: Uuid,
+) -> Result<Vec<DeviceView>, RepositoryError> {
+    match db
+        .get::<DeviceView>(Some(HashMap::from([("network_id", network_id.into())])))
+        .await
+    {
+        Err(RepositoryError::RowNotFound) => Ok(Vec::new()),
+        e => e,
+    }
+}
+
+async fn probe(
+    targets: Vec<IpAddr>,
+    settings: &Settings,
+    progress: impl Fn(bool),
+) -> HashMap<IpAddr, bool> {
+    let timeout = std::time::Duration::from_millis(settings.timeout_ms);
+
+    stream::iter(targets)
+        .map(|ip| async move {
+            let alive = match &settings.pinger {
+                Some(pinger) => pinger.ping(ip, timeout).await.is_ok(),
+                None => ping(ip, settings.timeout_ms).await == Ping::Pong,
+            };
+            (ip, alive)
+        })
+        .buffer_unordered(settings.concurrency.max(1))
+        .inspect(|x| progress(x.1))
+        .collect()
+        .await
+}
+
+// The lock is released while pinging, the addresses are read again before
+// writing so concurrent edits aren't overwritten
+pub async fn sweep(
+    db: &Db,
+    network_id: Uuid,
+    settings: &Settings,
+    transitions: &Transitions,
+) -> Result<ScanResult, RepositoryError> {
+    let started_at = OffsetDateTime::now_utc();
+    let targets = addresses(&*db.lock().await, network_id)
+        .await?
+        .iter()
+        .map(|x| x.ip)
+        .collect();
+    let replies = probe(targets, settings, |_| {}).await;
+
+    apply(
+        &*db.lock().await,
+        network_id,
+        &replies,
+        started_at,
+        transitions,
+    )
+    .await
+}
+
+async fn apply(
+    db: &RepositoryInjection<Postgres>,
+    network_id: Uuid,
+    replies: &HashMap<IpAddr, bool>,
+    started_at: OffsetDateTime,
+    transitions: &Transitions,
+) -> Result<ScanResult, RepositoryError> {
+    let finished_at = OffsetDateTime::now_utc();
+    let mut audit = Vec::new();
+    let mut result = ScanResult {
+        id: Uuid::new_v4(),
+        network_id,
+        started_at,
+        finished_at,
+        scanned: replies.len() as i64,
+        online: 0,
+        offline: 0,
+        alive: replies.iter().filter(|x| *x.1).map(|x| *x.0).collect(),
+    };
+    result.alive.sort();
+
+    for before in addresses(db, network_id).await? {
+        let Some(&alive) = replies.get(&before.ip) else {
+            continue;
+        };
+        let address = Address {
+            status: observed(&before, alive, transitions),
+            last_seen: if alive {
+                Some(finished_at)
+            } else {
+                before.last_seen
+            },
+            ..before.address()
+        };
+
+        match address.status {
+            Status::Online => result.online += 1,
+            Status::Offline => result.offline += 1,
+            _ => {}
+        }
+        if address.status == before.status && address.last_seen == before.last_seen {
+            continue;
+        }
+
+        let key: HashMap<&str, TypeTable> =
+            HashMap::from([("ip", before.ip.into()), ("network_id", network_id.into())]);
+        if address.status != before.status {
+            audit.push(Audit::update(
+                None,
+                &before,
+                &DeviceView::new(address.clone(), before.device()),
+            ));
+        }
+        db.update::<Address, _>(address, Some(key)).await?;
+    }
+
+    if !audit.is_empty() {
+        db.insert(audit).await?;
+    }
+    db.insert(vec![result.clone()]).await?;
+    let key = || HashMap::from([("network_id", network_id.into())]);
+    if let Some(mut schedule) = db
+        .get::<Schedule>(Some(key()))
+        .await
+        .ok()
+        .and_then(|mut x| x.pop())
+    {
+        schedule.last_run = Some(started_at);
+        db.update::<Schedule, _>(schedule, Some(key())).await?;
+    }
+
+    Ok(result)
+}
+
+pub type Jobs = Arc<std::sync::Mutex<HashMap<Uuid, watch::Receiver<ScanJob>>>>;
+
+const JOB_RETENTION: time::Duration = time::Duration::hours(1);
+
+pub fn running(jobs: &Jobs, network_id: Uuid) -> Option<Uuid> {
+    jobs.lock()
+        .unwrap()
+        .values()
+        .map(|x| x.borrow())
+        .find(|x| x.network_id == network_id && x.state == JobState::Running)
+        .map(|x| x.id)
+}
+
+// Pings every host of the network, finished jobs are kept for an hour
+pub fn start(
+    db: Db,
+    jobs: &Jobs,
+    network: &Network,
+    settings: Settings,
+    transitions: Arc<Transitions>,
+) -> ScanJob {
+    let targets: Vec<IpAddr> = network.network.hosts().collect();
+    let job = ScanJob::new(network.id, targets.len());
+    let (tx, rx) = watch::channel(job.clone());
+
+    let mut registry = jobs.lock().unwrap();
+    let now = OffsetDateTime::now_utc();
+    registry.retain(|_, x| {
+        x.borrow()
+            .finished_at
+            .is_none_or(|x| x + JOB_RETENTION > now)
+    });
+    registry.insert(job.id, rx);
+
+    let (network_id, started_at) = (network.id, job.started_at);
+    tokio::spawn(async move {
+        let replies = probe(targets, &settings, |alive| {
+            tx.send_modify(|x| {
+                x.done += 1;
+                x.alive += alive as usize;
+            })
+        })
+        .await;
+        let report = report(&db, network_id, &replies, started_at, &transitions).await;
+
+        tx.send_modify(|x| {
+            x.finished_at = Some(OffsetDateTime::now_utc());
+            match report {
+                Ok(e) => {
+                    x.state = JobState::Done;
+                    x.report = Some(e);
+                }
+                Err(e) => {
+                    tracing::error!("Scan {}: {}", x.id, e);
+                    x.state = JobState::Failed;
+                    x.error = Some(e.to_string());
+                }
+            }
+        });
+    });
+
+    job
+}
+
+async fn report(
+    db: &Db,
+    network_id: Uuid,
+    replies: &HashMap<IpAddr, bool>,
+    started_at: OffsetDateTime,
+    transitions: &Transitions,
+) -> Result<ScanReport, RepositoryError> {
+    let db = db.lock().await;
+    let registered: HashMap<IpAddr, DeviceView> = addresses(&db, network_id)
+        .await?
+        .into_iter()
+        .filter(|x| x.device_id.is_some())
+        .map(|x| (x.ip, x))
+        .collect();
+    let result = apply(&db, network_id, replies, started_at, transitions).await?;
+
+    let rogue = result
+        .alive
+        .iter()
+        .filter(|x| !registered.contains_key(x))
+        .copied()
+        .collect();
+    let mut silent: Vec<DeviceView> = registered
+        .into_values()
+        .filter(|x| replies.get(&x.ip) == Some(&false))
+        .collect();
+    silent.sort_by_key(|x| x.ip);
+
+    Ok(ScanReport {
+        result,
+        rogue,
+        silent,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{observed, Address, DeviceView, Status, Transitions};
+    use uuid::Uuid;
+
+    fn view(status: Status, assigned: bool) -> DeviceView {
+        let address = Address {
+            status,
+            ..Address::free("10.0.0.10".parse().unwrap(), Uuid::new_v4())
+        };
+
+        DeviceView {
+            device_id: assigned.then(Uuid::new_v4),
+            ..DeviceView::new(address, None)
+        }
+    }
+
+    #[test]
+    fn observed_alive_goes_online() {
+        let transitions = Transitions::default();
+
+        for status in [
+            Status::Unknown,
+            Status::Allocated,
+            Status::Online,
+            Status::Offline,
+        ] {
+            assert_eq!(
+                observed(&view(status, true), true, &transitions),
+                Status::Online
+            );
+        }
+    }
+
+    #[test]
+    fn observed_silent_online_goes_offline() {
+        let transitions = Transitions::default();
+
+        assert_eq!(
+            observed(&view(Status::Online, true), false, &transitions),
+            Status::Offline
+        );
+        assert_eq!(
+            observed(&view(Status::Allocated, true), false, &transitions),
+            Status::Allocated
+        );
+    }
+
+    #[test]
+    fn observed_keeps_other_statuses() {
+        let transitions = Transitions::default();
+
+        for status in [
+            Status::Reserved,
+            Status::Deprecated,
+            Status::Quarantined,
+            Status::Dhcp,
+        ] {
+            assert_eq!(
+                observed(&view(status.clone(), true), true, &transitions),
+                status
+            );
+        }
+        assert_eq!(
+            observed(&view(Status::Unknown, false), true, &transitions),
+            Status::Unknown
+        );
+    }
+
+    #[test]
+    fn observed_follows_transitions() {
+        let transitions: Transitions =
+            serde_json::from_str(r#"{"Unknown": ["Online"], "Online": []}"#).unwrap();
+
+        assert_eq!(
+            observed(&view(Status::Unknown, true), true, &transitions),
+            Status::Online
+        );
+        assert_eq!(
+            observed(&view(Status::Allocated, true), true, &transitions),
+            Status::Allocated
+        );
+        assert_eq!(
+            observed(&view(Status::Online, true), false, &transitions),
+            Status::Online
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository},
+        RepositoryInjection,
+    },
+    models::{
+        audit::Audit,
+        device::{Address, Credential, Device, DeviceView, SnmpConflict, SnmpReport, Status},
+        network::Network,
+    },
+    services::{ip_history, leases::name_is_free},
+};
+use libipam::{
+    ipam_services::{
+        neighbors::Neighbor,
+        snmp::{self, AuthProtocol, Security, Session, SnmpError, System},
+    },
+    type_net::dns::Hostname,
+};
+use sqlx::Postgres;
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub struct Settings {
+    pub port: u16,
+    pub timeout: Duration,
+    pub auth: AuthProtocol,
+}
+
+// SNMP_AUTH is the SNMPv3 auth protocol: md5, sha1 or sha256
+pub fn settings() -> Result<Arc<Settings>, Box<dyn std::error::Error>> {
+    Ok(Arc::new(Settings {
+        port: env::var("SNMP_PORT")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(161),
+        timeout: Duration::from_millis(
+            env::var("SNMP_TIMEOUT_MS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(2000),
+        ),
+        auth: match env::var("SNMP_AUTH").ok().filter(|x| !x.is_empty()) {
+            Some(e) => e.parse()?,
+            None => AuthProtocol::Sha1,
+        },
+    }))
+}
+
+// Without username the password is a v2c community, otherwise it
+// authenticates a v3 user
+pub fn security(credential: &Credential, auth: AuthProtocol) -> Security {
+    match credential.username.is_empty() {
+        true => Security::Community(credential.password.clone()),
+        false => Security::Usm {
+            user: credential.username.clone(),
+            auth: (!credential.password.is_empty()).then(|| (auth, credential.password.clone())),
+        },
+    }
+}
+
+pub async fn poll(
+    ip: IpAddr,
+    credential: &Credential,
+    settings: &Settings,
+) -> Result<System, SnmpError> {
+    let mut session = Session::connect(
+        SocketAddr::new(ip, settings.port),
+        security(credential, settings.auth),
+        settings.timeout,
+    )
+    .await?;
+
+    snmp::poll(&mut session).await
+}
+
+pub async fn neighbors(
+    ip: IpAddr,
+    credential: &Credential,
+    settings: &Settings,
+) -> Result<Vec<Neighbor>, SnmpError> {
+    let mut session = Session::connect(
+        SocketAddr::new(ip, settings.port),
+        security(credential, settings.auth),
+        settings.timeout,
+    )
+    .await?;
+
+    snmp::neighbors(&mut session).await
+}
+
+fn refused(status: &Status) -> Option<&'static str> {
+    match status {
+        Status::Reserved => Some("The address is reserved"),
+        Status::Quarantined => Some("The address is quarantined"),
+        Status::Deprecated => Some("The address is deprecated"),
+        _ => None,
+    }
+}
+
+// sysName may be a fqdn, the domain of the device is kept
+fn hostname(system: &System) -> Option<Hostname> {
+    system.name.as_ref()?.split('.').next()?.parse().ok()
+}
+
+// Addresses the agent reports are assigned to the device in the most
+// specific network containing them
+pub async fn apply(
+    db: &RepositoryInjection<Postgres>,
+    actor: Option<Uuid>,
+    view: &DeviceView,
+    mut device: Device,
+    system: System,
+) -> Result<SnmpReport, RepositoryError> {
+    let mut tx = db.transaction().await?;
+    let mut audit = Vec::new();
+    let mut report = SnmpReport {
+        device_id: device.id,
+        name: system.name.clone(),
+        description: system.description.clone(),
+        hostname: device.hostname.clone(),
+        interfaces: system.interfaces.clone(),
+        created: 0,
+        updated: 0,
+        unmatched: Vec::new(),
+        conflicts: Vec::new(),
+    };
+
+    if let Some(hostname) = hostname(&system).filter(|x| Some(x) != device.hostname.as_ref()) {
+        let after = Device {
+            hostname: Some(hostname.clone()),
+            ..device.clone()
+        };
+        if name_is_free(db, &after).await? {
+            tx.update::<Device, _>(
+                after.clone(),
+                Some(HashMap::from([("id", after.id.into())])),
+            );
+            audit.push(Audit::update(actor, &device, &after));
+            report.hostname = after.hostname.clone();
+            device = after;
+        } else {
+            report.conflicts.push(SnmpConflict {
+                ip: view.ip,
+                reason: format!("The hostname {} is used by another device", hostname),
+            });
+        }
+    }
+
+    let networks = match db.get::<Network>(None).await {
+        Err(RepositoryError::RowNotFound) => Vec::new(),
+        e => e?,
+    };
+    // Reads don't see the queued writes, a mac taken by an earlier address
+    // of the device is only known here
+    let mut macs = HashMap::new();
+
+    for found in &system.addresses {
+        let Some(network) = networks
+            .iter()
+            .filter(|x| x.network.contains(&found.ip))
+            .max_by_key(|x| x.network.prefix_len())
+        else {
+            report.unmatched.push(found.ip);
+            continue;
+        };
+        let key = || HashMap::from([("ip", found.ip.into()), ("network_id", network.id.into())]);
+        let mut conflict = |reason: &str| {
+            report.conflicts.push(SnmpConflict {
+                ip: found.ip,
+                reason: reason.to_string(),
+            })
+        };
+
+        if let Some(e) = network.designation(&found.ip) {
+            conflict(&format!("The address is {} of the network", e));
+            continue;
+        }
+
+        if !db.get_trash::<Address>(Some(key())).await?.is_empty() {
+            tx.delete::<Address>(Some(key()));
+        }
+        let current = db
+            .get::<DeviceView>(Some(key()))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop());
+
+        let mac = match system.interface(found.index).and_then(|x| x.mac) {
+            Some(mac) => {
+                let used = db
+                    .get::<Address>(Some(HashMap::from([
+                        ("network_id", network.id.into()),
+                        ("mac", mac.into()),
+                    ])))
+                    .await
+                    .unwrap_or_default();
+                let taken = macs.get(&(network.id, mac)).is_some_and(|x| *x != found.ip);
+                (!taken && !used.iter().any(|x| x.ip != found.ip)).then_some(mac)
+            }
+            None => None,
+        };
+
+        match &current {
+            Some(e) if e.device_id == Some(device.id) && (e.mac.is_some() || mac.is_none()) => {
+                continue
+            }
+            Some(e) if e.device_id.is_some_and(|x| x != device.id) => {
+                conflict("The address belongs to another device");
+                continue;
+            }
+            Some(e) if e.device_id.is_none() && refused(&e.status).is_some() => {
+                conflict(refused(&e.status).unwrap());
+                continue;
+            }
+            _ => {}
+        }
+
+        let address = Address {
+            ip: found.ip,
+            network_id: network.id,
+            device_id: Some(device.id),
+            status: match current.as_ref().map(|x| &x.status) {
+                None | Some(Status::Unknown) => Status::Allocated,
+                Some(e) => e.clone(),
+            },
+            mac: current.as_ref().and_then(|x| x.mac).or(mac),
+            last_seen: current.as_ref().and_then(|x| x.last_seen),
+            reserved_until: None,
+        };
+        if let Some(e) = address.mac {
+            macs.insert((network.id, e), found.ip);
+        }
+        let after = DeviceView::new(address.clone(), Some(device.clone()));
+
+        match &current {
+            Some(before) => {
+                tx.update::<Address, _>(address, Some(key()));
+                audit.push(Audit::update(actor, before, &after));
+                report.updated += 1;
+            }
+            None => {
+                tx.insert(vec![address]);
+                audit.push(Audit::insert(actor, &after));
+                report.created += 1;
+            }
+        }
+        ip_history::record(&mut tx, current.as_ref(), Some(&after));
+    }
+    tx.insert(audit);
+    tx.execute().await?;
+
+    Ok(report)
+}
+
+// Every device with a credential is polled through the first of its
+// addresses that answers
+pub async fn run(
+    db: Arc<Mutex<RepositoryInjection<Postgres>>>,
+    every: Duration,
+    settings: Arc<Settings>,
+) {
+    let mut interval = tokio::time::interval(every);
+
+    loop {
+        interval.tick().await;
+        let views = match db.lock().await.get::<DeviceView>(None).await {
+            Ok(e) => e,
+            Err(RepositoryError::RowNotFound) => continue,
+            Err(e) => {
+                tracing::error!("SNMP: {}", e);
+                continue;
+            }
+        };
+        let mut polled = HashSet::new();
+
+        for view in views {
+            let (Some(device), Some(credential)) = (view.device(), view.credential.clone()) else {
+                continue;
+            };
+            if polled.contains(&device.id) {
+                continue;
+            }
+
+            let system = match poll(view.ip, &credential, &settings).await {
+                Ok(e) => e,
+                Err(e) => {
+                    tracing::warn!("SNMP: {}: {}", view.ip, e);
+                    continue;
+                }
+            };
+            polled.insert(device.id);
+            match apply(&*db.lock().await, None, &view, device, system).await {
+                Ok(e) => tracing::info!(
+                    "SNMP: {} added {} and updated {} addresses",
+                    view.ip,
+                    e.created,
+                    e.updated
+                ),
+                Err(e) => tracing::error!("SNMP: {}: {}", view.ip, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    database::{
+        repository::{Repository, TypeTable},
+        RepositoryInjection,
+    },
+    models::{
+        audit::Audit,
+        device::{Address, DeviceView, Status},
+    },
+    services::{self, ddns::Ddns},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Postgres;
+use std::{collections::HashMap, env, path::Path, sync::Arc};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+
+// Status changes a user may request; automatic changes (leases, designation,
+// expiry) don't go through these rules
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Transitions(HashMap<Status, Vec<Status>>);
+
+impl Default for Transitions {
+    fn default() -> Self {
+        use Status::*;
+
+        Self(HashMap::from([
+            (
+                Unknown,
+                vec![Reserved, Allocated, Online, Offline, Dhcp, Quarantined],
+            ),
+            (Reserved, vec![Unknown, Allocated, Quarantined]),
+            (
+                Allocated,
+                vec![Unknown, Reserved, Online, Offline, Deprecated, Quarantined],
+            ),
+            (Online, vec![Allocated, Offline, Deprecated, Quarantined]),
+            (
+                Offline,
+                vec![Unknown, Allocated, Online, Deprecated, Quarantined],
+            ),
+            (Dhcp, vec![Unknown, Online, Offline, Quarantined]),
+            (Deprecated, vec![Unknown, Quarantined]),
+            (Quarantined, vec![Unknown, Allocated]),
+        ]))
+    }
+}
+
+impl Transitions {
+    pub fn allows(&self, from: &Status, to: &Status) -> bool {
+        from == to || self.allowed(from).contains(to)
+    }
+
+    pub fn allowed(&self, from: &Status) -> &[Status] {
+        self.0.get(from).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    // A json file like {"Unknown": ["Reserved"], ...}
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+// STATUS_TRANSITIONS points to the rules file, the defaults apply without it
+pub fn transitions() -> Result<Arc<Transitions>, Box<dyn std::error::Error>> {
+    match env::var("STATUS_TRANSITIONS")
+        .ok()
+        .filter(|x| !x.is_empty())
+    {
+        Some(path) => Ok(Arc::new(Transitions::load(Path::new(&path))?)),
+        None => Ok(Arc::new(Transitions::default())),
+    }
+}
+
+// Reservations past their reserved_until go back to free addresses. The
+// database is only locked while reading them and opening the transaction
+pub async fn expire(
+    db: Arc<Mutex<RepositoryInjection<Postgres>>>,
+    ddns: Ddns,
+    every: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(every);
+
+    loop {
+        interval.tick().await;
+        let now = OffsetDateTime::now_utc();
+        let expired: Vec<DeviceView> = db
+            .lock()
+            .await
+            .get::<DeviceView>(Some(HashMap::from([("status", Status::Reserved.into())])))
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|x| x.reserved_until.is_some_and(|x| x <= now))
+            .collect();
+        if expired.is_empty() {
+            continue;
+        }
+
+        if let Err(e) = release(&db, &ddns, &expired).await {
+            tracing::error!("Reservation expiry: {}", e);
+        }
+    }
+}
+
+async fn release(
+    db: &Mutex<RepositoryInjection<Postgres>>,
+    ddns: &Ddns,
+    expired: &[DeviceView],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = db.lock().await.transaction().await?;
+    let mut audit = Vec::new();
+    let mut released = Vec::new();
+
+    for before in expired {
+        let address = Address {
+            last_seen: before.last_seen,
+            ..Address::free(before.ip, before.network_id)
+        };
+        let after = DeviceView::new(address.clone(), None);
+        // A reservation changed since it was read is left alone
+        let key: HashMap<&'static str, TypeTable> = HashMap::from([
+            ("ip", before.ip.into()),
+            ("network_id", before.network_id.into()),
+            ("status", Status::Reserved.into()),
+            ("reserved_until", before.reserved_until.into()),
+        ]);
+
+        tx.update::<Address, _>(address, Some(key));
+        audit.push(Audit::update(None, before, &after));
+        services::ip_history::record(&mut tx, Some(before), Some(&after));
+        released.push(after);
+    }
+    tx.insert(audit);
+    tx.execute().await?;
+
+    for (before, after) in expired.iter().zip(&released) {
+        services::ddns::publish(&*db.lock().await, ddns, Some(before), Some(after)).await;
+        tracing::info!("Reservation of {} expired", before.ip);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Status, Transitions};
+
+    #[test]
+    fn default_transitions() {
+        let transitions = Transitions::default();
+
+        assert!(transitions.allows(&Status::Unknown, &Status::Reserved));
+        assert!(transitions.allows(&Status::Reserved, &Status::Allocated));
+        assert!(transitions.allows(&Status::Online, &Status::Offline));
+        assert!(transitions.allows(&Status::Quarantined, &Status::Unknown));
+        assert_eq!(
+            transitions.allowed(&Status::Deprecated),
+            [Status::Unknown, Status::Quarantined]
+        );
+    }
+
+    #[test]
+    fn default_transitions_reject_illegal() {
+        let transitions = Transitions::default();
+
+        assert!(!transitions.allows(&Status::Deprecated, &Status::Online));
+        assert!(!transitions.allows(&Status::Reserved, &Status::Online));
+        assert!(!transitions.allows(&Status::Quarantined, &Status::Dhcp));
+    }
+
+    #[test]
+    fn same_status_is_always_allowed() {
+        let transitions: Transitions = serde_json::from_str("{}").unwrap();
+
+        assert!(transitions.allows(&Status::Online, &Status::Online));
+        assert!(!transitions.allows(&Status::Online, &Status::Offline));
+    }
+
+    #[test]
+    fn transitions_from_file() {
+        let path = std::env::temp_dir().join(format!("transitions-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"Unknown": ["Reserved", "DHCP"], "DHCP": []}"#).unwrap();
+
+        let transitions = Transitions::load(&path).unwrap();
+        std::fs::write(&path, r#"{"Unknown": ["Retired"]}"#).unwrap();
+        let unknown_status = Transitions::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(transitions.allows(&Status::Unknown, &Status::Reserved));
+        assert!(transitions.allows(&Status::Unknown, &Status::Dhcp));
+        assert!(!transitions.allows(&Status::Unknown, &Status::Online));
+        assert!(!transitions.allows(&Status::Dhcp, &Status::Unknown));
+        assert!(transitions.allowed(&Status::Online).is_empty());
+        assert!(unknown_status.is_err());
+    }
+}
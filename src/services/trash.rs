@@ -0,0 +1,41 @@
+use crate::{
+    database::RepositoryInjection,
+    models::{
+        device::{Address, Device},
+        network::Network,
+        office::Office,
+    },
+};
+use sqlx::Postgres;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tokio::sync::Mutex;
+
+pub async fn purge(
+    db: Arc<Mutex<RepositoryInjection<Postgres>>>,
+    retention: Duration,
+    every: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(every);
+
+    loop {
+        interval.tick().await;
+        let before = OffsetDateTime::now_utc() - retention;
+        let db = db.lock().await;
+
+        let resp = [
+            db.purge::<Address>(before).await,
+            db.purge::<Device>(before).await,
+            db.purge::<Network>(before).await,
+            db.purge::<Office>(before).await,
+        ];
+
+        for i in resp {
+            match i {
+                Ok(e) if e > 0 => tracing::info!("Trash purge: {} rows deleted", e),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Trash purge: {}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository},
+        RepositoryInjection,
+    },
+    models::{
+        user::Role,
+        webhook::{Delivery, Webhook},
+    },
+    services::events,
+};
+use libipam::ipam_services::webhook::{self, DELIVERY, EVENT};
+use sqlx::Postgres;
+use std::{collections::HashMap, env, sync::Arc, time::Duration};
+use time::OffsetDateTime;
+use tokio::sync::{broadcast::error::RecvError, Mutex};
+
+type Db = Arc<Mutex<RepositoryInjection<Postgres>>>;
+
+#[derive(Debug, Clone)]
+pub struct Dispatcher {
+    client: reqwest::Client,
+    attempts: u32,
+    backoff: Duration,
+}
+
+// WEBHOOK_BACKOFF_MS doubles after every failed attempt
+pub fn dispatcher() -> Result<Dispatcher, Box<dyn std::error::Error>> {
+    let var = |name: &str, default: u64| {
+        env::var(name)
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(default)
+    };
+
+    Ok(Dispatcher {
+        client: reqwest::Client::builder()
+            .timeout(Duration::from_millis(var("WEBHOOK_TIMEOUT_MS", 5000)))
+            .build()?,
+        attempts: var("WEBHOOK_ATTEMPTS", 5).max(1) as u32,
+        backoff: Duration::from_millis(var("WEBHOOK_BACKOFF_MS", 1000)),
+    })
+}
+
+// Timeouts and rate limits are worth retrying, other client errors aren't
+fn retryable(status: u16) -> bool {
+    !(400..500).contains(&status) || status == 408 || status == 429
+}
+
+impl Dispatcher {
+    // Every attempt is written to the delivery log
+    pub async fn deliver(self, db: Db, webhook: Webhook, mut delivery: Delivery) {
+        let body = serde_json::to_vec(&delivery.event).unwrap_or_default();
+        let headers = [
+            (EVENT, delivery.event_name()),
+            (DELIVERY, delivery.id.to_string()),
+        ];
+
+        for attempt in 0..self.attempts {
+            if attempt > 0 {
+                tokio::time::sleep(self.backoff * 2u32.saturating_pow(attempt - 1)).await;
+            }
+
+            let resp = webhook::send(
+                &self.client,
+                &webhook.url,
+                &webhook.secret,
+                &headers,
+                body.clone(),
+            )
+            .await;
+            delivery.attempts += 1;
+            delivery.last_attempt = Some(OffsetDateTime::now_utc());
+            let retry = match resp {
+                Ok(e) => {
+                    delivery.status = Some(e as i64);
+                    delivery.delivered = (200..300).contains(&e);
+                    delivery.error =
+                        (!delivery.delivered).then(|| format!("The receiver answered {}", e));
+                    !delivery.delivered && retryable(e)
+                }
+                Err(e) => {
+                    delivery.error = Some(e);
+                    true
+                }
+            };
+
+            let key = HashMap::from([("id", delivery.id.into())]);
+            if let Err(e) = db
+                .lock()
+                .await
+                .update::<Delivery, _>(delivery.clone(), Some(key))
+                .await
+            {
+                tracing::error!("Webhook {}: {}", webhook.id, e);
+            }
+            if !retry {
+                break;
+            }
+        }
+
+        if !delivery.delivered {
+            tracing::warn!(
+                "Webhook {}: delivery {} failed after {} attempts",
+                webhook.id,
+                delivery.id,
+                delivery.attempts
+            );
+        }
+    }
+
+    pub async fn dispatch(
+        &self,
+        db: &Db,
+        webhook: Webhook,
+        delivery: Delivery,
+    ) -> Result<(), RepositoryError> {
+        db.lock().await.insert(vec![delivery.clone()]).await?;
+        tokio::spawn(self.clone().deliver(db.clone(), webhook, delivery));
+
+        Ok(())
+    }
+}
+
+// Receivers get the same redacted events as an operator on /events
+pub async fn run(db: Db, dispatcher: Dispatcher) {
+    let mut rx = events::subscribe();
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(e) => e,
+            Err(RecvError::Lagged(e)) => {
+                tracing::warn!("Webhooks: {} events were dropped", e);
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        let Some(event) = events::redact(event, &Role::Operator) else {
+            continue;
+        };
+
+        let webhooks = match db
+            .lock()
+            .await
+            .get::<Webhook>(Some(HashMap::from([("enabled", true.into())])))
+            .await
+        {
+            Ok(e) => e,
+            Err(RepositoryError::RowNotFound) => continue,
+            Err(e) => {
+                tracing::error!("Webhooks: {}", e);
+                continue;
+            }
+        };
+
+        for webhook in webhooks.into_iter().filter(|x| x.matches(&event)) {
+            let delivery =
+                Delivery::new(webhook.id, serde_json::to_value(&event).unwrap_or_default());
+            if let Err(e) = dispatcher.dispatch(&db, webhook, delivery).await {
+                tracing::error!("Webhooks: {}", e);
+            }
+        }
+    }
+}